@@ -20,12 +20,14 @@ extern crate rand;
 extern crate rsgenetic;
 
 use rand::distributions::{IndependentSample, Range};
+use rand::Rng;
 use rsgenetic::pheno::*;
 use rsgenetic::sim::select::*;
 use rsgenetic::sim::seq::Simulator;
 use rsgenetic::sim::*;
 use std::cmp::Ordering;
 
+#[derive(Clone)]
 struct MyFitness {
     f: f64,
 }
@@ -40,13 +42,13 @@ impl PartialEq for MyFitness {
 
 impl PartialOrd for MyFitness {
     fn partial_cmp(&self, other: &MyFitness) -> Option<Ordering> {
-        self.f.partial_cmp(&other.f)
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for MyFitness {
     fn cmp(&self, other: &MyFitness) -> Ordering {
-        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+        self.f.partial_cmp(&other.f).unwrap_or(Ordering::Equal)
     }
 }
 
@@ -82,15 +84,17 @@ impl Phenotype<MyFitness> for MyData {
     }
 
     fn mutate(&self) -> MyData {
-        // Shift x with a random number.
-        // (This RNG code should reside somewhere else, not in this function, but it's just an
-        // example).
+        self.mutate_with_rng(&mut rand::thread_rng())
+    }
+
+    fn mutate_with_rng(&self, mut rng: &mut dyn Rng) -> MyData {
+        // Shift x with a random number, drawn from the random number generator
+        // of the simulator so that seeded runs are reproducible.
 
         // Because we don't want to have too big mutations, we limit the range to -1, +1.
         // Smaller values can cause slower convergence, but larger values may cause completely
         // wrong values.
         let between = Range::new(-1.0, 1.0);
-        let mut rng = rand::thread_rng();
         let offset = between.ind_sample(&mut rng);
         MyData { x: self.x + offset }
     }
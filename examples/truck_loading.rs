@@ -56,7 +56,7 @@ impl Phenotype<SchemeFitness> for LoadingScheme {
             let space_left = CAPACITY - load;
             if space_left < 0 {
                 // We have overfilled a truck: penalize this solution heavily.
-                return i32::min_value();
+                return i32::MIN;
             }
             if space_left == CAPACITY {
                 // We have an empty truck: give this solution a little boost.
@@ -73,30 +73,36 @@ impl Phenotype<SchemeFitness> for LoadingScheme {
     }
 
     fn crossover(&self, other: &LoadingScheme) -> LoadingScheme {
+        self.crossover_with_rng(other, &mut ::rand::thread_rng())
+    }
+
+    fn mutate(&self) -> LoadingScheme {
+        self.mutate_with_rng(&mut ::rand::thread_rng())
+    }
+
+    fn crossover_with_rng(&self, other: &LoadingScheme, mut rng: &mut dyn Rng) -> LoadingScheme {
+        // `Rng::gen` cannot be called on a trait object, but it can on a reference to one.
+        let rng = &mut rng;
         // 2-way crossover
-        let mut rng = ::rand::thread_rng();
         let crossover_indices = (
             rng.gen::<usize>() % PACKAGES.len(),
             rng.gen::<usize>() % PACKAGES.len(),
         );
         let mut crossed_over: Scheme = vec![(0, 0); PACKAGES.len()];
-        for i in 0..crossover_indices.0 {
-            crossed_over[i] = self.scheme[i];
-        }
-        for i in crossover_indices.0..crossover_indices.1 {
-            crossed_over[i] = other.scheme[i];
-        }
-        for i in crossover_indices.1..PACKAGES.len() {
-            crossed_over[i] = self.scheme[i];
+        let (start, end) = crossover_indices;
+        crossed_over[..start].copy_from_slice(&self.scheme[..start]);
+        if start < end {
+            crossed_over[start..end].copy_from_slice(&other.scheme[start..end]);
         }
+        crossed_over[end..].copy_from_slice(&self.scheme[end..]);
         LoadingScheme {
             scheme: crossed_over,
         }
     }
 
-    fn mutate(&self) -> LoadingScheme {
+    fn mutate_with_rng(&self, mut rng: &mut dyn Rng) -> LoadingScheme {
+        let rng = &mut rng;
         // Put some stuff on other trucks
-        let mut rng = ::rand::thread_rng();
         LoadingScheme {
             scheme: self
                 .scheme
@@ -0,0 +1,399 @@
+// file: analysis.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides tools to analyse a problem and the operators used to solve it,
+//! independently of a running `Simulation`.
+//!
+//! All functions here work on a sample population, which is typically the
+//! initial population you would pass to a `Simulator`. Most of them require a
+//! `NumericFitness`, because they compute correlations between fitness values.
+//!
+//! * `fitness_distance_correlation` measures how well fitness predicts the distance
+//!   to a known optimum.
+//! * `mutation_locality` and `crossover_locality` measure how strongly the fitness
+//!   of a child correlates with the fitness of its parent(s). Operators with high
+//!   locality make small, predictable steps through the search space.
+//! * `takeover_time` and `selection_intensity` quantify the selection pressure of any
+//!   `Selector`, so custom selectors can be compared against the built-in ones.
+//!
+//! To compare fitness trajectories, such as `RunReport::history`, across runs or
+//! problems with different fitness scales, they can be normalized with
+//! `min_max_normalize`, `z_score_normalize` or, if the optimum is known,
+//! `fraction_of_optimum`.
+
+use error::Error;
+use pheno::{Fitness, NumericFitness, Phenotype};
+use sim::select::{SelectionContext, Selector};
+
+/// Calculate the Pearson correlation coefficient of a list of pairs.
+///
+/// Returns `None` if there are fewer than two pairs or if either
+/// variable has zero variance.
+fn pearson(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < 2 {
+        return None;
+    }
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+    let mut cov = 0.0;
+    let mut var_x = 0.0;
+    let mut var_y = 0.0;
+    for &(x, y) in pairs {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x) * (x - mean_x);
+        var_y += (y - mean_y) * (y - mean_y);
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        None
+    } else {
+        Some(cov / (var_x.sqrt() * var_y.sqrt()))
+    }
+}
+
+/// Calculate the fitness-distance correlation of a sample population.
+///
+/// `distance` should return the distance of a phenotype to the (known) global optimum.
+/// For a maximization problem, a value close to `-1` indicates that fitness guides the
+/// search well towards the optimum, while values close to `0` or positive values indicate
+/// a difficult or deceptive problem.
+///
+/// Returns `None` if the sample contains fewer than two phenotypes, or if all fitness
+/// values or all distances are equal.
+pub fn fitness_distance_correlation<T, F, D>(sample: &[T], mut distance: D) -> Option<f64>
+where
+    T: Phenotype<F>,
+    F: NumericFitness,
+    D: FnMut(&T) -> f64,
+{
+    let pairs: Vec<(f64, f64)> = sample
+        .iter()
+        .map(|x| (x.fitness().to_f64(), distance(x)))
+        .collect();
+    pearson(&pairs)
+}
+
+/// Measure the locality of a mutation operator on a sample population.
+///
+/// Every phenotype in `sample` is mutated once using `mutate`, which can simply be
+/// `|x| x.mutate()` or any other mutation operator. The result is the correlation
+/// between the fitness of each parent and the fitness of its child.
+///
+/// Returns `None` if the sample contains fewer than two phenotypes, or if all parent
+/// or all child fitness values are equal.
+pub fn mutation_locality<T, F, M>(sample: &[T], mut mutate: M) -> Option<f64>
+where
+    T: Phenotype<F>,
+    F: NumericFitness,
+    M: FnMut(&T) -> T,
+{
+    let pairs: Vec<(f64, f64)> = sample
+        .iter()
+        .map(|x| (x.fitness().to_f64(), mutate(x).fitness().to_f64()))
+        .collect();
+    pearson(&pairs)
+}
+
+/// Measure the locality of a crossover operator on a sample population.
+///
+/// Each phenotype in `sample` is crossed with its successor (wrapping around at the end)
+/// using `crossover`, which can simply be `|a, b| a.crossover(b)`. The result is the
+/// correlation between the mean fitness of the parents and the fitness of the child.
+///
+/// Returns `None` if the sample contains fewer than two phenotypes, or if all mean parent
+/// or all child fitness values are equal.
+pub fn crossover_locality<T, F, C>(sample: &[T], mut crossover: C) -> Option<f64>
+where
+    T: Phenotype<F>,
+    F: NumericFitness,
+    C: FnMut(&T, &T) -> T,
+{
+    let pairs: Vec<(f64, f64)> = sample
+        .iter()
+        .zip(sample.iter().cycle().skip(1))
+        .map(|(a, b)| {
+            let parents = (a.fitness().to_f64() + b.fitness().to_f64()) / 2.0;
+            (parents, crossover(a, b).fitness().to_f64())
+        })
+        .collect();
+    pearson(&pairs)
+}
+
+/// Measure the takeover time of a selector.
+///
+/// Starting from `population`, new generations are created using selection only:
+/// the selected parents are copied into the next generation until it has the same
+/// size as the current one. The takeover time is the number of generations it takes
+/// until every phenotype has the fitness of the best phenotype of the initial population.
+/// Lower takeover times indicate a higher selection pressure.
+///
+/// For meaningful results, the best fitness value should occur only once in `population`.
+///
+/// Returns `Ok(None)` if takeover did not happen within `max_generations` generations,
+/// or an error if the selector fails.
+pub fn takeover_time<T, F, S>(
+    selector: &S,
+    population: &[T],
+    max_generations: u64,
+) -> Result<Option<u64>, Error>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+    S: Selector<T, F> + ?Sized,
+{
+    let best = match population.iter().map(|x| x.fitness()).max() {
+        Some(best) => best,
+        None => return Err(Error::EmptyPopulation),
+    };
+    let mut current: Vec<T> = population.to_vec();
+    let mut rng = ::rand::thread_rng();
+    for generation in 1..=max_generations {
+        let fitness: Vec<F> = current.iter().map(|x| x.fitness()).collect();
+        let mut next: Vec<T> = Vec::with_capacity(current.len());
+        while next.len() < current.len() {
+            let context = SelectionContext::new(&current, &fitness, generation - 1, &mut rng);
+            let parents = selector.select_from(context)?;
+            if parents.is_empty() {
+                return Err(Error::SelectorFailure(String::from(
+                    "The selector did not select any parents.",
+                )));
+            }
+            for (a, b) in parents {
+                next.push(a.clone());
+                next.push(b.clone());
+            }
+        }
+        next.truncate(current.len());
+        current = next;
+        if current.iter().all(|x| x.fitness() == best) {
+            return Ok(Some(generation));
+        }
+    }
+    Ok(None)
+}
+
+/// Measure the selection intensity of a selector on a population.
+///
+/// The selection intensity is the difference between the mean fitness of the selected
+/// parents and the mean fitness of the population, divided by the standard deviation
+/// of the population's fitness. Higher values indicate a higher selection pressure.
+///
+/// Returns an error if the selector fails, or if all phenotypes in `population` have
+/// the same fitness.
+pub fn selection_intensity<T, F, S>(selector: &S, population: &[T]) -> Result<f64, Error>
+where
+    T: Phenotype<F>,
+    F: NumericFitness,
+    S: Selector<T, F> + ?Sized,
+{
+    let parents = selector.select(population)?;
+    if parents.is_empty() {
+        return Err(Error::SelectorFailure(String::from(
+            "The selector did not select any parents.",
+        )));
+    }
+    let n = population.len() as f64;
+    let mean = population.iter().map(|x| x.fitness().to_f64()).sum::<f64>() / n;
+    let variance = population
+        .iter()
+        .map(|x| {
+            let d = x.fitness().to_f64() - mean;
+            d * d
+        })
+        .sum::<f64>()
+        / n;
+    if variance == 0.0 {
+        return Err(Error::UndefinedMeasure(String::from(
+            "Cannot measure selection intensity when all fitness values are equal.",
+        )));
+    }
+    let selected_mean = parents
+        .iter()
+        .map(|&(a, b)| a.fitness().to_f64() + b.fitness().to_f64())
+        .sum::<f64>()
+        / (2 * parents.len()) as f64;
+    Ok((selected_mean - mean) / variance.sqrt())
+}
+
+/// Scale fitness values linearly to the range `[0, 1]`.
+///
+/// The lowest value is mapped to `0` and the highest to `1`. To compare several runs
+/// on the same scale, normalize their concatenated trajectories at once.
+///
+/// Returns `None` if `values` is empty or if all values are equal.
+pub fn min_max_normalize<F>(values: &[F]) -> Option<Vec<f64>>
+where
+    F: NumericFitness,
+{
+    let values: Vec<f64> = values.iter().map(NumericFitness::to_f64).collect();
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if values.is_empty() || min == max {
+        None
+    } else {
+        Some(values.iter().map(|v| (v - min) / (max - min)).collect())
+    }
+}
+
+/// Standardize fitness values to a mean of `0` and a standard deviation of `1`.
+///
+/// Returns `None` if `values` is empty or if all values are equal.
+pub fn z_score_normalize<F>(values: &[F]) -> Option<Vec<f64>>
+where
+    F: NumericFitness,
+{
+    if values.is_empty() {
+        return None;
+    }
+    let values: Vec<f64> = values.iter().map(NumericFitness::to_f64).collect();
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n;
+    if variance == 0.0 {
+        None
+    } else {
+        let sd = variance.sqrt();
+        Some(values.iter().map(|v| (v - mean) / sd).collect())
+    }
+}
+
+/// Express fitness values as a fraction of a known optimum.
+///
+/// A value of `1` means the optimum was reached. This is only meaningful if
+/// all fitness values have the same sign as the optimum, e.g. for a maximization
+/// problem with non-negative fitness.
+///
+/// Returns `None` if the optimum is zero.
+pub fn fraction_of_optimum<F>(values: &[F], optimum: &F) -> Option<Vec<f64>>
+where
+    F: NumericFitness,
+{
+    let optimum = optimum.to_f64();
+    if optimum == 0.0 {
+        None
+    } else {
+        Some(values.iter().map(|v| v.to_f64() / optimum).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sim::select::*;
+    use test::{MyFitness, Test};
+
+    fn fitness(values: &[i64]) -> Vec<MyFitness> {
+        values.iter().map(|&f| MyFitness { f }).collect()
+    }
+
+    #[test]
+    fn test_fdc_perfect() {
+        let sample: Vec<Test> = (1..50).map(|i| Test { f: i }).collect();
+        // The optimum of `Test` lies at 0, with fitness 0, so fitness grows with distance.
+        let fdc = fitness_distance_correlation(&sample, |x| x.f.abs() as f64).unwrap();
+        assert!((fdc - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fdc_too_small() {
+        let sample = vec![Test { f: 1 }];
+        assert!(fitness_distance_correlation(&sample, |x| x.f as f64).is_none());
+    }
+
+    #[test]
+    fn test_mutation_locality() {
+        let sample: Vec<Test> = (1..50).map(|i| Test { f: i }).collect();
+        let locality = mutation_locality(&sample, |x| x.mutate()).unwrap();
+        assert!((locality - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_mutation_locality_constant() {
+        let sample: Vec<Test> = (1..50).map(|i| Test { f: i }).collect();
+        assert!(mutation_locality(&sample, |_| Test { f: 0 }).is_none());
+    }
+
+    #[test]
+    fn test_crossover_locality() {
+        let sample: Vec<Test> = (1..50).map(|i| Test { f: i }).collect();
+        let locality = crossover_locality(&sample, |a, b| a.crossover(b)).unwrap();
+        assert!(locality > 0.0);
+    }
+
+    #[test]
+    fn test_takeover_time_maximize() {
+        let selector = UnstableMaximizeSelector::new(2);
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        // The first generation consists of copies of the two best phenotypes,
+        // the second one only of copies of the best.
+        assert_eq!(takeover_time(&selector, &population, 10), Ok(Some(2)));
+    }
+
+    #[test]
+    fn test_takeover_time_limit() {
+        let selector = UnstableMaximizeSelector::new(2);
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        assert_eq!(takeover_time(&selector, &population, 1), Ok(None));
+    }
+
+    #[test]
+    fn test_takeover_time_selector_error() {
+        let selector = UnstableMaximizeSelector::new(0);
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        assert!(takeover_time(&selector, &population, 10).is_err());
+    }
+
+    #[test]
+    fn test_selection_intensity() {
+        let maximize = UnstableMaximizeSelector::new(20);
+        let stochastic = StochasticSelector::new(20);
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let max_intensity = selection_intensity(&maximize, &population).unwrap();
+        let sus_intensity = selection_intensity(&stochastic, &population).unwrap();
+        assert!(max_intensity > 1.0);
+        assert!(max_intensity > sus_intensity);
+    }
+
+    #[test]
+    fn test_selection_intensity_equal_fitness() {
+        let selector = UnstableMaximizeSelector::new(20);
+        let population: Vec<Test> = (0..100).map(|_| Test { f: 3 }).collect();
+        assert!(selection_intensity(&selector, &population).is_err());
+    }
+
+    #[test]
+    fn test_min_max_normalize() {
+        let normalized = min_max_normalize(&fitness(&[2, 4, 6])).unwrap();
+        assert_eq!(normalized, vec![0.0, 0.5, 1.0]);
+        assert!(min_max_normalize(&fitness(&[3, 3])).is_none());
+        assert!(min_max_normalize::<MyFitness>(&[]).is_none());
+    }
+
+    #[test]
+    fn test_z_score_normalize() {
+        let normalized = z_score_normalize(&fitness(&[1, 3])).unwrap();
+        assert_eq!(normalized, vec![-1.0, 1.0]);
+        assert!(z_score_normalize(&fitness(&[3, 3])).is_none());
+        assert!(z_score_normalize::<MyFitness>(&[]).is_none());
+    }
+
+    #[test]
+    fn test_fraction_of_optimum() {
+        let fractions = fraction_of_optimum(&fitness(&[5, 10]), &MyFitness { f: 20 }).unwrap();
+        assert_eq!(fractions, vec![0.25, 0.5]);
+        assert!(fraction_of_optimum(&fitness(&[5]), &MyFitness { f: 0 }).is_none());
+    }
+}
@@ -0,0 +1,95 @@
+// file: error.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains the `Error` type returned by selectors, simulators and the tools built on them.
+//!
+//! Every error carries a human-readable message, but code that handles errors
+//! should match on the variant instead of the message, which may change.
+
+use std::error;
+use std::fmt;
+
+/// An error of a selector, a simulator or a tool built on them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The parameters of a selector are invalid, e.g. a `count` that is not a multiple of two.
+    InvalidSelectorParams(String),
+    /// The population is empty.
+    EmptyPopulation,
+    /// A selector failed for another reason than its parameters.
+    SelectorFailure(String),
+    /// A setting of a simulator or runner is invalid, e.g. a mutation rate larger than one.
+    InvalidConfig(String),
+    /// A checkpoint or the best individual could not be saved.
+    Io(String),
+    /// The thread pool of a parallel simulator could not be built.
+    ThreadPool(String),
+    /// The thread running a simulation panicked.
+    ThreadPanicked,
+    /// The breeding trace of a child is not available.
+    TraceUnavailable(String),
+    /// An analysis measure is undefined for its input, e.g. because all fitness values are equal.
+    UndefinedMeasure(String),
+    /// A run does not match a `stats::RunDigest`. Contains the first generation that differs.
+    DigestMismatch(usize),
+    /// A `stats::RunDigest` could not be read from a string.
+    InvalidDigest(String),
+    /// A problem instance could not be read from a string, e.g. a graph in the DIMACS format.
+    Parse(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidSelectorParams(ref message)
+            | Error::SelectorFailure(ref message)
+            | Error::InvalidConfig(ref message)
+            | Error::TraceUnavailable(ref message)
+            | Error::UndefinedMeasure(ref message) => f.write_str(message),
+            Error::EmptyPopulation => f.write_str("The population is empty."),
+            Error::Io(ref message) => write!(f, "I/O error: {}", message),
+            Error::ThreadPool(ref message) => {
+                write!(f, "Could not build a thread pool: {}", message)
+            }
+            Error::ThreadPanicked => f.write_str("The simulation thread panicked."),
+            Error::DigestMismatch(generation) => write!(
+                f,
+                "The run differs from the digest in generation {}.",
+                generation
+            ),
+            Error::InvalidDigest(ref message) => write!(f, "Invalid digest: {}", message),
+            Error::Parse(ref message) => write!(f, "Parse error: {}", message),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Error::EmptyPopulation.to_string(),
+            "The population is empty."
+        );
+        let e = Error::InvalidConfig(String::from("Invalid mutation rate: 2."));
+        assert_eq!(e.to_string(), "Invalid mutation rate: 2.");
+    }
+}
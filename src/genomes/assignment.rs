@@ -0,0 +1,349 @@
+// file: assignment.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains an assignment problem with time windows, as a template for scheduling.
+//!
+//! Every task has a duration and a set of resources it may be assigned to, e.g. employees
+//! or machines. Every resource is only available during some time windows. A task must
+//! be processed in one piece, within a single window of its resource.
+//!
+//! An `Assignment` is a mixed genome: for every task, it holds the resource the task is
+//! assigned to and a priority. `AssignmentProblem::plan` turns it into a `Plan` by
+//! scheduling the tasks of every resource in order of decreasing priority, each as early
+//! as possible. Tasks that are assigned to a resource they may not use, or that do not
+//! fit in any window, are left unscheduled. A fitness function typically minimizes the
+//! number of unscheduled tasks first, and the makespan second.
+//!
+//! The built-in crossover operators can be used on assignments, since every gene is
+//! valid at every position. `AssignmentMutation` changes both parts of a gene.
+
+use pheno::Genome;
+use rand::distributions::{IndependentSample, Normal};
+use rand::Rng;
+use sim::mutation::MutationOperator;
+
+/// A task that must be processed by a single resource.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Task {
+    /// The time needed to process the task.
+    pub duration: u64,
+    /// The indices of the resources that may process the task.
+    pub resources: Vec<usize>,
+}
+
+/// A resource that can process one task at a time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Resource {
+    /// The time windows `(start, end)` during which the resource is available, in
+    /// increasing order. A window includes its start, but not its end.
+    pub windows: Vec<(u64, u64)>,
+}
+
+/// An assignment problem with time windows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssignmentProblem {
+    tasks: Vec<Task>,
+    resources: Vec<Resource>,
+}
+
+impl AssignmentProblem {
+    /// Create a problem from its tasks and resources.
+    pub fn new(tasks: Vec<Task>, resources: Vec<Resource>) -> AssignmentProblem {
+        AssignmentProblem { tasks, resources }
+    }
+
+    /// Get the tasks.
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
+    /// Get the resources.
+    pub fn resources(&self) -> &[Resource] {
+        &self.resources
+    }
+
+    /// Create a random assignment, in which every task is assigned to one of the
+    /// resources it may use, with a random priority between zero and one.
+    pub fn random_assignment<R: Rng>(&self, rng: &mut R) -> Assignment {
+        Assignment::new(
+            self.tasks
+                .iter()
+                .map(|task| Allocation {
+                    resource: rng.choose(&task.resources).cloned().unwrap_or(0),
+                    priority: rng.gen(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Schedule the tasks as described by `assignment`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `assignment` does not have an allocation for every task.
+    pub fn plan(&self, assignment: &Assignment) -> Plan {
+        assert_eq!(
+            assignment.allocations.len(),
+            self.tasks.len(),
+            "Every task needs an allocation."
+        );
+        let mut starts = vec![None; self.tasks.len()];
+        let mut order: Vec<usize> = (0..self.tasks.len()).collect();
+        order.sort_by(|&a, &b| {
+            let (a, b) = (&assignment.allocations[a], &assignment.allocations[b]);
+            b.priority.total_cmp(&a.priority)
+        });
+        let mut available = vec![0; self.resources.len()];
+        for i in order {
+            let task = &self.tasks[i];
+            let r = assignment.allocations[i].resource;
+            if !task.resources.contains(&r) || r >= self.resources.len() {
+                continue;
+            }
+            let window = self.resources[r].windows.iter().find_map(|&(start, end)| {
+                let start = start.max(available[r]);
+                if start + task.duration <= end {
+                    Some(start)
+                } else {
+                    None
+                }
+            });
+            if let Some(start) = window {
+                starts[i] = Some(start);
+                available[r] = start + task.duration;
+            }
+        }
+        let makespan = starts
+            .iter()
+            .zip(&self.tasks)
+            .filter_map(|(s, t)| s.map(|s| s + t.duration))
+            .max()
+            .unwrap_or(0);
+        Plan { starts, makespan }
+    }
+}
+
+/// The resource and priority of a single task.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Allocation {
+    /// The index of the resource the task is assigned to.
+    pub resource: usize,
+    /// The priority of the task. Tasks with a higher priority are scheduled first.
+    pub priority: f64,
+}
+
+/// An allocation for every task of an `AssignmentProblem`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Assignment {
+    allocations: Vec<Allocation>,
+}
+
+impl Assignment {
+    /// Create an assignment from the allocation of every task.
+    pub fn new(allocations: Vec<Allocation>) -> Assignment {
+        Assignment { allocations }
+    }
+
+    /// Get the allocation of every task.
+    pub fn allocations(&self) -> &[Allocation] {
+        &self.allocations
+    }
+}
+
+impl Genome for Assignment {
+    type Gene = Allocation;
+
+    fn genes(&self) -> &[Allocation] {
+        &self.allocations
+    }
+
+    fn with_genes(&self, genes: Vec<Allocation>) -> Assignment {
+        Assignment::new(genes)
+    }
+}
+
+/// The schedule that results from an `Assignment`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Plan {
+    starts: Vec<Option<u64>>,
+    makespan: u64,
+}
+
+impl Plan {
+    /// Get the start time of every task, or `None` if it could not be scheduled.
+    pub fn starts(&self) -> &[Option<u64>] {
+        &self.starts
+    }
+
+    /// Get the number of tasks that could not be scheduled.
+    pub fn unscheduled(&self) -> usize {
+        self.starts.iter().filter(|s| s.is_none()).count()
+    }
+
+    /// Get the time at which the last scheduled task finishes.
+    pub fn makespan(&self) -> u64 {
+        self.makespan
+    }
+}
+
+/// Mutates every allocation of an `Assignment` with a given probability.
+///
+/// A mutated allocation either gets a random resource among those its task may use,
+/// or normally distributed noise with standard deviation `sigma` added to its priority.
+#[derive(Clone, Debug)]
+pub struct AssignmentMutation {
+    problem: AssignmentProblem,
+    probability: f64,
+    sigma: f64,
+}
+
+impl AssignmentMutation {
+    /// Create and return an operator for assignments of `problem` that mutates every
+    /// allocation with probability `probability`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` is not between zero and one, inclusive.
+    pub fn new(problem: AssignmentProblem, probability: f64, sigma: f64) -> AssignmentMutation {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "Invalid mutation probability: {}. Should be between zero and one.",
+            probability
+        );
+        AssignmentMutation {
+            problem,
+            probability,
+            sigma,
+        }
+    }
+}
+
+impl MutationOperator<Assignment> for AssignmentMutation {
+    fn mutate(&self, x: &Assignment) -> Assignment {
+        self.mutate_with_rng(x, &mut ::rand::thread_rng())
+    }
+
+    fn mutate_with_rng(&self, x: &Assignment, mut rng: &mut dyn Rng) -> Assignment {
+        let rng = &mut rng;
+        let normal = Normal::new(0.0, self.sigma);
+        let allocations = x
+            .allocations
+            .iter()
+            .zip(&self.problem.tasks)
+            .map(|(&a, task)| {
+                if rng.gen::<f64>() >= self.probability {
+                    a
+                } else if rng.gen() {
+                    Allocation {
+                        resource: rng.choose(&task.resources).cloned().unwrap_or(a.resource),
+                        ..a
+                    }
+                } else {
+                    Allocation {
+                        priority: a.priority + normal.ind_sample(rng),
+                        ..a
+                    }
+                }
+            })
+            .collect();
+        Assignment::new(allocations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem() -> AssignmentProblem {
+        AssignmentProblem::new(
+            vec![
+                Task {
+                    duration: 3,
+                    resources: vec![0],
+                },
+                Task {
+                    duration: 2,
+                    resources: vec![0, 1],
+                },
+                Task {
+                    duration: 4,
+                    resources: vec![1],
+                },
+            ],
+            vec![
+                Resource {
+                    windows: vec![(0, 4), (6, 10)],
+                },
+                Resource {
+                    windows: vec![(2, 5)],
+                },
+            ],
+        )
+    }
+
+    fn allocation(resource: usize, priority: f64) -> Allocation {
+        Allocation { resource, priority }
+    }
+
+    #[test]
+    fn test_plan() {
+        let assignment = Assignment::new(vec![
+            allocation(0, 1.0),
+            allocation(0, 0.5),
+            allocation(1, 0.0),
+        ]);
+        let plan = problem().plan(&assignment);
+        // Task 1 does not fit after task 0 in the first window of resource 0.
+        // Task 2 does not fit in the only window of resource 1.
+        assert_eq!(plan.starts(), &[Some(0), Some(6), None]);
+        assert_eq!(plan.unscheduled(), 1);
+        assert_eq!(plan.makespan(), 8);
+    }
+
+    #[test]
+    fn test_priority() {
+        let assignment = Assignment::new(vec![
+            allocation(0, 0.0),
+            allocation(0, 1.0),
+            allocation(1, 0.0),
+        ]);
+        let plan = problem().plan(&assignment);
+        assert_eq!(plan.starts()[0], Some(6));
+        assert_eq!(plan.starts()[1], Some(0));
+    }
+
+    #[test]
+    fn test_forbidden_resource() {
+        let assignment = Assignment::new(vec![
+            allocation(1, 1.0),
+            allocation(1, 0.5),
+            allocation(1, 0.0),
+        ]);
+        assert_eq!(problem().plan(&assignment).starts()[0], None);
+    }
+
+    #[test]
+    fn test_mutation_keeps_resources_valid() {
+        let problem = problem();
+        let op = AssignmentMutation::new(problem.clone(), 1.0, 0.1);
+        for _ in 0..100 {
+            let mutated = op.mutate(&problem.random_assignment(&mut ::rand::thread_rng()));
+            for (a, task) in mutated.allocations().iter().zip(problem.tasks()) {
+                assert!(task.resources.contains(&a.resource));
+            }
+        }
+    }
+}
@@ -0,0 +1,426 @@
+// file: graph.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains graph coloring and vertex cover problems, and a parser for graphs in the
+//! DIMACS format.
+//!
+//! `GraphColoring` partitions the vertices into color classes, so it uses the
+//! `Grouping` genome with `GroupCrossover` and `GroupMutation`. It reinserts vertices
+//! with the first-fit heuristic, so the operators only create proper colorings.
+//!
+//! `VertexCover` uses a `Cover` with one bit per vertex, like `knapsack::Packing`, so
+//! all built-in crossover operators can be used, together with a `GeneMutation` that
+//! flips bits. Covers that leave edges uncovered are handled according to a
+//! `knapsack::ConstraintHandling`.
+
+use error::Error;
+use genomes::grouping::{Grouping, Reinsert};
+use genomes::knapsack::ConstraintHandling;
+use pheno::Genome;
+use rand::Rng;
+
+/// An undirected graph without self-loops.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Graph {
+    neighbours: Vec<Vec<usize>>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl Graph {
+    /// Create a graph with `vertices` vertices and the given edges between them.
+    ///
+    /// Self-loops and duplicate edges are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an edge refers to a vertex that does not exist.
+    pub fn new(vertices: usize, edges: &[(usize, usize)]) -> Graph {
+        let mut graph = Graph {
+            neighbours: vec![Vec::new(); vertices],
+            edges: Vec::new(),
+        };
+        for &(a, b) in edges {
+            assert!(
+                a < vertices && b < vertices,
+                "The edge ({}, {}) refers to a vertex that does not exist.",
+                a,
+                b
+            );
+            if a != b && !graph.neighbours[a].contains(&b) {
+                graph.neighbours[a].push(b);
+                graph.neighbours[b].push(a);
+                graph.edges.push((a.min(b), a.max(b)));
+            }
+        }
+        graph
+    }
+
+    /// Parse a graph in the DIMACS format.
+    ///
+    /// The input has a problem line `p edge <vertices> <edges>` (or `p col`), followed
+    /// by a line `e <a> <b>` for every edge, with vertices numbered from one. Comment
+    /// lines start with `c`. The number of edges on the problem line is not checked,
+    /// since many published instances list some edges twice.
+    ///
+    /// Returns an `Error::Parse` if the input is not a valid DIMACS graph.
+    pub fn parse_dimacs(input: &str) -> Result<Graph, Error> {
+        let mut vertices: Option<usize> = None;
+        let mut edges = Vec::new();
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words[0] {
+                "c" => {}
+                "p" if words.len() == 4 && vertices.is_none() => {
+                    vertices = Some(parse_number(words[2])?);
+                }
+                "e" if words.len() == 3 => {
+                    let n = vertices.ok_or_else(|| parse_error("Edge before the problem line."))?;
+                    let vertex = |word| match parse_number(word)? {
+                        v if v >= 1 && v <= n => Ok(v - 1),
+                        v => Err(parse_error(&format!("Invalid vertex: {}.", v))),
+                    };
+                    edges.push((vertex(words[1])?, vertex(words[2])?));
+                }
+                _ => return Err(parse_error(&format!("Invalid line: {}.", line))),
+            }
+        }
+        let n = vertices.ok_or_else(|| parse_error("Missing problem line."))?;
+        Ok(Graph::new(n, &edges))
+    }
+
+    /// Get the number of vertices.
+    pub fn vertices(&self) -> usize {
+        self.neighbours.len()
+    }
+
+    /// Get the edges, with the lowest vertex of every edge first.
+    pub fn edges(&self) -> &[(usize, usize)] {
+        &self.edges
+    }
+
+    /// Get the vertices that share an edge with vertex `v`.
+    pub fn neighbours(&self, v: usize) -> &[usize] {
+        &self.neighbours[v]
+    }
+}
+
+/// Parse a non-negative number in a DIMACS file.
+fn parse_number(word: &str) -> Result<usize, Error> {
+    word.parse()
+        .map_err(|_| parse_error(&format!("Invalid number: {}.", word)))
+}
+
+fn parse_error(message: &str) -> Error {
+    Error::Parse(String::from(message))
+}
+
+/// A graph coloring problem: give every vertex a color, such that adjacent vertices have
+/// different colors, using as few colors as possible.
+///
+/// A coloring is a `Grouping` of the vertices, with one group per color.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphColoring {
+    graph: Graph,
+}
+
+impl GraphColoring {
+    /// Create a problem for coloring `graph`.
+    pub fn new(graph: Graph) -> GraphColoring {
+        GraphColoring { graph }
+    }
+
+    /// Get the graph to color.
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// Create a random proper coloring, by coloring the vertices in a random order with
+    /// first fit.
+    pub fn random_coloring<R: Rng>(&self, rng: &mut R) -> Grouping {
+        let mut vertices: Vec<usize> = (0..self.graph.vertices()).collect();
+        rng.shuffle(&mut vertices);
+        let mut groups = Vec::new();
+        for v in vertices {
+            self.first_fit(&mut groups, v);
+        }
+        Grouping::new(groups)
+    }
+
+    /// Get the number of edges whose vertices have the same color.
+    ///
+    /// This is zero for a proper coloring.
+    pub fn conflicts(&self, coloring: &Grouping) -> usize {
+        coloring
+            .groups()
+            .iter()
+            .map(|g| {
+                g.iter()
+                    .map(|&v| {
+                        let neighbours = self.graph.neighbours(v);
+                        g.iter().filter(|w| neighbours.contains(w)).count()
+                    })
+                    .sum::<usize>()
+                    / 2
+            })
+            .sum()
+    }
+
+    /// Get the fitness of `coloring`: the sum of the squared sizes of its color classes,
+    /// divided by the squared number of vertices.
+    ///
+    /// Like the fitness of `grouping::BinPacking`, this rewards large classes, which
+    /// leads to fewer colors, and is one if a single color suffices. Conflicts are not
+    /// penalised, since the operators only create proper colorings.
+    pub fn fitness(&self, coloring: &Grouping) -> f64 {
+        let n = self.graph.vertices();
+        if n == 0 {
+            return 0.0;
+        }
+        let total: usize = coloring.groups().iter().map(|g| g.len() * g.len()).sum();
+        total as f64 / (n * n) as f64
+    }
+
+    /// Put vertex `v` in the first group without any of its neighbours, or in a new group.
+    fn first_fit(&self, groups: &mut Vec<Vec<usize>>, v: usize) {
+        let neighbours = self.graph.neighbours(v);
+        match groups
+            .iter_mut()
+            .find(|g| !g.iter().any(|w| neighbours.contains(w)))
+        {
+            Some(group) => group.push(v),
+            None => groups.push(vec![v]),
+        }
+    }
+}
+
+impl Reinsert for GraphColoring {
+    /// Reinsert the vertices with first fit, in order of decreasing degree.
+    fn reinsert(&self, groups: &mut Vec<Vec<usize>>, mut items: Vec<usize>) {
+        items.sort_by_key(|&v| ::std::cmp::Reverse(self.graph.neighbours(v).len()));
+        for v in items {
+            self.first_fit(groups, v);
+        }
+    }
+}
+
+/// A vertex cover problem: choose as few vertices as possible, such that every edge has
+/// at least one chosen vertex.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VertexCover {
+    graph: Graph,
+    handling: ConstraintHandling,
+}
+
+impl VertexCover {
+    /// Create a problem for covering `graph`. By default, `ConstraintHandling::Repair`
+    /// is used.
+    pub fn new(graph: Graph) -> VertexCover {
+        VertexCover {
+            graph,
+            handling: ConstraintHandling::Repair,
+        }
+    }
+
+    /// Set how covers that leave edges uncovered are handled.
+    ///
+    /// With `ConstraintHandling::Penalty`, every uncovered edge costs as much as a
+    /// vertex, since adding one of its vertices would cover it.
+    pub fn with_handling(mut self, handling: ConstraintHandling) -> VertexCover {
+        self.handling = handling;
+        self
+    }
+
+    /// Get the graph to cover.
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// Create a random cover, in which every vertex is chosen with probability one half.
+    pub fn random_cover<R: Rng>(&self, rng: &mut R) -> Cover {
+        Cover::new((0..self.graph.vertices()).map(|_| rng.gen()).collect())
+    }
+
+    /// Get the number of edges that have no chosen vertex.
+    pub fn uncovered(&self, cover: &Cover) -> usize {
+        self.graph
+            .edges()
+            .iter()
+            .filter(|&&(a, b)| !cover.vertices[a] && !cover.vertices[b])
+            .count()
+    }
+
+    /// Check whether `cover` covers every edge.
+    pub fn is_feasible(&self, cover: &Cover) -> bool {
+        self.uncovered(cover) == 0
+    }
+
+    /// Get a feasible cover, by choosing a vertex of every uncovered edge: the one
+    /// that covers the most edges that are still uncovered.
+    pub fn repair(&self, cover: &Cover) -> Cover {
+        let mut vertices = cover.vertices.clone();
+        for &(a, b) in self.graph.edges() {
+            if !vertices[a] && !vertices[b] {
+                let uncovered = |v: usize| {
+                    let neighbours = self.graph.neighbours(v);
+                    neighbours.iter().filter(|&&w| !vertices[w]).count()
+                };
+                let v = if uncovered(b) > uncovered(a) { b } else { a };
+                vertices[v] = true;
+            }
+        }
+        Cover::new(vertices)
+    }
+
+    /// Get the fitness of `cover`: the number of vertices that are not chosen, handling
+    /// covers that leave edges uncovered as configured.
+    pub fn fitness(&self, cover: &Cover) -> f64 {
+        let free = |cover: &Cover| cover.vertices.iter().filter(|&&v| !v).count() as f64;
+        match self.handling {
+            ConstraintHandling::Penalty => free(cover) - self.uncovered(cover) as f64,
+            ConstraintHandling::Repair => free(&self.repair(cover)),
+        }
+    }
+}
+
+/// A selection of vertices of a `VertexCover`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cover {
+    vertices: Vec<bool>,
+}
+
+impl Cover {
+    /// Create a cover from a bit per vertex, telling whether it is chosen.
+    pub fn new(vertices: Vec<bool>) -> Cover {
+        Cover { vertices }
+    }
+
+    /// Get the bit of every vertex, telling whether it is chosen.
+    pub fn vertices(&self) -> &[bool] {
+        &self.vertices
+    }
+}
+
+impl Genome for Cover {
+    type Gene = bool;
+
+    fn genes(&self) -> &[bool] {
+        &self.vertices
+    }
+
+    fn with_genes(&self, genes: Vec<bool>) -> Cover {
+        Cover::new(genes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use genomes::grouping::{GroupCrossover, GroupMutation};
+    use rand::{SeedableRng, StdRng};
+    use sim::crossover::CrossoverOperator;
+    use sim::mutation::MutationOperator;
+
+    /// A cycle of five vertices, which needs three colors and a cover of three vertices.
+    fn pentagon() -> Graph {
+        Graph::new(5, &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 0)])
+    }
+
+    #[test]
+    fn test_parse_dimacs() {
+        let input = "c A triangle.\np edge 3 4\ne 1 2\ne 2 3\ne 3 1\ne 1 3\n";
+        let graph = Graph::parse_dimacs(input).unwrap();
+        assert_eq!(graph.vertices(), 3);
+        assert_eq!(graph.edges(), &[(0, 1), (1, 2), (0, 2)]);
+        assert_eq!(graph.neighbours(2), &[1, 0]);
+    }
+
+    #[test]
+    fn test_parse_dimacs_invalid() {
+        assert_eq!(
+            Graph::parse_dimacs("e 1 2\n"),
+            Err(Error::Parse(String::from("Edge before the problem line.")))
+        );
+        assert!(Graph::parse_dimacs("c Nothing.\n").is_err());
+        assert!(Graph::parse_dimacs("p edge 2 1\ne 1 3\n").is_err());
+        assert!(Graph::parse_dimacs("p edge 2 1\ne 0 1\n").is_err());
+        assert!(Graph::parse_dimacs("p edge 2 1\ne 1 x\n").is_err());
+        assert!(Graph::parse_dimacs("p edge 2 1\nx\n").is_err());
+    }
+
+    fn is_proper(problem: &GraphColoring, coloring: &Grouping) -> bool {
+        let mut vertices: Vec<usize> = coloring.groups().iter().flatten().cloned().collect();
+        vertices.sort();
+        vertices == (0..problem.graph().vertices()).collect::<Vec<_>>()
+            && problem.conflicts(coloring) == 0
+    }
+
+    #[test]
+    fn test_coloring_fitness() {
+        let problem = GraphColoring::new(pentagon());
+        let coloring = Grouping::new(vec![vec![0, 2], vec![1, 3], vec![4]]);
+        assert_eq!(problem.conflicts(&coloring), 0);
+        assert_eq!(problem.fitness(&coloring), 9.0 / 25.0);
+        let conflicting = Grouping::new(vec![vec![0, 1, 2], vec![3, 4]]);
+        assert_eq!(problem.conflicts(&conflicting), 3);
+    }
+
+    #[test]
+    fn test_coloring_operators_keep_colorings_proper() {
+        let problem = GraphColoring::new(pentagon());
+        let crossover = GroupCrossover::new(problem.clone());
+        let mutation = GroupMutation::new(1, problem.clone());
+        let mut rng = StdRng::from_seed(&[42]);
+        for _ in 0..100 {
+            let a = problem.random_coloring(&mut rng);
+            let b = problem.random_coloring(&mut rng);
+            assert!(is_proper(&problem, &a));
+            let child = crossover.crossover(&a, &b);
+            assert!(is_proper(&problem, &child));
+            assert!(is_proper(&problem, &mutation.mutate(&child)));
+        }
+    }
+
+    #[test]
+    fn test_cover_repair() {
+        let problem = VertexCover::new(pentagon());
+        let cover = Cover::new(vec![true, false, false, false, false]);
+        assert_eq!(problem.uncovered(&cover), 3);
+        let repaired = problem.repair(&cover);
+        assert!(problem.is_feasible(&repaired));
+        assert_eq!(repaired.vertices(), &[true, false, true, true, false]);
+        assert_eq!(problem.fitness(&cover), 2.0);
+    }
+
+    #[test]
+    fn test_cover_penalty() {
+        let problem = VertexCover::new(pentagon()).with_handling(ConstraintHandling::Penalty);
+        let cover = Cover::new(vec![true, false, false, false, false]);
+        assert_eq!(problem.fitness(&cover), 4.0 - 3.0);
+        let feasible = Cover::new(vec![true, false, true, true, false]);
+        assert_eq!(problem.fitness(&feasible), 2.0);
+    }
+
+    #[test]
+    fn test_random_cover() {
+        let problem = VertexCover::new(pentagon());
+        let mut rng = StdRng::from_seed(&[42]);
+        for _ in 0..20 {
+            let cover = problem.random_cover(&mut rng);
+            assert_eq!(cover.vertices().len(), 5);
+            assert!(problem.is_feasible(&problem.repair(&cover)));
+        }
+    }
+}
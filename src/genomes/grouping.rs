@@ -0,0 +1,277 @@
+// file: grouping.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains a grouping genome for partitioning problems, such as bin packing.
+//!
+//! Following Falkenauer's grouping genetic algorithm, a `Grouping` is a list of groups
+//! of items, and the operators work on whole groups instead of on the group of every
+//! single item. A good group is then inherited as a whole, which lets grouping problems
+//! converge much better than an encoding that stores a group index per item.
+//!
+//! Both operators remove some groups and have to find a new place for the items in them.
+//! This is done by a `Reinsert` implementation, which holds the problem-specific
+//! knowledge, e.g. `BinPacking` reinserts items using the first-fit decreasing heuristic.
+
+use pheno::Genome;
+use rand::Rng;
+use sim::crossover::CrossoverOperator;
+use sim::mutation::MutationOperator;
+use std::collections::HashSet;
+use std::fmt::Debug;
+
+/// A partition of items, identified by their indices, into groups.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grouping {
+    groups: Vec<Vec<usize>>,
+}
+
+impl Grouping {
+    /// Create a grouping from a list of groups.
+    pub fn new(groups: Vec<Vec<usize>>) -> Grouping {
+        Grouping { groups }
+    }
+
+    /// Get the groups.
+    pub fn groups(&self) -> &[Vec<usize>] {
+        &self.groups
+    }
+}
+
+impl Genome for Grouping {
+    type Gene = Vec<usize>;
+
+    fn genes(&self) -> &[Vec<usize>] {
+        &self.groups
+    }
+
+    fn with_genes(&self, genes: Vec<Vec<usize>>) -> Grouping {
+        Grouping::new(genes)
+    }
+}
+
+/// Puts items that were removed from their group back into a grouping.
+pub trait Reinsert: Debug + Send + Sync {
+    /// Add every item of `items` to a group in `groups`, possibly creating new groups.
+    fn reinsert(&self, groups: &mut Vec<Vec<usize>>, items: Vec<usize>);
+}
+
+/// A bin packing problem: items of given sizes must be packed into as few bins of a
+/// fixed capacity as possible.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BinPacking {
+    sizes: Vec<f64>,
+    capacity: f64,
+}
+
+impl BinPacking {
+    /// Create a problem with the size of every item and the capacity of every bin.
+    pub fn new(sizes: Vec<f64>, capacity: f64) -> BinPacking {
+        BinPacking { sizes, capacity }
+    }
+
+    /// Get the total size of the items in `group`.
+    pub fn load(&self, group: &[usize]) -> f64 {
+        group.iter().map(|&i| self.sizes[i]).sum()
+    }
+
+    /// Create a random grouping, by packing the items in a random order with first fit.
+    pub fn random_grouping<R: Rng>(&self, rng: &mut R) -> Grouping {
+        let mut items: Vec<usize> = (0..self.sizes.len()).collect();
+        rng.shuffle(&mut items);
+        let mut groups = Vec::new();
+        for item in items {
+            self.first_fit(&mut groups, item);
+        }
+        Grouping::new(groups)
+    }
+
+    /// Get Falkenauer's fitness of `grouping`: the mean of the squared fill ratios of
+    /// its bins. This rewards full bins more than bins that are evenly filled, and is
+    /// one for a packing in which every bin is full.
+    pub fn fitness(&self, grouping: &Grouping) -> f64 {
+        let groups = grouping.groups();
+        if groups.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = groups
+            .iter()
+            .map(|g| (self.load(g) / self.capacity).powi(2))
+            .sum();
+        total / groups.len() as f64
+    }
+
+    /// Put `item` in the first group with enough room left, or in a new group.
+    fn first_fit(&self, groups: &mut Vec<Vec<usize>>, item: usize) {
+        let size = self.sizes[item];
+        match groups
+            .iter_mut()
+            .find(|g| self.load(g) + size <= self.capacity)
+        {
+            Some(group) => group.push(item),
+            None => groups.push(vec![item]),
+        }
+    }
+}
+
+impl Reinsert for BinPacking {
+    /// Reinsert the items using first fit decreasing.
+    fn reinsert(&self, groups: &mut Vec<Vec<usize>>, mut items: Vec<usize>) {
+        items.sort_by(|&a, &b| self.sizes[b].total_cmp(&self.sizes[a]));
+        for item in items {
+            self.first_fit(groups, item);
+        }
+    }
+}
+
+/// Falkenauer's group crossover.
+///
+/// A random range of groups of the second parent is inserted at a random position in
+/// the first parent. Groups of the first parent that share an item with the inserted
+/// groups are removed, and their other items are reinserted.
+#[derive(Clone, Debug)]
+pub struct GroupCrossover<R> {
+    reinsert: R,
+}
+
+impl<R> GroupCrossover<R> {
+    /// Create and return a group crossover operator that uses `reinsert`.
+    pub fn new(reinsert: R) -> GroupCrossover<R> {
+        GroupCrossover { reinsert }
+    }
+}
+
+impl<R: Reinsert> CrossoverOperator<Grouping> for GroupCrossover<R> {
+    fn crossover(&self, a: &Grouping, b: &Grouping) -> Grouping {
+        self.crossover_with_rng(a, b, &mut ::rand::thread_rng())
+    }
+
+    fn crossover_with_rng(&self, a: &Grouping, b: &Grouping, mut rng: &mut dyn Rng) -> Grouping {
+        let rng = &mut rng;
+        let i = rng.gen_range(0, b.groups.len() + 1);
+        let j = rng.gen_range(0, b.groups.len() + 1);
+        let inserted = &b.groups[i.min(j)..i.max(j)];
+        let taken: HashSet<usize> = inserted.iter().flat_map(|g| g.iter().cloned()).collect();
+        let at = rng.gen_range(0, a.groups.len() + 1);
+        let mut freed = Vec::new();
+        let mut keep = |g: &&Vec<usize>| {
+            if g.iter().any(|item| taken.contains(item)) {
+                freed.extend(g.iter().filter(|item| !taken.contains(item)));
+                false
+            } else {
+                true
+            }
+        };
+        let mut groups: Vec<Vec<usize>> =
+            a.groups[..at].iter().filter(&mut keep).cloned().collect();
+        groups.extend_from_slice(inserted);
+        groups.extend(a.groups[at..].iter().filter(&mut keep).cloned());
+        self.reinsert.reinsert(&mut groups, freed);
+        Grouping::new(groups)
+    }
+}
+
+/// Removes a number of random groups and reinserts their items.
+#[derive(Clone, Debug)]
+pub struct GroupMutation<R> {
+    count: usize,
+    reinsert: R,
+}
+
+impl<R> GroupMutation<R> {
+    /// Create and return an operator that removes `count` groups and reinserts their
+    /// items using `reinsert`.
+    pub fn new(count: usize, reinsert: R) -> GroupMutation<R> {
+        GroupMutation { count, reinsert }
+    }
+}
+
+impl<R: Reinsert> MutationOperator<Grouping> for GroupMutation<R> {
+    fn mutate(&self, x: &Grouping) -> Grouping {
+        self.mutate_with_rng(x, &mut ::rand::thread_rng())
+    }
+
+    fn mutate_with_rng(&self, x: &Grouping, mut rng: &mut dyn Rng) -> Grouping {
+        let rng = &mut rng;
+        let mut groups = x.groups.clone();
+        let mut freed = Vec::new();
+        for _ in 0..self.count.min(groups.len()) {
+            let i = rng.gen_range(0, groups.len());
+            freed.extend(groups.swap_remove(i));
+        }
+        self.reinsert.reinsert(&mut groups, freed);
+        Grouping::new(groups)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem() -> BinPacking {
+        BinPacking::new(vec![3.0, 8.0, 2.0, 7.0, 6.0, 1.0, 3.0], 10.0)
+    }
+
+    fn is_valid(problem: &BinPacking, grouping: &Grouping) -> bool {
+        let mut items: Vec<usize> = grouping.groups().iter().flatten().cloned().collect();
+        items.sort();
+        items == (0..7).collect::<Vec<_>>()
+            && grouping
+                .groups()
+                .iter()
+                .all(|g| !g.is_empty() && problem.load(g) <= 10.0)
+    }
+
+    #[test]
+    fn test_random_grouping() {
+        let problem = problem();
+        for _ in 0..100 {
+            assert!(is_valid(
+                &problem,
+                &problem.random_grouping(&mut ::rand::thread_rng())
+            ));
+        }
+    }
+
+    #[test]
+    fn test_fitness() {
+        let problem = problem();
+        let full = Grouping::new(vec![vec![1, 2], vec![3, 0]]);
+        assert_eq!(problem.fitness(&full), 1.0);
+        let half = Grouping::new(vec![vec![0, 2], vec![0, 2]]);
+        assert_eq!(problem.fitness(&half), 0.25);
+    }
+
+    #[test]
+    fn test_reinsert_first_fit_decreasing() {
+        let mut groups = vec![vec![1]];
+        problem().reinsert(&mut groups, vec![5, 2, 3]);
+        assert_eq!(groups, vec![vec![1, 2], vec![3, 5]]);
+    }
+
+    #[test]
+    fn test_operators_keep_groupings_valid() {
+        let problem = problem();
+        let crossover = GroupCrossover::new(problem.clone());
+        let mutation = GroupMutation::new(2, problem.clone());
+        for _ in 0..100 {
+            let a = problem.random_grouping(&mut ::rand::thread_rng());
+            let b = problem.random_grouping(&mut ::rand::thread_rng());
+            let child = crossover.crossover(&a, &b);
+            assert!(is_valid(&problem, &child));
+            assert!(is_valid(&problem, &mutation.mutate(&child)));
+        }
+    }
+}
@@ -0,0 +1,256 @@
+// file: jobshop.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains an operation-based encoding for job-shop scheduling.
+//!
+//! In a job-shop problem, every job is a sequence of operations that must be processed
+//! in order, each on a given machine for a given duration. A machine processes one
+//! operation at a time. The goal is usually to minimize the makespan, i.e. the time at
+//! which the last operation finishes.
+//!
+//! A `Schedule` is a permutation with repetition: it contains the index of every job
+//! once for each of its operations. The `k`-th occurrence of job `j` stands for the
+//! `k`-th operation of job `j`, so every permutation decodes to a feasible schedule.
+//! `PrecedenceCrossover`, `SwapMutation` and `TranspositionMutation` keep the number
+//! of occurrences of every job intact, so their offspring are always valid.
+
+use pheno::Genome;
+use rand::Rng;
+use sim::crossover::CrossoverOperator;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// A job-shop scheduling problem.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JobShop {
+    jobs: Vec<Vec<(usize, u64)>>,
+}
+
+impl JobShop {
+    /// Create a problem from the operations of every job.
+    ///
+    /// Every operation is a pair of a machine index and a duration. The operations of
+    /// a job are processed in the given order.
+    pub fn new(jobs: Vec<Vec<(usize, u64)>>) -> JobShop {
+        JobShop { jobs }
+    }
+
+    /// Get the classic 6 x 6 instance of Fisher and Thompson, known as `ft06`.
+    ///
+    /// Its optimal makespan is 55.
+    pub fn ft06() -> JobShop {
+        JobShop::new(vec![
+            vec![(2, 1), (0, 3), (1, 6), (3, 7), (5, 3), (4, 6)],
+            vec![(1, 8), (2, 5), (4, 10), (5, 10), (0, 10), (3, 4)],
+            vec![(2, 5), (3, 4), (5, 8), (0, 9), (1, 1), (4, 7)],
+            vec![(1, 5), (0, 5), (2, 5), (3, 3), (4, 8), (5, 9)],
+            vec![(2, 9), (1, 3), (4, 5), (5, 4), (0, 3), (3, 1)],
+            vec![(1, 3), (3, 3), (5, 9), (0, 10), (4, 4), (2, 1)],
+        ])
+    }
+
+    /// Get the operations of every job.
+    pub fn jobs(&self) -> &[Vec<(usize, u64)>] {
+        &self.jobs
+    }
+
+    /// Create a random schedule for this problem.
+    pub fn random_schedule<R: Rng>(&self, rng: &mut R) -> Schedule {
+        let mut operations: Vec<usize> = self
+            .jobs
+            .iter()
+            .enumerate()
+            .flat_map(|(j, ops)| ::std::iter::repeat(j).take(ops.len()))
+            .collect();
+        rng.shuffle(&mut operations);
+        Schedule::new(operations)
+    }
+
+    /// Get the makespan of `schedule`, where every operation starts as soon as both
+    /// its job and its machine are available.
+    ///
+    /// Returns `None` if `schedule` does not contain every job exactly once for each
+    /// of its operations.
+    pub fn makespan(&self, schedule: &Schedule) -> Option<u64> {
+        let mut next = vec![0; self.jobs.len()];
+        let mut job_ready = vec![0; self.jobs.len()];
+        let mut machine_ready: Vec<u64> = Vec::new();
+        for &j in &schedule.operations {
+            let &(machine, duration) = self.jobs.get(j)?.get(next[j])?;
+            if machine >= machine_ready.len() {
+                machine_ready.resize(machine + 1, 0);
+            }
+            let end = job_ready[j].max(machine_ready[machine]) + duration;
+            job_ready[j] = end;
+            machine_ready[machine] = end;
+            next[j] += 1;
+        }
+        if next.iter().zip(&self.jobs).any(|(&n, ops)| n != ops.len()) {
+            return None;
+        }
+        Some(job_ready.into_iter().max().unwrap_or(0))
+    }
+}
+
+/// An operation-based schedule for a `JobShop`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    operations: Vec<usize>,
+}
+
+impl Schedule {
+    /// Create a schedule from a sequence of job indices.
+    pub fn new(operations: Vec<usize>) -> Schedule {
+        Schedule { operations }
+    }
+
+    /// Get the sequence of job indices.
+    pub fn operations(&self) -> &[usize] {
+        &self.operations
+    }
+}
+
+impl Genome for Schedule {
+    type Gene = usize;
+
+    fn genes(&self) -> &[usize] {
+        &self.operations
+    }
+
+    fn with_genes(&self, genes: Vec<usize>) -> Schedule {
+        Schedule::new(genes)
+    }
+}
+
+/// Precedence preserving operation crossover (POX) for permutations with repetition.
+///
+/// A random subset of the genes, i.e. jobs, is chosen. The child takes these genes
+/// from the first parent, at the same positions. The remaining positions are filled
+/// with the other genes in the order in which they occur in the second parent.
+/// If both parents contain the same number of occurrences of every gene, so does the
+/// child, and the relative order of the operations of every job is inherited.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PrecedenceCrossover;
+
+impl PrecedenceCrossover {
+    /// Create and return a precedence preserving crossover operator.
+    pub fn new() -> PrecedenceCrossover {
+        PrecedenceCrossover
+    }
+}
+
+impl<T> CrossoverOperator<T> for PrecedenceCrossover
+where
+    T: Genome,
+    T::Gene: Hash + Eq,
+{
+    fn crossover(&self, a: &T, b: &T) -> T {
+        self.crossover_with_rng(a, b, &mut ::rand::thread_rng())
+    }
+
+    fn crossover_with_rng(&self, a: &T, b: &T, mut rng: &mut dyn Rng) -> T {
+        let rng = &mut rng;
+        let distinct: HashSet<&T::Gene> = a.genes().iter().collect();
+        let kept: HashSet<&T::Gene> = distinct.into_iter().filter(|_| rng.gen()).collect();
+        let mut rest = b.genes().iter().filter(|g| !kept.contains(g));
+        let genes = a
+            .genes()
+            .iter()
+            .filter_map(|g| {
+                if kept.contains(g) {
+                    Some(g.clone())
+                } else {
+                    rest.next().cloned()
+                }
+            })
+            .collect();
+        a.with_genes(genes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sim::mutation::{MutationOperator, SwapMutation};
+
+    fn occurrences(schedule: &Schedule) -> Vec<usize> {
+        let mut counts = vec![0; 6];
+        for &j in schedule.operations() {
+            counts[j] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_makespan() {
+        let problem = JobShop::new(vec![vec![(0, 3), (1, 2)], vec![(1, 4), (0, 1)]]);
+        // Job 0 runs on machine 0 during [0, 3), job 1 on machine 1 during [0, 4).
+        // Then job 0 runs on machine 1 during [4, 6), job 1 on machine 0 during [4, 5).
+        assert_eq!(problem.makespan(&Schedule::new(vec![0, 1, 0, 1])), Some(6));
+        assert_eq!(problem.makespan(&Schedule::new(vec![0, 0, 1, 1])), Some(10));
+    }
+
+    #[test]
+    fn test_invalid_schedule() {
+        let problem = JobShop::new(vec![vec![(0, 3), (1, 2)], vec![(1, 4), (0, 1)]]);
+        assert_eq!(problem.makespan(&Schedule::new(vec![0, 1, 0])), None);
+        assert_eq!(problem.makespan(&Schedule::new(vec![0, 0, 0, 1])), None);
+        assert_eq!(problem.makespan(&Schedule::new(vec![0, 2, 0, 1])), None);
+    }
+
+    #[test]
+    fn test_ft06_random_schedule() {
+        let problem = JobShop::ft06();
+        let schedule = problem.random_schedule(&mut ::rand::thread_rng());
+        assert_eq!(occurrences(&schedule), vec![6; 6]);
+        assert!(problem.makespan(&schedule).unwrap() >= 55);
+    }
+
+    #[test]
+    fn test_operators_keep_schedules_valid() {
+        let problem = JobShop::ft06();
+        for _ in 0..100 {
+            let a = problem.random_schedule(&mut ::rand::thread_rng());
+            let b = problem.random_schedule(&mut ::rand::thread_rng());
+            let child = PrecedenceCrossover::new().crossover(&a, &b);
+            assert_eq!(occurrences(&child), vec![6; 6]);
+            let mutated = SwapMutation::new().mutate(&child);
+            assert!(problem.makespan(&mutated).is_some());
+        }
+    }
+
+    #[test]
+    fn test_precedence_crossover() {
+        let a = Schedule::new(vec![0, 1, 2, 0, 1, 2]);
+        let b = Schedule::new(vec![2, 2, 1, 1, 0, 0]);
+        for _ in 0..100 {
+            let child = PrecedenceCrossover::new().crossover(&a, &b);
+            // Some subset of the jobs keeps its positions from `a`, and the other
+            // jobs appear in the order of `b`.
+            let valid = (0..8).any(|subset: usize| {
+                let kept = |j: usize| subset & (1 << j) != 0;
+                let positions = (0..6).all(|i| {
+                    kept(a.operations()[i]) == kept(child.operations()[i])
+                        && (!kept(a.operations()[i]) || a.operations()[i] == child.operations()[i])
+                });
+                let rest_child: Vec<_> = child.operations().iter().filter(|&&j| !kept(j)).collect();
+                let rest_b: Vec<_> = b.operations().iter().filter(|&&j| !kept(j)).collect();
+                positions && rest_child == rest_b
+            });
+            assert!(valid);
+        }
+    }
+}
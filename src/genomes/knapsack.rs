@@ -0,0 +1,216 @@
+// file: knapsack.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains a 0/1 knapsack problem, to compare ways of handling constraints.
+//!
+//! A `Packing` has one bit per item, telling whether the item is packed. Packings that
+//! exceed the capacity of the knapsack are infeasible. `Knapsack::fitness` handles them
+//! according to its `ConstraintHandling`: either the value is reduced by a penalty, or
+//! the packing is first repaired by greedily dropping items.
+//!
+//! Since the genes of a `Packing` are plain booleans, all built-in crossover operators
+//! can be used, together with a `GeneMutation` that flips bits.
+
+use pheno::Genome;
+use rand::Rng;
+
+/// Determines how `Knapsack::fitness` handles packings that exceed the capacity.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConstraintHandling {
+    /// The excess weight, multiplied by the highest value per unit of weight of any item,
+    /// is subtracted from the value.
+    Penalty,
+    /// The fitness is the value of the packing after `Knapsack::repair`.
+    Repair,
+}
+
+/// A 0/1 knapsack problem: choose items with a maximal total value, without exceeding
+/// the capacity of the knapsack.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Knapsack {
+    weights: Vec<f64>,
+    values: Vec<f64>,
+    capacity: f64,
+    handling: ConstraintHandling,
+}
+
+impl Knapsack {
+    /// Create a problem with the weight and value of every item and the capacity of the
+    /// knapsack. By default, `ConstraintHandling::Repair` is used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weights` and `values` have different lengths.
+    pub fn new(weights: Vec<f64>, values: Vec<f64>, capacity: f64) -> Knapsack {
+        assert_eq!(
+            weights.len(),
+            values.len(),
+            "Every item needs both a weight and a value."
+        );
+        Knapsack {
+            weights,
+            values,
+            capacity,
+            handling: ConstraintHandling::Repair,
+        }
+    }
+
+    /// Set how infeasible packings are handled.
+    pub fn with_handling(mut self, handling: ConstraintHandling) -> Knapsack {
+        self.handling = handling;
+        self
+    }
+
+    /// Create a random packing, in which every item is packed with probability one half.
+    pub fn random_packing<R: Rng>(&self, rng: &mut R) -> Packing {
+        Packing::new((0..self.weights.len()).map(|_| rng.gen()).collect())
+    }
+
+    /// Get the total weight of the packed items.
+    pub fn weight(&self, packing: &Packing) -> f64 {
+        self.sum(&self.weights, packing)
+    }
+
+    /// Get the total value of the packed items.
+    pub fn value(&self, packing: &Packing) -> f64 {
+        self.sum(&self.values, packing)
+    }
+
+    /// Check whether `packing` does not exceed the capacity.
+    pub fn is_feasible(&self, packing: &Packing) -> bool {
+        self.weight(packing) <= self.capacity
+    }
+
+    /// Get a feasible packing, by dropping the packed items with the lowest value per
+    /// unit of weight until the capacity is no longer exceeded.
+    pub fn repair(&self, packing: &Packing) -> Packing {
+        let mut items = packing.items.clone();
+        let mut packed: Vec<usize> = (0..items.len()).filter(|&i| items[i]).collect();
+        packed.sort_by(|&a, &b| self.density(a).total_cmp(&self.density(b)));
+        let mut weight = self.weight(packing);
+        for i in packed {
+            if weight <= self.capacity {
+                break;
+            }
+            items[i] = false;
+            weight -= self.weights[i];
+        }
+        Packing::new(items)
+    }
+
+    /// Get the fitness of `packing`, handling infeasible packings as configured.
+    pub fn fitness(&self, packing: &Packing) -> f64 {
+        match self.handling {
+            ConstraintHandling::Penalty => {
+                let excess = (self.weight(packing) - self.capacity).max(0.0);
+                let rate = (0..self.weights.len())
+                    .map(|i| self.density(i))
+                    .fold(0.0, f64::max);
+                self.value(packing) - rate * excess
+            }
+            ConstraintHandling::Repair => self.value(&self.repair(packing)),
+        }
+    }
+
+    /// Get the value per unit of weight of item `i`.
+    fn density(&self, i: usize) -> f64 {
+        self.values[i] / self.weights[i]
+    }
+
+    /// Sum `amounts` over the packed items.
+    fn sum(&self, amounts: &[f64], packing: &Packing) -> f64 {
+        amounts
+            .iter()
+            .zip(&packing.items)
+            .filter(|&(_, &packed)| packed)
+            .map(|(a, _)| a)
+            .sum()
+    }
+}
+
+/// A selection of items of a `Knapsack`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Packing {
+    items: Vec<bool>,
+}
+
+impl Packing {
+    /// Create a packing from a bit per item, telling whether it is packed.
+    pub fn new(items: Vec<bool>) -> Packing {
+        Packing { items }
+    }
+
+    /// Get the bit of every item, telling whether it is packed.
+    pub fn items(&self) -> &[bool] {
+        &self.items
+    }
+}
+
+impl Genome for Packing {
+    type Gene = bool;
+
+    fn genes(&self) -> &[bool] {
+        &self.items
+    }
+
+    fn with_genes(&self, genes: Vec<bool>) -> Packing {
+        Packing::new(genes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem() -> Knapsack {
+        // Densities: 6, 5, 4.
+        Knapsack::new(vec![10.0, 20.0, 30.0], vec![60.0, 100.0, 120.0], 50.0)
+    }
+
+    #[test]
+    fn test_feasible() {
+        let packing = Packing::new(vec![false, true, true]);
+        assert!(problem().is_feasible(&packing));
+        assert_eq!(problem().fitness(&packing), 220.0);
+        assert_eq!(problem().repair(&packing), packing);
+    }
+
+    #[test]
+    fn test_repair() {
+        let packing = Packing::new(vec![true, true, true]);
+        let repaired = problem().repair(&packing);
+        assert_eq!(repaired.items(), &[true, true, false]);
+        assert_eq!(problem().fitness(&packing), 160.0);
+    }
+
+    #[test]
+    fn test_penalty() {
+        let problem = problem().with_handling(ConstraintHandling::Penalty);
+        let packing = Packing::new(vec![true, true, true]);
+        // 10 units too heavy, at 6 per unit.
+        assert_eq!(problem.fitness(&packing), 280.0 - 60.0);
+    }
+
+    #[test]
+    fn test_random_packing() {
+        let problem = problem();
+        for _ in 0..20 {
+            let packing = problem.random_packing(&mut ::rand::thread_rng());
+            assert_eq!(packing.items().len(), 3);
+            assert!(problem.is_feasible(&problem.repair(&packing)));
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// file: mod.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains ready-made genome representations for common kinds of problems.
+//!
+//! The types in this module implement `Genome`, so the built-in operators in
+//! `sim::crossover` and `sim::mutation` can be used with them. They do not implement
+//! `Phenotype`, because the fitness function is specific to your problem: wrap a genome
+//! in your own phenotype type and delegate `Genome` to it.
+
+pub mod assignment;
+pub mod graph;
+pub mod grouping;
+pub mod jobshop;
+pub mod knapsack;
+pub mod neural;
+pub mod rules;
+pub mod tsp;
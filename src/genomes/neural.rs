@@ -0,0 +1,225 @@
+// file: neural.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains a feed-forward neural network genome for neuroevolution.
+//!
+//! A `NeuralNetwork` has a fixed topology, given as the number of neurons in every
+//! layer, and evolves its weights. Every neuron has a bias and uses `tanh` as its
+//! activation function. The genes are the weights, so the built-in operators can be
+//! used directly. For crossover, `neuron_linkage` keeps the incoming weights of every
+//! neuron together, which avoids breaking up features that a neuron has learned.
+//! For mutation, `gaussian_perturbation` can be passed to a `GeneMutation`.
+//!
+//! All networks in a population should have the same topology.
+
+use pheno::Genome;
+use rand::distributions::{IndependentSample, Normal};
+use rand::Rng;
+use sim::crossover::Linkage;
+
+/// A fully connected feed-forward neural network.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NeuralNetwork {
+    layers: Vec<usize>,
+    weights: Vec<f64>,
+}
+
+impl NeuralNetwork {
+    /// Create a network with the given number of neurons per layer, including the
+    /// input and output layers, and random weights between -1 and 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than two layers.
+    pub fn random<R: Rng>(layers: &[usize], rng: &mut R) -> NeuralNetwork {
+        let weights = (0..weight_count(layers))
+            .map(|_| rng.gen_range(-1.0, 1.0))
+            .collect();
+        NeuralNetwork::with_weights(layers, weights)
+    }
+
+    /// Create a network with the given number of neurons per layer and the given weights.
+    ///
+    /// The weights are ordered by layer, then by neuron. Every neuron has one weight per
+    /// neuron in the previous layer, followed by its bias.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than two layers, or if the number of weights does not
+    /// match the topology.
+    pub fn with_weights(layers: &[usize], weights: Vec<f64>) -> NeuralNetwork {
+        assert_eq!(
+            weights.len(),
+            weight_count(layers),
+            "The number of weights does not match the topology."
+        );
+        NeuralNetwork {
+            layers: layers.to_vec(),
+            weights,
+        }
+    }
+
+    /// Get the number of neurons in every layer.
+    pub fn layers(&self) -> &[usize] {
+        &self.layers
+    }
+
+    /// Get the weights of the network.
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Compute the output of the network for `input`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `input` differs from the size of the input layer.
+    pub fn forward(&self, input: &[f64]) -> Vec<f64> {
+        assert_eq!(
+            input.len(),
+            self.layers[0],
+            "The input does not match the input layer."
+        );
+        let mut activations = input.to_vec();
+        let mut offset = 0;
+        for &size in &self.layers[1..] {
+            let fan_in = activations.len();
+            activations = (0..size)
+                .map(|_| {
+                    let neuron = &self.weights[offset..offset + fan_in + 1];
+                    offset += fan_in + 1;
+                    let sum: f64 = neuron.iter().zip(&activations).map(|(w, a)| w * a).sum();
+                    (sum + neuron[fan_in]).tanh()
+                })
+                .collect();
+        }
+        activations
+    }
+}
+
+impl Genome for NeuralNetwork {
+    type Gene = f64;
+
+    fn genes(&self) -> &[f64] {
+        &self.weights
+    }
+
+    fn with_genes(&self, genes: Vec<f64>) -> NeuralNetwork {
+        NeuralNetwork {
+            layers: self.layers.clone(),
+            weights: genes,
+        }
+    }
+}
+
+/// Get the number of weights, including biases, of a network with the given topology.
+///
+/// # Panics
+///
+/// Panics if there are fewer than two layers.
+pub fn weight_count(layers: &[usize]) -> usize {
+    assert!(layers.len() >= 2, "A network needs at least two layers.");
+    layers.windows(2).map(|w| (w[0] + 1) * w[1]).sum()
+}
+
+/// Get a `Linkage` that keeps the incoming weights and the bias of every neuron together.
+pub fn neuron_linkage(layers: &[usize]) -> Linkage {
+    let blocks: Vec<usize> = layers
+        .windows(2)
+        .flat_map(|w| ::std::iter::repeat(w[0] + 1).take(w[1]))
+        .collect();
+    Linkage::from_blocks(&blocks)
+}
+
+/// Get a gene mutation function that adds normally distributed noise with standard
+/// deviation `sigma` to a weight.
+///
+/// Use it with a `GeneMutation`, e.g. `GeneMutation::new(0.1, gaussian_perturbation(0.5))`.
+pub fn gaussian_perturbation(sigma: f64) -> impl Fn(&f64, &mut dyn Rng) -> f64 + Send + Sync {
+    let normal = Normal::new(0.0, sigma);
+    move |w: &f64, mut rng: &mut dyn Rng| w + normal.ind_sample(&mut rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, StdRng};
+    use sim::crossover::{CrossoverOperator, UniformCrossover};
+    use sim::mutation::{GeneMutation, MutationOperator};
+
+    #[test]
+    fn test_weight_count() {
+        assert_eq!(weight_count(&[2, 3, 1]), 3 * 3 + 4);
+    }
+
+    #[test]
+    fn test_forward() {
+        // A single neuron computing tanh(x - y + 0.5).
+        let network = NeuralNetwork::with_weights(&[2, 1], vec![1.0, -1.0, 0.5]);
+        let output = network.forward(&[2.0, 1.0]);
+        assert_eq!(output.len(), 1);
+        assert!((output[0] - 1.5f64.tanh()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_forward_hidden_layer() {
+        let network = NeuralNetwork::random(&[3, 4, 2], &mut ::rand::thread_rng());
+        let output = network.forward(&[0.1, 0.2, 0.3]);
+        assert_eq!(output.len(), 2);
+        assert!(output.iter().all(|x| x.abs() < 1.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_wrong_weight_count() {
+        NeuralNetwork::with_weights(&[2, 1], vec![1.0]);
+    }
+
+    #[test]
+    fn test_neuron_crossover() {
+        let layers = [2, 2, 1];
+        let a = NeuralNetwork::with_weights(&layers, vec![0.0; 9]);
+        let b = NeuralNetwork::with_weights(&layers, vec![1.0; 9]);
+        let op = UniformCrossover::new().with_linkage(neuron_linkage(&layers));
+        for _ in 0..100 {
+            let child = op.crossover(&a, &b);
+            let w = child.weights();
+            assert!(w[0..3].iter().all(|&x| x == w[0]));
+            assert!(w[3..6].iter().all(|&x| x == w[3]));
+            assert!(w[6..9].iter().all(|&x| x == w[6]));
+        }
+    }
+
+    #[test]
+    fn test_gaussian_perturbation() {
+        let network = NeuralNetwork::with_weights(&[2, 1], vec![0.0; 3]);
+        let op = GeneMutation::new(1.0, gaussian_perturbation(0.1));
+        let child = op.mutate(&network);
+        assert_eq!(child.layers(), network.layers());
+        assert!(child.weights().iter().all(|w| w.abs() < 1.0));
+    }
+
+    #[test]
+    fn test_gaussian_perturbation_seeded() {
+        let network = NeuralNetwork::with_weights(&[2, 1], vec![0.0; 3]);
+        let op = GeneMutation::new(1.0, gaussian_perturbation(0.1));
+        let mutate = || {
+            op.mutate_with_rng(&network, &mut StdRng::from_seed(&[42]))
+                .weights
+        };
+        assert_eq!(mutate(), mutate());
+    }
+}
@@ -0,0 +1,295 @@
+// file: rules.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains a Pittsburgh-style rule-set genome for evolving classifiers.
+//!
+//! A `RuleSet` is a variable-length, ordered list of condition-action rules with a
+//! default action. The first rule whose conditions all match an input decides its
+//! class, so an evolved rule set can be read as a decision list. Every condition
+//! checks whether a single feature lies within an interval, and can be disabled
+//! to match any value.
+//!
+//! The genes of a `RuleSet` are its rules, so the built-in operators work at the
+//! level of whole rules: `OnePointCrossover` exchanges rules between parents, and
+//! `DuplicationMutation`, `DeletionMutation` and `TranspositionMutation` grow, shrink
+//! and reorder the rule list. `rule_mutation` can be passed to a `GeneMutation` to
+//! change the rules themselves.
+
+use pheno::Genome;
+use rand::distributions::{IndependentSample, Normal};
+use rand::Rng;
+
+/// Checks whether a feature lies within an interval.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Condition {
+    /// The lower bound of the interval, inclusive.
+    pub low: f64,
+    /// The upper bound of the interval, inclusive.
+    pub high: f64,
+    /// Whether the condition is checked. A disabled condition matches any value.
+    pub enabled: bool,
+}
+
+impl Condition {
+    /// Create an enabled condition that matches values between `low` and `high`, inclusive.
+    pub fn between(low: f64, high: f64) -> Condition {
+        Condition {
+            low,
+            high,
+            enabled: true,
+        }
+    }
+
+    /// Check whether `value` satisfies this condition.
+    pub fn matches(&self, value: f64) -> bool {
+        !self.enabled || (self.low <= value && value <= self.high)
+    }
+}
+
+/// A rule that maps inputs matching all of its conditions to an action.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule<A> {
+    /// One condition per input feature.
+    pub conditions: Vec<Condition>,
+    /// The action, or class, of inputs that match this rule.
+    pub action: A,
+}
+
+impl<A> Rule<A> {
+    /// Create a rule from one condition per input feature and an action.
+    pub fn new(conditions: Vec<Condition>, action: A) -> Rule<A> {
+        Rule { conditions, action }
+    }
+
+    /// Create a random rule for inputs whose features lie within `bounds`.
+    ///
+    /// Every condition is enabled with probability one half, and covers a random
+    /// interval within the bounds of its feature. The action is picked uniformly
+    /// from `actions`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `actions` is empty.
+    pub fn random<R: Rng>(bounds: &[(f64, f64)], actions: &[A], rng: &mut R) -> Rule<A>
+    where
+        A: Clone,
+    {
+        let conditions = bounds
+            .iter()
+            .map(|&(low, high)| {
+                let (x, y) = (rng.gen_range(low, high), rng.gen_range(low, high));
+                Condition {
+                    low: x.min(y),
+                    high: x.max(y),
+                    enabled: rng.gen(),
+                }
+            })
+            .collect();
+        let action = rng.choose(actions).expect("No actions to choose from.");
+        Rule::new(conditions, action.clone())
+    }
+
+    /// Check whether every feature of `input` satisfies its condition.
+    ///
+    /// Features without a condition are ignored, as are conditions without a feature.
+    pub fn matches(&self, input: &[f64]) -> bool {
+        self.conditions
+            .iter()
+            .zip(input)
+            .all(|(c, &x)| c.matches(x))
+    }
+}
+
+/// An ordered list of rules with a default action.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleSet<A> {
+    rules: Vec<Rule<A>>,
+    default: A,
+}
+
+impl<A> RuleSet<A> {
+    /// Create a rule set. `default` is the action for inputs that match no rule.
+    pub fn new(rules: Vec<Rule<A>>, default: A) -> RuleSet<A> {
+        RuleSet { rules, default }
+    }
+
+    /// Get the rules, in the order in which they are tried.
+    pub fn rules(&self) -> &[Rule<A>] {
+        &self.rules
+    }
+
+    /// Get the action for inputs that match no rule.
+    pub fn default_action(&self) -> &A {
+        &self.default
+    }
+
+    /// Get the action of the first rule that matches `input`, or the default action.
+    pub fn classify(&self, input: &[f64]) -> &A {
+        self.rules
+            .iter()
+            .find(|r| r.matches(input))
+            .map_or(&self.default, |r| &r.action)
+    }
+
+    /// Get the fraction of `samples` that are classified correctly.
+    ///
+    /// Every sample is a pair of an input and its expected action. Returns zero
+    /// if there are no samples.
+    pub fn accuracy(&self, samples: &[(Vec<f64>, A)]) -> f64
+    where
+        A: PartialEq,
+    {
+        if samples.is_empty() {
+            return 0.0;
+        }
+        let correct = samples
+            .iter()
+            .filter(|&(input, expected)| self.classify(input) == expected)
+            .count();
+        correct as f64 / samples.len() as f64
+    }
+}
+
+impl<A: Clone> Genome for RuleSet<A> {
+    type Gene = Rule<A>;
+
+    fn genes(&self) -> &[Rule<A>] {
+        &self.rules
+    }
+
+    fn with_genes(&self, genes: Vec<Rule<A>>) -> RuleSet<A> {
+        RuleSet {
+            rules: genes,
+            default: self.default.clone(),
+        }
+    }
+}
+
+/// Get a gene mutation function that changes a single part of a rule.
+///
+/// Either the action is replaced by one picked from `actions`, or one condition
+/// is changed: it is disabled or enabled with probability one quarter, and otherwise
+/// normally distributed noise with standard deviation `sigma` is added to both bounds.
+///
+/// Use it with a `GeneMutation`, e.g. `GeneMutation::new(0.1, rule_mutation(0.5, actions))`.
+///
+/// # Panics
+///
+/// The returned function panics if it has to pick an action and `actions` is empty.
+pub fn rule_mutation<A>(
+    sigma: f64,
+    actions: Vec<A>,
+) -> impl Fn(&Rule<A>, &mut dyn Rng) -> Rule<A> + Send + Sync
+where
+    A: Clone + Send + Sync,
+{
+    let normal = Normal::new(0.0, sigma);
+    move |rule: &Rule<A>, mut rng: &mut dyn Rng| {
+        let rng = &mut rng;
+        let mut rule = rule.clone();
+        let i = rng.gen_range(0, rule.conditions.len() + 1);
+        if i == rule.conditions.len() {
+            let action = rng.choose(&actions).expect("No actions to choose from.");
+            rule.action = action.clone();
+        } else {
+            let c = &mut rule.conditions[i];
+            if rng.gen::<f64>() < 0.25 {
+                c.enabled = !c.enabled;
+            } else {
+                let x = c.low + normal.ind_sample(rng);
+                let y = c.high + normal.ind_sample(rng);
+                c.low = x.min(y);
+                c.high = x.max(y);
+            }
+        }
+        rule
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sim::crossover::{CrossoverOperator, OnePointCrossover};
+    use sim::mutation::{GeneMutation, MutationOperator};
+
+    fn rule_set() -> RuleSet<u8> {
+        RuleSet::new(
+            vec![
+                Rule::new(vec![Condition::between(0.0, 1.0)], 1),
+                Rule::new(vec![Condition::between(0.5, 2.0)], 2),
+            ],
+            0,
+        )
+    }
+
+    #[test]
+    fn test_classify() {
+        let rules = rule_set();
+        assert_eq!(*rules.classify(&[0.7]), 1);
+        assert_eq!(*rules.classify(&[1.5]), 2);
+        assert_eq!(*rules.classify(&[3.0]), 0);
+    }
+
+    #[test]
+    fn test_disabled_condition() {
+        let mut condition = Condition::between(0.0, 1.0);
+        condition.enabled = false;
+        assert!(condition.matches(100.0));
+    }
+
+    #[test]
+    fn test_accuracy() {
+        let samples = vec![(vec![0.7], 1), (vec![1.5], 2), (vec![3.0], 1)];
+        assert!((rule_set().accuracy(&samples) - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(rule_set().accuracy(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_random_rule() {
+        let bounds = [(0.0, 1.0), (-5.0, 5.0)];
+        for _ in 0..100 {
+            let rule = Rule::random(&bounds, &[1, 2], &mut ::rand::thread_rng());
+            assert_eq!(rule.conditions.len(), 2);
+            for (c, &(low, high)) in rule.conditions.iter().zip(&bounds) {
+                assert!(low <= c.low && c.low <= c.high && c.high <= high);
+            }
+        }
+    }
+
+    #[test]
+    fn test_crossover_keeps_rules_whole() {
+        let a = rule_set();
+        let b = RuleSet::new(vec![Rule::new(vec![Condition::between(5.0, 6.0)], 3)], 0);
+        let child = OnePointCrossover::new().crossover(&a, &b);
+        for rule in child.rules() {
+            assert!(a.rules().contains(rule) || b.rules().contains(rule));
+        }
+    }
+
+    #[test]
+    fn test_rule_mutation() {
+        let op = GeneMutation::new(1.0, rule_mutation(0.1, vec![1, 2]));
+        for _ in 0..100 {
+            let child = op.mutate(&rule_set());
+            assert_eq!(child.rules().len(), 2);
+            for rule in child.rules() {
+                let c = rule.conditions[0];
+                assert!(c.low <= c.high);
+                assert!(rule.action == 1 || rule.action == 2);
+            }
+        }
+    }
+}
@@ -0,0 +1,364 @@
+// file: tsp.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains a travelling salesman problem with a permutation genome.
+//!
+//! A `Tour` visits every city exactly once and returns to the first one. Problems can be
+//! created from a distance matrix, from coordinates, or from a subset of the TSPLIB
+//! format. Tours are best recombined with an order-preserving operator, like
+//! `jobshop::PrecedenceCrossover`, and mutated with `SwapMutation` or
+//! `TranspositionMutation`, which keep every city exactly once in the tour.
+//! `Tsp::two_opt` improves a tour by local search, and can be applied to offspring
+//! in a custom `MutationOperator` to get a memetic algorithm.
+
+use pheno::Genome;
+use rand::Rng;
+
+/// A symmetric travelling salesman problem.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tsp {
+    distances: Vec<Vec<f64>>,
+}
+
+impl Tsp {
+    /// Create a problem from a matrix with the distance between every pair of cities.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square.
+    pub fn new(distances: Vec<Vec<f64>>) -> Tsp {
+        let n = distances.len();
+        assert!(
+            distances.iter().all(|row| row.len() == n),
+            "The distance matrix should be square."
+        );
+        Tsp { distances }
+    }
+
+    /// Create a problem from the coordinates of every city, using Euclidean distances.
+    pub fn from_coordinates(coordinates: &[(f64, f64)]) -> Tsp {
+        Tsp::new(
+            coordinates
+                .iter()
+                .map(|&(x1, y1)| {
+                    coordinates
+                        .iter()
+                        .map(|&(x2, y2)| (x1 - x2).hypot(y1 - y2))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Parse a problem in the TSPLIB format.
+    ///
+    /// Two kinds of problems are supported: `EDGE_WEIGHT_TYPE: EUC_2D` with a
+    /// `NODE_COORD_SECTION`, and `EDGE_WEIGHT_TYPE: EXPLICIT` with
+    /// `EDGE_WEIGHT_FORMAT: FULL_MATRIX` and an `EDGE_WEIGHT_SECTION`. As prescribed by
+    /// TSPLIB, `EUC_2D` distances are rounded to the nearest integer.
+    pub fn parse_tsplib(input: &str) -> Result<Tsp, String> {
+        let mut dimension: Option<usize> = None;
+        let mut weight_type = String::new();
+        let mut weight_format = String::new();
+        let mut lines = input.lines().map(str::trim).filter(|l| !l.is_empty());
+        let mut numbers: Vec<f64> = Vec::new();
+        while let Some(line) = lines.next() {
+            match line {
+                "NODE_COORD_SECTION" | "EDGE_WEIGHT_SECTION" => {
+                    for line in lines.by_ref() {
+                        if line == "EOF" {
+                            break;
+                        }
+                        for word in line.split_whitespace() {
+                            numbers.push(
+                                word.parse()
+                                    .map_err(|_| format!("Invalid number: {}.", word))?,
+                            );
+                        }
+                    }
+                    break;
+                }
+                "EOF" => break,
+                _ => {}
+            }
+            let mut parts = line.splitn(2, ':');
+            let key = parts.next().unwrap_or("").trim();
+            let value = parts.next().unwrap_or("").trim();
+            match key {
+                "DIMENSION" => {
+                    dimension = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid dimension: {}.", value))?,
+                    )
+                }
+                "EDGE_WEIGHT_TYPE" => weight_type = value.to_string(),
+                "EDGE_WEIGHT_FORMAT" => weight_format = value.to_string(),
+                _ => {}
+            }
+        }
+        let n = dimension.ok_or_else(|| String::from("Missing dimension."))?;
+        if n == 0 {
+            return Err(String::from("Invalid dimension: 0."));
+        }
+        match (&weight_type[..], &weight_format[..]) {
+            ("EUC_2D", _) => {
+                if numbers.len() != 3 * n {
+                    return Err(format!("Expected {} coordinates.", n));
+                }
+                let coordinates: Vec<(f64, f64)> =
+                    numbers.chunks(3).map(|c| (c[1], c[2])).collect();
+                let mut tsp = Tsp::from_coordinates(&coordinates);
+                for row in &mut tsp.distances {
+                    for d in row.iter_mut() {
+                        *d = d.round();
+                    }
+                }
+                Ok(tsp)
+            }
+            ("EXPLICIT", "FULL_MATRIX") => {
+                if numbers.len() != n * n {
+                    return Err(format!("Expected {} edge weights.", n * n));
+                }
+                Ok(Tsp::new(numbers.chunks(n).map(|r| r.to_vec()).collect()))
+            }
+            _ => Err(format!(
+                "Unsupported edge weights: {} {}.",
+                weight_type, weight_format
+            )),
+        }
+    }
+
+    /// Get the number of cities.
+    pub fn cities(&self) -> usize {
+        self.distances.len()
+    }
+
+    /// Get the distance between cities `a` and `b`.
+    pub fn distance(&self, a: usize, b: usize) -> f64 {
+        self.distances[a][b]
+    }
+
+    /// Create a random tour.
+    pub fn random_tour<R: Rng>(&self, rng: &mut R) -> Tour {
+        let mut cities: Vec<usize> = (0..self.cities()).collect();
+        rng.shuffle(&mut cities);
+        Tour::new(cities)
+    }
+
+    /// Get the length of `tour`, including the way back to the first city.
+    pub fn length(&self, tour: &Tour) -> f64 {
+        let cities = &tour.cities;
+        if cities.is_empty() {
+            return 0.0;
+        }
+        let back = self.distance(cities[cities.len() - 1], cities[0]);
+        cities
+            .windows(2)
+            .map(|w| self.distance(w[0], w[1]))
+            .sum::<f64>()
+            + back
+    }
+
+    /// Improve `tour` with the 2-opt local search.
+    ///
+    /// Segments of the tour are reversed as long as that makes the tour shorter.
+    /// The result is a tour in which no single reversal is an improvement.
+    pub fn two_opt(&self, tour: &Tour) -> Tour {
+        let mut cities = tour.cities.clone();
+        let n = cities.len();
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for i in 0..n.saturating_sub(1) {
+                for j in i + 2..n {
+                    // Replace the edges (a, b) and (c, d) by (a, c) and (b, d).
+                    let (a, b) = (cities[i], cities[i + 1]);
+                    let (c, d) = (cities[j], cities[(j + 1) % n]);
+                    if a == d {
+                        continue;
+                    }
+                    let delta = self.distance(a, c) + self.distance(b, d)
+                        - self.distance(a, b)
+                        - self.distance(c, d);
+                    if delta < -1e-9 {
+                        cities[i + 1..j + 1].reverse();
+                        improved = true;
+                    }
+                }
+            }
+        }
+        Tour::new(cities)
+    }
+}
+
+/// A tour along all cities of a `Tsp`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tour {
+    cities: Vec<usize>,
+}
+
+impl Tour {
+    /// Create a tour that visits the given cities in order.
+    pub fn new(cities: Vec<usize>) -> Tour {
+        Tour { cities }
+    }
+
+    /// Get the cities in the order in which they are visited.
+    pub fn cities(&self) -> &[usize] {
+        &self.cities
+    }
+}
+
+impl Genome for Tour {
+    type Gene = usize;
+
+    fn genes(&self) -> &[usize] {
+        &self.cities
+    }
+
+    fn with_genes(&self, genes: Vec<usize>) -> Tour {
+        Tour::new(genes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use genomes::jobshop::PrecedenceCrossover;
+    use pheno::Phenotype;
+    use sim::crossover::CrossoverOperator;
+    use sim::mutation::{MutationOperator, SwapMutation};
+    use sim::select::TournamentSelector;
+    use sim::seq::Simulator;
+    use sim::{Builder, Simulation};
+    use std::f64::consts::PI;
+
+    /// `n` cities on a circle with radius 100. The optimal tour visits them in order.
+    fn circle(n: usize) -> Tsp {
+        let coordinates: Vec<(f64, f64)> = (0..n)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / n as f64;
+                (100.0 * angle.cos(), 100.0 * angle.sin())
+            })
+            .collect();
+        Tsp::from_coordinates(&coordinates)
+    }
+
+    fn optimum(tsp: &Tsp) -> f64 {
+        tsp.length(&Tour::new((0..tsp.cities()).collect()))
+    }
+
+    #[test]
+    fn test_length() {
+        let tsp = Tsp::from_coordinates(&[(0.0, 0.0), (3.0, 0.0), (3.0, 4.0)]);
+        assert_eq!(tsp.length(&Tour::new(vec![0, 1, 2])), 12.0);
+    }
+
+    #[test]
+    fn test_parse_euc_2d() {
+        let input = "NAME : square\nTYPE : TSP\nDIMENSION : 4\nEDGE_WEIGHT_TYPE : EUC_2D\n\
+                     NODE_COORD_SECTION\n1 0 0\n2 0 10\n3 10 10\n4 10 0\nEOF\n";
+        let tsp = Tsp::parse_tsplib(input).unwrap();
+        assert_eq!(tsp.cities(), 4);
+        assert_eq!(tsp.distance(0, 2), 14.0);
+        assert_eq!(tsp.length(&Tour::new(vec![0, 1, 2, 3])), 40.0);
+    }
+
+    #[test]
+    fn test_parse_full_matrix() {
+        let input = "DIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\n\
+                     EDGE_WEIGHT_SECTION\n0 1 2\n1 0 3\n2 3 0\nEOF";
+        let tsp = Tsp::parse_tsplib(input).unwrap();
+        assert_eq!(tsp.distance(1, 2), 3.0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Tsp::parse_tsplib("EDGE_WEIGHT_TYPE: EUC_2D").is_err());
+        assert!(Tsp::parse_tsplib("DIMENSION: 2\nEDGE_WEIGHT_TYPE: GEO").is_err());
+        let missing = "DIMENSION: 3\nEDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n1 0 0\nEOF";
+        assert!(Tsp::parse_tsplib(missing).is_err());
+        let empty = "DIMENSION: 0\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\n\
+                     EDGE_WEIGHT_SECTION\nEOF";
+        assert_eq!(
+            Tsp::parse_tsplib(empty),
+            Err(String::from("Invalid dimension: 0."))
+        );
+    }
+
+    #[test]
+    fn test_two_opt_finds_optimum() {
+        // For cities in convex position, every 2-optimal tour is optimal.
+        let tsp = circle(12);
+        for _ in 0..10 {
+            let tour = tsp.two_opt(&tsp.random_tour(&mut ::rand::thread_rng()));
+            assert!((tsp.length(&tour) - optimum(&tsp)).abs() < 1e-6);
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    struct Route {
+        tour: Tour,
+    }
+
+    impl Genome for Route {
+        type Gene = usize;
+
+        fn genes(&self) -> &[usize] {
+            self.tour.cities()
+        }
+
+        fn with_genes(&self, genes: Vec<usize>) -> Route {
+            Route {
+                tour: Tour::new(genes),
+            }
+        }
+    }
+
+    impl Phenotype<i64> for Route {
+        fn fitness(&self) -> i64 {
+            -(circle(8).length(&self.tour).round() as i64)
+        }
+
+        fn crossover(&self, other: &Route) -> Route {
+            PrecedenceCrossover::new().crossover(self, other)
+        }
+
+        fn mutate(&self) -> Route {
+            SwapMutation::new().mutate(self)
+        }
+    }
+
+    #[test]
+    fn test_convergence() {
+        let tsp = circle(8);
+        let mut population: Vec<Route> = (0..100)
+            .map(|_| Route {
+                tour: tsp.random_tour(&mut ::rand::thread_rng()),
+            })
+            .collect();
+        let mut builder = Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(TournamentSelector::new_checked(20, 5).unwrap()))
+            .with_elitism(1)
+            .with_max_iters(300);
+        let mut s = builder.build();
+        s.run();
+        let best = s.get().unwrap();
+        assert_eq!(best.fitness(), -(optimum(&tsp).round() as i64));
+    }
+}
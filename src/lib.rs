@@ -35,16 +35,31 @@
 //! # Features
 //! ## Available Simulators
 //!
-//! There is currently only one, sequential, simulator. This simulator will run
-//! the genetic algorithm on a single thread.
+//! There are four simulators. The sequential simulator (`sim::seq`) runs the genetic
+//! algorithm on a single thread. The parallel simulator (`sim::par`) evaluates fitness
+//! and performs crossover and mutation on the rayon thread pool, which is worthwhile
+//! when these operations are expensive. It supports a subset of the settings of the
+//! sequential simulator.
+//!
+//! The island model simulator (`sim::island`) splits the population into islands
+//! that evolve independently on the rayon thread pool, and periodically migrates
+//! the best individuals between them.
+//! Both run on the global rayon pool by default; `with_threads` and `with_thread_pool`
+//! select another one, e.g. to share the pool of the application.
+//!
+//! The evolution strategy simulator (`sim::es`) implements (μ+λ) and (μ,λ) evolution
+//! strategies, where μ parents create λ offspring and only the fittest survive.
 //!
 //! ## Available Selection Types
 //!
-//! There are currently four selection types available:
+//! There are currently six selection types available:
 //!
 //! * Maximize
 //! * Tournament
 //! * Stochastic
+//! * Roulette
+//! * Rank
+//! * Boltzmann
 //!
 //! There is a short explanation for each of these below. For more information, look at the
 //! documentation of individual selectors.
@@ -65,11 +80,71 @@
 //!
 //! Stochastic takes 1 parameter: the count. The resulting number of parents is `count`.
 //!
+//! ### Roulette
+//!
+//! Roulette takes 1 parameter: the count. Parents are selected with a probability
+//! proportional to their fitness, which requires a `NumericFitness`.
+//! The resulting number of parents is `count`.
+//!
+//! ### Rank
+//!
+//! Rank takes 1 parameter: the count. Parents are selected with a probability
+//! proportional to their rank in the population. The resulting number of parents is `count`.
+//!
+//! ### Boltzmann
+//!
+//! Boltzmann takes 3 parameters: the count, an initial temperature and a cooling factor.
+//! Parents are selected with a probability proportional to `exp(fitness / temperature)`,
+//! where the temperature decreases every generation. The resulting number of parents is `count`.
+//!
 //! ## Early Stopping
 //!
 //! If you wish, you can stop early if the fitness value of the best performing Phenotype
 //! doesn't improve by a large amount for a number of iterations. This can be done by calling the
 //! `set_early_stop(delta: Fitness, n_iters: u32)` function on the `SimulatorBuilder`.
+//! Calling it more than once adds multiple conditions; the simulator stops as soon
+//! as any of them is met.
+//!
+//! More general stopping conditions, e.g. a time limit or a target fitness, are found in
+//! `sim::termination`. They can be combined with `Any` and `All`, and are set on the
+//! sequential `SimulatorBuilder` using `with_termination`.
+//!
+//! ## Genetic Operators
+//!
+//! By default, children are created with `Phenotype::crossover` and `Phenotype::mutate`.
+//! A `CrossoverOperator` (in `sim::crossover`) or a `MutationOperator` (in `sim::mutation`)
+//! can be set on the `SimulatorBuilder` instead. One-point, two-point and uniform
+//! crossover are provided for phenotypes that implement `Genome`.
+//!
+//! ## Replacement
+//!
+//! After every iteration, the children replace part of the population. The sequential
+//! `SimulatorBuilder` accepts a `ReplacementPolicy` (in `sim::replace`) that decides which
+//! individuals are replaced: random ones (the default), the worst, the oldest, or the
+//! entire population.
+//!
+//! ## Reproducibility
+//!
+//! The sequential `SimulatorBuilder` accepts a seed with `with_seed`. Selection, replacement
+//! and the built-in operators then draw from a single seeded random number generator, so
+//! runs can be repeated exactly. A random `Phenotype` should implement `crossover_with_rng`
+//! and `mutate_with_rng` to draw from the same generator. The generator is a `StdRng` by default; any other `Rng`,
+//! such as a fast `XorShiftRng` or a deterministic generator in tests, can be passed to
+//! `seq::Simulator::builder_with_rng`.
+//!
+//! ## Checkpoints
+//!
+//! The sequential `Simulator` can save its state in a `Checkpoint` (in `sim::checkpoint`),
+//! and a new `Simulator` can resume from it. With the `serde` feature enabled, checkpoints
+//! can be serialized in any format supported by serde. Every checkpoint records the version
+//! of its format, and checkpoints written by a newer version of this crate are refused.
+//! Checkpoints are streamed to a `CheckpointWriter`, e.g. a plain file or, with the
+//! `gzip` feature, a compressed one.
+//!
+//! ## Pipelines
+//!
+//! A `Pipeline` (in `sim::pipeline`) runs several simulator stages one after another
+//! on the same population, each stage with its own selector and termination settings.
 //!
 //! # Examples
 //!
@@ -83,7 +158,7 @@
 //! use rsgenetic::pheno::*;
 //! use std::cmp::Ordering;
 //!
-//! #[derive(Eq, PartialEq, PartialOrd, Ord)]
+//! #[derive(Clone, Eq, PartialEq, PartialOrd, Ord)]
 //! struct MyFitness {
 //!     value: i32,
 //! }
@@ -185,13 +260,27 @@
     unused_qualifications
 )]
 
+#[cfg(feature = "gzip")]
+extern crate flate2;
 extern crate rand;
 extern crate rayon;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
 
+/// Contains tools to analyse problems and operators on a sample population.
+pub mod analysis;
+/// Contains the error type of this crate.
+pub mod error;
+/// Contains ready-made genome representations.
+pub mod genomes;
 /// Contains the definition of a Phenotype.
 pub mod pheno;
 /// Contains implementations of Simulators, which can run genetic algorithms.
 pub mod sim;
+/// Contains statistics about populations.
+pub mod stats;
 /// Contains code used by unit tests.
 #[cfg(test)]
 mod test;
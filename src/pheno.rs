@@ -14,13 +14,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use rand::Rng;
+use stats::CacheStats;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
 /// A `Fitness` value is used to determine the quality of a `Phenotype`.
 /// `Fitness` values should have an ordering.
 ///
+/// `Fitness` values must be `Clone`, because the simulators keep copies of them,
+/// e.g. for early stopping or the hall of fame. This supertrait was added after
+/// version 1.8, so custom fitness types written for it may need to derive `Clone`.
+///
 /// **Make sure the following statement holds:**
 /// A `Phenotype` with a `Fitness` value of `f1` performs better than
 /// another `Phenotype` with a `Fitness` value of `f2` iff `f1 > f2`.
-pub trait Fitness: Ord + Eq {
+pub trait Fitness: Clone + Ord + Eq {
     /// Get the zero value of this `Fitness` value.
     /// The internal value should be 0.
     fn zero() -> Self;
@@ -44,4 +54,226 @@ where
     fn crossover(&self, other: &Self) -> Self;
     /// Perform mutation on this Phenotype, returning a new Phenotype.
     fn mutate(&self) -> Self;
+
+    /// Perform crossover on this Phenotype, making every random decision with `rng`.
+    ///
+    /// The sequential simulator calls this function with its own random number
+    /// generator, so that seeded runs are reproducible. By default, `rng` is ignored
+    /// and `crossover` is called, so a random `crossover` should override this function.
+    fn crossover_with_rng(&self, other: &Self, rng: &mut dyn Rng) -> Self {
+        let _ = rng;
+        self.crossover(other)
+    }
+
+    /// Perform mutation on this Phenotype, making every random decision with `rng`.
+    ///
+    /// By default, `rng` is ignored and `mutate` is called, so a random `mutate`
+    /// should override this function.
+    fn mutate_with_rng(&self, rng: &mut dyn Rng) -> Self {
+        let _ = rng;
+        self.mutate()
+    }
+}
+
+/// A `Fitness` value that can be converted to a floating point number.
+///
+/// This is needed by code that does arithmetic on fitness values, such as
+/// computing averages or correlations. It is implemented for the primitive
+/// integer types in the `types` module.
+pub trait NumericFitness: Fitness {
+    /// Convert this `Fitness` value to an `f64`.
+    fn to_f64(&self) -> f64;
+}
+
+/// A `Phenotype` whose genotype is a sequence of genes.
+///
+/// Implementing this trait makes the generic operators in `sim::crossover`
+/// available for a phenotype, e.g. for bit strings or vectors of numbers.
+pub trait Genome: Clone {
+    /// The type of a single gene.
+    type Gene: Clone;
+    /// Get the genes of this genome.
+    fn genes(&self) -> &[Self::Gene];
+    /// Create a new genome of the same kind as this one, with the given genes.
+    ///
+    /// Any state of `self` that is not part of its genes should be carried over.
+    fn with_genes(&self, genes: Vec<Self::Gene>) -> Self;
+}
+
+/// Shared storage for the fitness values of `CachedFitness` phenotypes.
+///
+/// Phenotypes wrapped by the same cache share their fitness values, so a genome
+/// that reappears in a later generation, e.g. after a neutral mutation, is not
+/// evaluated again. The cache grows without bound; create a new one to clear it.
+#[derive(Debug)]
+pub struct FitnessCache<T, F> {
+    state: Mutex<CacheState<T, F>>,
+}
+
+#[derive(Debug)]
+struct CacheState<T, F> {
+    values: HashMap<T, F>,
+    stats: CacheStats,
+}
+
+impl<T, F> FitnessCache<T, F>
+where
+    T: Phenotype<F> + Hash + Eq,
+    F: Fitness,
+{
+    /// Create a new, empty cache.
+    ///
+    /// Phenotypes share the cache through an `Arc`, see `CachedFitness::new`.
+    pub fn new() -> FitnessCache<T, F> {
+        FitnessCache {
+            state: Mutex::new(CacheState {
+                values: HashMap::new(),
+                stats: CacheStats::default(),
+            }),
+        }
+    }
+
+    /// Get the number of cache hits and misses so far.
+    pub fn stats(&self) -> CacheStats {
+        self.lock().stats
+    }
+
+    /// Get the number of distinct genomes whose fitness is stored.
+    pub fn len(&self) -> usize {
+        self.lock().values.len()
+    }
+
+    /// Check whether no fitness values are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState<T, F>> {
+        // A panicking fitness function cannot leave the map in an invalid state.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn fitness(&self, inner: &T) -> F {
+        if let Some(f) = {
+            let mut state = self.lock();
+            let hit = state.values.get(inner).cloned();
+            if hit.is_some() {
+                state.stats.hits += 1;
+            }
+            hit
+        } {
+            return f;
+        }
+        // Evaluate without holding the lock, so other threads are not blocked.
+        let f = inner.fitness();
+        let mut state = self.lock();
+        state.stats.misses += 1;
+        state.values.insert(inner.clone(), f.clone());
+        f
+    }
+}
+
+impl<T, F> Default for FitnessCache<T, F>
+where
+    T: Phenotype<F> + Hash + Eq,
+    F: Fitness,
+{
+    fn default() -> Self {
+        FitnessCache::new()
+    }
+}
+
+/// A `Phenotype` whose fitness values are memoized in a `FitnessCache`.
+///
+/// Crossover and mutation are delegated to the wrapped phenotype, and the
+/// children share the cache of their parents. This is opt-in, because it only
+/// pays off when fitness evaluation is expensive compared to hashing.
+///
+/// ```
+/// use rsgenetic::pheno::*;
+/// use std::sync::Arc;
+///
+/// #[derive(Clone, Hash, PartialEq, Eq)]
+/// struct Word(u32);
+///
+/// impl Phenotype<i32> for Word {
+///     fn fitness(&self) -> i32 {
+///         self.0.count_ones() as i32
+///     }
+///     fn crossover(&self, other: &Word) -> Word {
+///         Word(self.0 & other.0)
+///     }
+///     fn mutate(&self) -> Word {
+///         Word(self.0 ^ 1)
+///     }
+/// }
+///
+/// let cache = Arc::new(FitnessCache::new());
+/// let a = CachedFitness::new(&cache, Word(7));
+/// assert_eq!(a.fitness(), 3);
+/// assert_eq!(a.mutate().mutate().fitness(), 3);
+/// assert_eq!(cache.stats().hits, 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CachedFitness<T, F> {
+    inner: T,
+    cache: Arc<FitnessCache<T, F>>,
+}
+
+impl<T, F> CachedFitness<T, F> {
+    /// Wrap a phenotype, so that its fitness is looked up in `cache`.
+    pub fn new(cache: &Arc<FitnessCache<T, F>>, inner: T) -> CachedFitness<T, F> {
+        CachedFitness {
+            inner,
+            cache: Arc::clone(cache),
+        }
+    }
+
+    /// Get the wrapped phenotype.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Get the cache this phenotype uses.
+    pub fn cache(&self) -> &Arc<FitnessCache<T, F>> {
+        &self.cache
+    }
+}
+
+impl<T, F> Phenotype<F> for CachedFitness<T, F>
+where
+    T: Phenotype<F> + Hash + Eq,
+    F: Fitness,
+{
+    fn fitness(&self) -> F {
+        self.cache.fitness(&self.inner)
+    }
+
+    fn crossover(&self, other: &Self) -> Self {
+        CachedFitness {
+            inner: self.inner.crossover(&other.inner),
+            cache: Arc::clone(&self.cache),
+        }
+    }
+
+    fn mutate(&self) -> Self {
+        CachedFitness {
+            inner: self.inner.mutate(),
+            cache: Arc::clone(&self.cache),
+        }
+    }
+
+    fn crossover_with_rng(&self, other: &Self, rng: &mut dyn Rng) -> Self {
+        CachedFitness {
+            inner: self.inner.crossover_with_rng(&other.inner, rng),
+            cache: Arc::clone(&self.cache),
+        }
+    }
+
+    fn mutate_with_rng(&self, rng: &mut dyn Rng) -> Self {
+        CachedFitness {
+            inner: self.inner.mutate_with_rng(rng),
+            cache: Arc::clone(&self.cache),
+        }
+    }
 }
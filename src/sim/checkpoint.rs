@@ -0,0 +1,619 @@
+// file: checkpoint.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains `Checkpoint`, the saved state of a sequential `Simulator`.
+//!
+//! A checkpoint holds the population and the counters of a run. It is created with
+//! `seq::Simulator::checkpoint`, and a new simulator resumes from it with
+//! `seq::SimulatorBuilder::with_checkpoint`. The selector, the operators and the
+//! other settings are not part of a checkpoint: they are configured on the builder
+//! as usual.
+//!
+//! With the `serde` feature enabled, a checkpoint can be serialized in any format
+//! supported by serde. Every checkpoint contains a `version` field. Deserializing a
+//! checkpoint of a version this crate does not know fails with an error that says
+//! so, instead of producing a corrupt state. The population of such a checkpoint
+//! can still be recovered with `PopulationOnly`, as long as the individuals
+//! themselves can be deserialized.
+//!
+//! Checkpoints are written as JSON, which is streamed to a `CheckpointWriter`
+//! rather than built up in memory first. `FileWriter` writes to a plain file,
+//! `GzipWriter` compresses the output of another writer (with the `gzip` feature),
+//! and `StreamWriter` writes to any `Write` you provide, e.g. an encrypting one.
+//! `AtomicFileWriter` replaces a file only once the new contents are completely written.
+//! `RotatingFileWriter` keeps the last few checkpoints in numbered files.
+//! `Checkpoint::read_from` reads a checkpoint back.
+//!
+//! `seq::SimulatorBuilder::with_checkpoint_every` saves checkpoints automatically
+//! while the simulator runs.
+
+#[cfg(feature = "gzip")]
+use flate2::{write::GzEncoder, Compression};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use serde_json;
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+#[cfg(feature = "serde")]
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::fs::{self, File};
+#[cfg(feature = "serde")]
+use std::io::{self, BufWriter, Read, Write};
+#[cfg(feature = "serde")]
+use std::path::PathBuf;
+
+/// The version of the checkpoint format written by this version of the crate.
+///
+/// It is incremented whenever the format changes.
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+/// The saved state of a sequential `Simulator`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "RawCheckpoint<T>",
+        bound(deserialize = "T: Deserialize<'de>")
+    )
+)]
+pub struct Checkpoint<T> {
+    version: u32,
+    iterations: u64,
+    evaluations: u64,
+    population: Vec<T>,
+}
+
+impl<T> Checkpoint<T> {
+    /// Create a checkpoint of the current format version.
+    pub fn new(iterations: u64, evaluations: u64, population: Vec<T>) -> Checkpoint<T> {
+        Checkpoint {
+            version: CHECKPOINT_VERSION,
+            iterations,
+            evaluations,
+            population,
+        }
+    }
+
+    /// Get the format version of this checkpoint.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Get the number of iterations that were executed.
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// Get the number of fitness evaluations that were made.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Get the population.
+    pub fn population(&self) -> &[T] {
+        &self.population
+    }
+
+    /// Get the population, consuming the checkpoint.
+    pub fn into_population(self) -> Vec<T> {
+        self.population
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> Checkpoint<T>
+where
+    T: Serialize,
+{
+    /// Write this checkpoint as JSON to `writer`.
+    pub fn save(&self, writer: &mut dyn CheckpointWriter) -> io::Result<()> {
+        writer.write(&mut |out| {
+            serde_json::to_writer(out, self)?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> Checkpoint<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    /// Read a checkpoint in JSON from `reader`.
+    ///
+    /// To read a compressed checkpoint, wrap `reader` in a decoder first,
+    /// e.g. `flate2::read::GzDecoder`.
+    pub fn read_from<R>(reader: R) -> io::Result<Checkpoint<T>>
+    where
+        R: Read,
+    {
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+/// A destination for checkpoints.
+///
+/// Every call to `write` stores a complete checkpoint. The writer decides how the
+/// output is opened and finished, so that it can e.g. compress or sync it.
+#[cfg(feature = "serde")]
+pub trait CheckpointWriter: fmt::Debug {
+    /// Call `serialize` with an output to write a checkpoint to, and finish the output.
+    fn write(
+        &mut self,
+        serialize: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<()>;
+}
+
+#[cfg(feature = "serde")]
+impl<W> CheckpointWriter for &mut W
+where
+    W: CheckpointWriter + ?Sized,
+{
+    fn write(
+        &mut self,
+        serialize: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<()> {
+        (**self).write(serialize)
+    }
+}
+
+/// Writes checkpoints to a file, which is overwritten every time.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug)]
+pub struct FileWriter {
+    path: PathBuf,
+}
+
+#[cfg(feature = "serde")]
+impl FileWriter {
+    /// Create a writer for the file at `path`.
+    pub fn new<P>(path: P) -> FileWriter
+    where
+        P: Into<PathBuf>,
+    {
+        FileWriter { path: path.into() }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl CheckpointWriter for FileWriter {
+    fn write(
+        &mut self,
+        serialize: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(&self.path)?);
+        serialize(&mut out)?;
+        out.flush()?;
+        out.get_ref().sync_all()
+    }
+}
+
+/// Writes checkpoints to a file, replacing it atomically.
+///
+/// The output is first written to a temporary file next to the target, which is
+/// synced to disk and then renamed. If the process crashes while writing, the
+/// target still holds the previous, complete contents.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug)]
+pub struct AtomicFileWriter {
+    path: PathBuf,
+}
+
+#[cfg(feature = "serde")]
+impl AtomicFileWriter {
+    /// Create a writer for the file at `path`.
+    pub fn new<P>(path: P) -> AtomicFileWriter
+    where
+        P: Into<PathBuf>,
+    {
+        AtomicFileWriter { path: path.into() }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl CheckpointWriter for AtomicFileWriter {
+    fn write(
+        &mut self,
+        serialize: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let temporary = PathBuf::from(temporary);
+        if let Err(e) = FileWriter::new(&temporary).write(serialize) {
+            let _ = fs::remove_file(&temporary);
+            return Err(e);
+        }
+        fs::rename(&temporary, &self.path)
+    }
+}
+
+/// Writes every checkpoint to a new numbered file, and keeps only the latest ones.
+///
+/// The files are named after `prefix`, with a sequence number appended:
+/// `run.json.1`, `run.json.2`, and so on. Once a file is completely written,
+/// the file that is `keep` checkpoints older is removed.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug)]
+pub struct RotatingFileWriter {
+    prefix: PathBuf,
+    keep: u64,
+    written: u64,
+}
+
+#[cfg(feature = "serde")]
+impl RotatingFileWriter {
+    /// Create a writer that keeps the last `keep` checkpoints.
+    ///
+    /// At least one checkpoint is always kept.
+    pub fn new<P>(prefix: P, keep: u64) -> RotatingFileWriter
+    where
+        P: Into<PathBuf>,
+    {
+        RotatingFileWriter {
+            prefix: prefix.into(),
+            keep: keep.max(1),
+            written: 0,
+        }
+    }
+
+    /// Get the path of the latest checkpoint, or `None` if none was written yet.
+    pub fn latest(&self) -> Option<PathBuf> {
+        if self.written == 0 {
+            None
+        } else {
+            Some(self.path(self.written))
+        }
+    }
+
+    fn path(&self, number: u64) -> PathBuf {
+        let mut path = self.prefix.clone().into_os_string();
+        path.push(format!(".{}", number));
+        PathBuf::from(path)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl CheckpointWriter for RotatingFileWriter {
+    fn write(
+        &mut self,
+        serialize: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let number = self.written + 1;
+        FileWriter::new(self.path(number)).write(serialize)?;
+        self.written = number;
+        if number > self.keep {
+            match fs::remove_file(self.path(number - self.keep)) {
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => {}
+                result => result?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compresses checkpoints with gzip before passing them to another writer.
+#[cfg(feature = "gzip")]
+#[derive(Clone, Debug)]
+pub struct GzipWriter<W> {
+    inner: W,
+    level: Compression,
+}
+
+#[cfg(feature = "gzip")]
+impl<W> GzipWriter<W>
+where
+    W: CheckpointWriter,
+{
+    /// Create a writer that compresses with the default compression level.
+    pub fn new(inner: W) -> GzipWriter<W> {
+        GzipWriter {
+            inner,
+            level: Compression::default(),
+        }
+    }
+
+    /// Set the compression level, from 0 (none) to 9 (best).
+    pub fn with_level(mut self, level: u32) -> GzipWriter<W> {
+        self.level = Compression::new(level);
+        self
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<W> CheckpointWriter for GzipWriter<W>
+where
+    W: CheckpointWriter,
+{
+    fn write(
+        &mut self,
+        serialize: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let level = self.level;
+        self.inner.write(&mut |out| {
+            let mut encoder = GzEncoder::new(out, level);
+            serialize(&mut encoder)?;
+            encoder.try_finish()
+        })
+    }
+}
+
+/// Writes checkpoints to outputs created by a closure.
+///
+/// The closure is called once for every checkpoint, so it can e.g. create a new
+/// file each time, or wrap the output in an encrypting `Write`.
+#[cfg(feature = "serde")]
+pub struct StreamWriter<F> {
+    open: F,
+}
+
+#[cfg(feature = "serde")]
+impl<F, W> StreamWriter<F>
+where
+    F: FnMut() -> io::Result<W>,
+    W: Write,
+{
+    /// Create a writer that writes every checkpoint to the output returned by `open`.
+    pub fn new(open: F) -> StreamWriter<F> {
+        StreamWriter { open }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<F, W> CheckpointWriter for StreamWriter<F>
+where
+    F: FnMut() -> io::Result<W>,
+    W: Write,
+{
+    fn write(
+        &mut self,
+        serialize: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let mut out = (self.open)()?;
+        serialize(&mut out)?;
+        out.flush()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<F> fmt::Debug for StreamWriter<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("StreamWriter")
+    }
+}
+
+/// A checkpoint of any version, as it is read before its version is checked.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawCheckpoint<T> {
+    version: u32,
+    #[serde(default)]
+    iterations: u64,
+    #[serde(default)]
+    evaluations: u64,
+    population: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<RawCheckpoint<T>> for Checkpoint<T> {
+    type Error = String;
+
+    fn try_from(raw: RawCheckpoint<T>) -> Result<Checkpoint<T>, String> {
+        // Migrations from older versions go here when the format changes.
+        if raw.version == CHECKPOINT_VERSION {
+            Ok(Checkpoint {
+                version: raw.version,
+                iterations: raw.iterations,
+                evaluations: raw.evaluations,
+                population: raw.population,
+            })
+        } else {
+            Err(format!(
+                "Unsupported checkpoint version: {}. This version of rsgenetic reads \
+                 checkpoint version {}. Use `PopulationOnly` to recover the population.",
+                raw.version, CHECKPOINT_VERSION
+            ))
+        }
+    }
+}
+
+/// The population of a checkpoint of any version.
+///
+/// Deserialize a checkpoint as `PopulationOnly` to recover the population from a
+/// checkpoint that can no longer be read as a `Checkpoint`. The other fields are ignored.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, Deserialize)]
+pub struct PopulationOnly<T> {
+    /// The format version of the checkpoint.
+    pub version: u32,
+    /// The population.
+    pub population: Vec<T>,
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use sim::select::*;
+    use sim::*;
+    use std::cell::RefCell;
+    use std::path::Path;
+    use std::rc::Rc;
+    use std::{env, process};
+    use test::Test;
+
+    /// An output that appends to a buffer the test can inspect.
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut population: Vec<Test> = (0..20).map(|i| Test { f: i }).collect();
+        let checkpoint = {
+            let mut builder = seq::Simulator::builder(&mut population);
+            builder
+                .with_selector(Box::new(StochasticSelector::new(4)))
+                .with_max_iters(3);
+            let mut s = builder.build();
+            s.run();
+            s.checkpoint()
+        };
+        let json = serde_json::to_string(&checkpoint).unwrap();
+        let restored: Checkpoint<Test> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, checkpoint);
+
+        let mut population = Vec::new();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(4)))
+            .with_max_iters(5)
+            .with_checkpoint(restored);
+        let mut s = builder.build();
+        assert_eq!(s.iterations(), 3);
+        s.run();
+        assert_eq!(s.iterations(), 5);
+        // The initial population is evaluated again when resuming, since its fitness
+        // is not part of the checkpoint.
+        assert_eq!(s.evaluations(), 20 * (1 + 3) + 20 * (1 + 2));
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let json = r#"{"version":2,"iterations":1,"extra":true,"population":[{"f":3}]}"#;
+        let err = serde_json::from_str::<Checkpoint<Test>>(json).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Unsupported checkpoint version: 2"));
+        let recovered: PopulationOnly<Test> = serde_json::from_str(json).unwrap();
+        assert_eq!(recovered.version, 2);
+        assert_eq!(recovered.population, vec![Test { f: 3 }]);
+    }
+
+    #[test]
+    fn test_stream_writer() {
+        let written = Rc::new(RefCell::new(Vec::new()));
+        let mut writer = {
+            let written = Rc::clone(&written);
+            StreamWriter::new(move || Ok(SharedBuffer(Rc::clone(&written))))
+        };
+        let checkpoint = Checkpoint::new(4, 10, vec![Test { f: 1 }, Test { f: -2 }]);
+        checkpoint.save(&mut writer).unwrap();
+        let restored: Checkpoint<Test> = Checkpoint::read_from(&written.borrow()[..]).unwrap();
+        assert_eq!(restored, checkpoint);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_writer() {
+        use flate2::read::GzDecoder;
+
+        let written = Rc::new(RefCell::new(Vec::new()));
+        let mut writer = {
+            let written = Rc::clone(&written);
+            GzipWriter::new(StreamWriter::new(move || {
+                Ok(SharedBuffer(Rc::clone(&written)))
+            }))
+        };
+        let checkpoint = Checkpoint::new(0, 1000, vec![Test { f: 7 }; 1000]);
+        checkpoint.save(&mut writer).unwrap();
+        let json = serde_json::to_vec(&checkpoint).unwrap();
+        assert!(written.borrow().len() < json.len() / 10);
+        let restored: Checkpoint<Test> =
+            Checkpoint::read_from(GzDecoder::new(&written.borrow()[..])).unwrap();
+        assert_eq!(restored, checkpoint);
+    }
+
+    #[test]
+    fn test_file_writer() {
+        let path = env::temp_dir().join(format!("rsgenetic-checkpoint-{}.json", process::id()));
+        let checkpoint = Checkpoint::new(1, 2, vec![Test { f: 3 }]);
+        checkpoint.save(&mut FileWriter::new(&path)).unwrap();
+        let restored: Checkpoint<Test> = Checkpoint::read_from(File::open(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(restored, checkpoint);
+    }
+
+    #[test]
+    fn test_checkpoint_every() {
+        let prefix = env::temp_dir().join(format!("rsgenetic-rotating-{}.json", process::id()));
+        let mut writer = RotatingFileWriter::new(&prefix, 2);
+        let mut population: Vec<Test> = (0..20).map(|i| Test { f: i }).collect();
+        {
+            let mut builder = seq::Simulator::builder(&mut population);
+            builder
+                .with_selector(Box::new(StochasticSelector::new(4)))
+                .with_checkpoint_every(3, &mut writer)
+                .with_max_iters(10);
+            assert_eq!(builder.build().run(), RunResult::Done);
+        }
+        let path = |n: u64| {
+            let mut path = prefix.clone().into_os_string();
+            path.push(format!(".{}", n));
+            path
+        };
+        assert_eq!(writer.latest(), Some(path(3).into()));
+        assert!(!Path::new(&path(1)).exists());
+        let restored: Checkpoint<Test> =
+            Checkpoint::read_from(File::open(path(3)).unwrap()).unwrap();
+        assert_eq!(restored.iterations(), 9);
+        fs::remove_file(path(2)).unwrap();
+        fs::remove_file(path(3)).unwrap();
+    }
+
+    #[test]
+    fn test_checkpoint_write_error() {
+        let mut population: Vec<Test> = (0..20).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(4)))
+            .with_checkpoint_every(
+                1,
+                StreamWriter::new(|| -> io::Result<Vec<u8>> { Err(io::Error::other("disk full")) }),
+            );
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Failure);
+        assert!(s.get().unwrap_err().to_string().contains("disk full"));
+    }
+
+    #[test]
+    fn test_best_persistence() {
+        let path = env::temp_dir().join(format!("rsgenetic-best-{}.json", process::id()));
+        let mut population: Vec<Test> = (0..20).map(|i| Test { f: i - 10 }).collect();
+        {
+            let mut builder = seq::Simulator::builder(&mut population);
+            builder
+                .with_selector(Box::new(StochasticSelector::new(4)))
+                .with_best_persistence(AtomicFileWriter::new(&path))
+                .with_max_iters(5);
+            builder.build().run();
+        }
+        let persisted: Test = serde_json::from_reader(File::open(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        // No operator of `Test` can improve on the best initial individual.
+        assert_eq!(persisted, Test { f: -10 });
+    }
+}
@@ -0,0 +1,323 @@
+// file: crossover.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The crossover module provides a trait that can be implemented to recombine
+//! phenotypes independently of their `Phenotype::crossover` implementation.
+//!
+//! An operator is configured on a simulator builder using `with_crossover`.
+//! This module also provides the classic operators for phenotypes that implement
+//! `Genome`. When the parents have different lengths, crossover points are chosen
+//! within the shorter parent and the remaining genes are taken from the parent that
+//! contributes the last segment.
+//!
+//! A `Linkage` can be set on the built-in operators to keep groups of genes together.
+
+use pheno::Genome;
+use rand::Rng;
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+/// A `CrossoverOperator` creates a child from two parents.
+///
+/// Operators must be `Send` and `Sync`, so that boxed operators can be shared
+/// between threads.
+pub trait CrossoverOperator<T>: Debug + Send + Sync {
+    /// Create a new phenotype from the parents `a` and `b`.
+    fn crossover(&self, a: &T, b: &T) -> T;
+
+    /// Create a new phenotype from the parents `a` and `b`, making every random
+    /// decision with `rng`.
+    ///
+    /// Simulators that can be seeded call this function, so that runs are reproducible.
+    /// By default, `rng` is ignored and `crossover` is called, so an operator that uses
+    /// its own random number generator should override this function.
+    fn crossover_with_rng(&self, a: &T, b: &T, rng: &mut dyn Rng) -> T {
+        let _ = rng;
+        self.crossover(a, b)
+    }
+}
+
+/// Describes which genes of a `Genome` are always inherited together.
+///
+/// Every locus is assigned a group. When a crossover operator has a linkage,
+/// all genes of a group are taken from the same parent, namely the parent that
+/// supplies the first gene of the group. Loci beyond the end of the group list
+/// are not linked to any other locus.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Linkage {
+    groups: Vec<usize>,
+}
+
+impl Linkage {
+    /// Create a linkage from the group of every locus.
+    ///
+    /// Loci with the same group are inherited together. Groups do not need to be
+    /// contiguous, e.g. `vec![0, 1, 0, 1]` links the first and third gene, and the
+    /// second and fourth gene.
+    pub fn new(groups: Vec<usize>) -> Linkage {
+        Linkage { groups }
+    }
+
+    /// Create a linkage of contiguous blocks with the given sizes.
+    ///
+    /// For example, `&[2, 3]` links the first two genes and the next three genes.
+    pub fn from_blocks(sizes: &[usize]) -> Linkage {
+        let groups = sizes
+            .iter()
+            .enumerate()
+            .flat_map(|(group, &size)| ::std::iter::repeat(group).take(size))
+            .collect();
+        Linkage { groups }
+    }
+
+    /// Make every group in the first `len` loci of `from_b` come from a single parent.
+    fn apply(&self, from_b: &mut [bool], len: usize) {
+        let mut sources: HashMap<usize, bool> = HashMap::new();
+        for (locus, &group) in self.groups.iter().enumerate().take(len) {
+            let source = *sources.entry(group).or_insert(from_b[locus]);
+            from_b[locus] = source;
+        }
+    }
+}
+
+/// Create a child that takes the gene at locus `i` from `b` if `from_b[i]` holds,
+/// and from `a` otherwise, after applying `linkage`.
+///
+/// `from_b` determines the length of the child. Loci at which only one parent has
+/// a gene must take it from that parent.
+fn assemble<T>(a: &T, b: &T, mut from_b: Vec<bool>, linkage: &Option<Linkage>) -> T
+where
+    T: Genome,
+{
+    let (x, y) = (a.genes(), b.genes());
+    if let Some(ref linkage) = *linkage {
+        linkage.apply(&mut from_b, x.len().min(y.len()));
+    }
+    let genes = from_b
+        .iter()
+        .enumerate()
+        .map(|(i, &b)| if b { y[i].clone() } else { x[i].clone() })
+        .collect();
+    a.with_genes(genes)
+}
+
+/// Picks a single crossover point. The child takes the genes before it from
+/// the first parent, and the genes after it from the second parent.
+#[derive(Clone, Debug, Default)]
+pub struct OnePointCrossover {
+    linkage: Option<Linkage>,
+}
+
+impl OnePointCrossover {
+    /// Create and return a one-point crossover operator.
+    pub fn new() -> OnePointCrossover {
+        OnePointCrossover { linkage: None }
+    }
+
+    /// Keep the genes of every group of `linkage` together.
+    pub fn with_linkage(mut self, linkage: Linkage) -> OnePointCrossover {
+        self.linkage = Some(linkage);
+        self
+    }
+}
+
+impl<T> CrossoverOperator<T> for OnePointCrossover
+where
+    T: Genome,
+{
+    fn crossover(&self, a: &T, b: &T) -> T {
+        self.crossover_with_rng(a, b, &mut ::rand::thread_rng())
+    }
+
+    fn crossover_with_rng(&self, a: &T, b: &T, mut rng: &mut dyn Rng) -> T {
+        let rng = &mut rng;
+        let (x, y) = (a.genes(), b.genes());
+        let point = rng.gen_range(0, x.len().min(y.len()) + 1);
+        let from_b = (0..y.len()).map(|i| i >= point).collect();
+        assemble(a, b, from_b, &self.linkage)
+    }
+}
+
+/// Picks two crossover points. The child takes the genes between them from the
+/// second parent, and all other genes from the first parent.
+#[derive(Clone, Debug, Default)]
+pub struct TwoPointCrossover {
+    linkage: Option<Linkage>,
+}
+
+impl TwoPointCrossover {
+    /// Create and return a two-point crossover operator.
+    pub fn new() -> TwoPointCrossover {
+        TwoPointCrossover { linkage: None }
+    }
+
+    /// Keep the genes of every group of `linkage` together.
+    pub fn with_linkage(mut self, linkage: Linkage) -> TwoPointCrossover {
+        self.linkage = Some(linkage);
+        self
+    }
+}
+
+impl<T> CrossoverOperator<T> for TwoPointCrossover
+where
+    T: Genome,
+{
+    fn crossover(&self, a: &T, b: &T) -> T {
+        self.crossover_with_rng(a, b, &mut ::rand::thread_rng())
+    }
+
+    fn crossover_with_rng(&self, a: &T, b: &T, mut rng: &mut dyn Rng) -> T {
+        let rng = &mut rng;
+        let (x, y) = (a.genes(), b.genes());
+        let len = x.len().min(y.len());
+        let i = rng.gen_range(0, len + 1);
+        let j = rng.gen_range(0, len + 1);
+        let (start, end) = if i <= j { (i, j) } else { (j, i) };
+        let from_b = (0..x.len()).map(|i| start <= i && i < end).collect();
+        assemble(a, b, from_b, &self.linkage)
+    }
+}
+
+/// Takes every gene from either parent with equal probability.
+///
+/// Genes beyond the length of the shorter parent are taken from the first parent.
+#[derive(Clone, Debug, Default)]
+pub struct UniformCrossover {
+    linkage: Option<Linkage>,
+}
+
+impl UniformCrossover {
+    /// Create and return a uniform crossover operator.
+    pub fn new() -> UniformCrossover {
+        UniformCrossover { linkage: None }
+    }
+
+    /// Keep the genes of every group of `linkage` together.
+    pub fn with_linkage(mut self, linkage: Linkage) -> UniformCrossover {
+        self.linkage = Some(linkage);
+        self
+    }
+}
+
+impl<T> CrossoverOperator<T> for UniformCrossover
+where
+    T: Genome,
+{
+    fn crossover(&self, a: &T, b: &T) -> T {
+        self.crossover_with_rng(a, b, &mut ::rand::thread_rng())
+    }
+
+    fn crossover_with_rng(&self, a: &T, b: &T, mut rng: &mut dyn Rng) -> T {
+        let rng = &mut rng;
+        let (x, y) = (a.genes(), b.genes());
+        let from_b = (0..x.len())
+            .map(|i| i < y.len() && rng.gen::<bool>())
+            .collect();
+        assemble(a, b, from_b, &self.linkage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test::Genes;
+
+    fn parents() -> (Genes, Genes) {
+        (Genes { genes: vec![0; 10] }, Genes { genes: vec![1; 10] })
+    }
+
+    #[test]
+    fn test_one_point() {
+        let (a, b) = parents();
+        for _ in 0..100 {
+            let child = OnePointCrossover::new().crossover(&a, &b);
+            assert_eq!(child.genes.len(), 10);
+            // A prefix of zeros followed by a suffix of ones.
+            let point = child.genes.iter().take_while(|&&x| x == 0).count();
+            assert!(child.genes[point..].iter().all(|&x| x == 1));
+        }
+    }
+
+    #[test]
+    fn test_two_point() {
+        let (a, b) = parents();
+        for _ in 0..100 {
+            let child = TwoPointCrossover::new().crossover(&a, &b);
+            assert_eq!(child.genes.len(), 10);
+            let start = child.genes.iter().take_while(|&&x| x == 0).count();
+            let end = start + child.genes[start..].iter().take_while(|&&x| x == 1).count();
+            assert!(child.genes[end..].iter().all(|&x| x == 0));
+        }
+    }
+
+    #[test]
+    fn test_uniform() {
+        let (a, b) = parents();
+        let child = UniformCrossover::new().crossover(&a, &b);
+        assert_eq!(child.genes.len(), 10);
+        assert!(child.genes.iter().all(|&x| x == 0 || x == 1));
+    }
+
+    #[test]
+    fn test_different_lengths() {
+        let a = Genes { genes: vec![0; 3] };
+        let b = Genes { genes: vec![1; 8] };
+        let child = UniformCrossover::new().crossover(&a, &b);
+        assert_eq!(child.genes.len(), 3);
+        let child = TwoPointCrossover::new().crossover(&b, &a);
+        assert_eq!(child.genes.len(), 8);
+        for _ in 0..100 {
+            assert_eq!(OnePointCrossover::new().crossover(&a, &b).genes.len(), 8);
+        }
+    }
+
+    #[test]
+    fn test_linkage_blocks() {
+        let (a, b) = parents();
+        let op = UniformCrossover::new().with_linkage(Linkage::from_blocks(&[5, 5]));
+        for _ in 0..100 {
+            let child = op.crossover(&a, &b);
+            assert!(child.genes[..5].iter().all(|&x| x == child.genes[0]));
+            assert!(child.genes[5..].iter().all(|&x| x == child.genes[5]));
+        }
+    }
+
+    #[test]
+    fn test_linkage_interleaved() {
+        let (a, b) = parents();
+        let op = TwoPointCrossover::new().with_linkage(Linkage::new(vec![0, 1, 0, 1, 0, 1]));
+        for _ in 0..100 {
+            let child = op.crossover(&a, &b);
+            assert_eq!(child.genes[0], child.genes[2]);
+            assert_eq!(child.genes[2], child.genes[4]);
+            assert_eq!(child.genes[1], child.genes[3]);
+            assert_eq!(child.genes[3], child.genes[5]);
+        }
+    }
+
+    #[test]
+    fn test_linkage_different_lengths() {
+        let a = Genes { genes: vec![0; 3] };
+        let b = Genes { genes: vec![1; 8] };
+        let op = OnePointCrossover::new().with_linkage(Linkage::new(vec![0; 8]));
+        for _ in 0..100 {
+            let child = op.crossover(&a, &b);
+            assert_eq!(child.genes.len(), 8);
+            assert!(child.genes[..3].iter().all(|&x| x == child.genes[0]));
+            assert!(child.genes[3..].iter().all(|&x| x == 1));
+        }
+    }
+}
@@ -52,6 +52,11 @@ impl<F: Fitness> EarlyStopper<F> {
     pub fn reached(&self) -> bool {
         self.iter_limit.reached()
     }
+
+    /// Get the number of consecutive iterations without significant improvement.
+    pub fn stagnant(&self) -> u64 {
+        self.iter_limit.get()
+    }
 }
 
 #[cfg(test)]
@@ -71,9 +76,9 @@ mod tests {
         for _ in 0..4 {
             stopper.update(MyFitness::new(1));
         }
-        assert_eq!(stopper.reached(), false);
+        assert!(!stopper.reached());
         stopper.update(MyFitness::new(20));
-        assert_eq!(stopper.reached(), false);
+        assert!(!stopper.reached());
     }
 
     #[test]
@@ -84,4 +89,15 @@ mod tests {
         }
         assert!(stopper.reached());
     }
+
+    #[test]
+    fn test_early_stopper_stagnant() {
+        let mut stopper = EarlyStopper::new(MyFitness::new(10), 5);
+        for _ in 0..3 {
+            stopper.update(MyFitness::new(1));
+        }
+        assert_eq!(stopper.stagnant(), 3);
+        stopper.update(MyFitness::new(20));
+        assert_eq!(stopper.stagnant(), 0);
+    }
 }
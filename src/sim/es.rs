@@ -0,0 +1,375 @@
+// file: es.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains an evolution strategy implementation of `::sim::Simulation`,
+//! called a `Simulator`.
+//!
+//! In every iteration, the μ parents generate λ offspring. Every offspring is a mutated
+//! copy of a random parent, or, if a crossover operator is set, the mutated child of two
+//! random parents. The μ fittest individuals survive, chosen either from the parents
+//! and the offspring together, a (μ+λ)-ES, or from the offspring only, a (μ,λ)-ES.
+//! See `SurvivorSelection`.
+//!
+//! After every iteration, the population consists of the survivors, ordered from
+//! fittest to least fit.
+//!
+//! To use a `Simulator`, you need a `SimulatorBuilder`, which you can
+//! obtain by calling `Simulator::builder()`.
+
+use super::crossover::CrossoverOperator;
+use super::earlystopper::*;
+use super::iterlimit::*;
+use super::mutation::MutationOperator;
+use super::*;
+use pheno::Fitness;
+use pheno::Phenotype;
+use rand::{Rng, StdRng};
+use std::cmp::Reverse;
+use std::time::Instant;
+
+/// Determines from which individuals the survivors of an iteration are chosen.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SurvivorSelection {
+    /// The survivors are chosen from the parents and the offspring, i.e. a (μ+λ)-ES.
+    /// The best individual found so far never gets lost.
+    Plus,
+    /// The survivors are chosen from the offspring only, i.e. a (μ,λ)-ES.
+    /// Every individual lives for a single iteration, which helps to escape local optima.
+    /// λ must be at least μ.
+    Comma,
+}
+
+/// An evolution strategy implementation of `::sim::Simulation`.
+#[derive(Debug)]
+pub struct Simulator<'a, T, F>
+where
+    T: 'a + Phenotype<F>,
+    F: Fitness,
+{
+    population: &'a mut Vec<T>,
+    mu: Option<usize>,
+    lambda: Option<usize>,
+    survivors: SurvivorSelection,
+    iter_limit: IterLimit,
+    earlystoppers: Vec<EarlyStopper<F>>,
+    crossover: Option<Box<dyn CrossoverOperator<T>>>,
+    mutator: Option<Box<dyn MutationOperator<T>>>,
+    duration: Option<NanoSecond>,
+    error: Option<Error>,
+    rng: StdRng,
+}
+
+impl<'a, T, F> Simulator<'a, T, F>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    /// Get μ and λ, or an error message if they are invalid.
+    fn sizes(&self) -> Result<(usize, usize), Error> {
+        let mu = self.mu.unwrap_or(self.population.len());
+        let lambda = self.lambda.unwrap_or(7 * mu);
+        if mu == 0 || lambda == 0 {
+            return Err(Error::InvalidConfig(format!(
+                "Invalid parameters: μ = {}, λ = {}. Both should be larger than zero.",
+                mu, lambda
+            )));
+        }
+        if self.survivors == SurvivorSelection::Comma && lambda < mu {
+            return Err(Error::InvalidConfig(format!(
+                "Invalid parameters: μ = {}, λ = {}. λ should be at least μ for \
+                 comma selection.",
+                mu, lambda
+            )));
+        }
+        Ok((mu, lambda))
+    }
+
+    /// Create `lambda` offspring from the current population.
+    fn offspring(&mut self, lambda: usize) -> Vec<T> {
+        let rng = &mut self.rng;
+        let parents = &self.population[..];
+        let (crossover, mutator) = (&self.crossover, &self.mutator);
+        (0..lambda)
+            .map(|_| {
+                let a = &parents[rng.gen_range(0, parents.len())];
+                let child = match *crossover {
+                    Some(ref op) => {
+                        let b = &parents[rng.gen_range(0, parents.len())];
+                        op.crossover_with_rng(a, b, rng)
+                    }
+                    None => a.clone(),
+                };
+                match *mutator {
+                    Some(ref op) => op.mutate_with_rng(&child, rng),
+                    None => child.mutate_with_rng(rng),
+                }
+            })
+            .collect()
+    }
+}
+
+impl<'a, T, F> Simulation<'a, T, F> for Simulator<'a, T, F>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    type B = SimulatorBuilder<'a, T, F>;
+
+    /// Create builder.
+    fn builder(population: &'a mut Vec<T>) -> SimulatorBuilder<'a, T, F> {
+        SimulatorBuilder {
+            sim: Simulator {
+                population,
+                mu: None,
+                lambda: None,
+                survivors: SurvivorSelection::Plus,
+                iter_limit: IterLimit::new(100),
+                earlystoppers: Vec::new(),
+                crossover: None,
+                mutator: None,
+                duration: Some(0),
+                error: None,
+                rng: StdRng::random_seeded(),
+            },
+        }
+    }
+
+    fn step(&mut self) -> StepResult {
+        if self.population.is_empty() {
+            self.error = Some(Error::EmptyPopulation);
+            return StepResult::Failure;
+        }
+        let (mu, lambda) = match self.sizes() {
+            Ok(sizes) => sizes,
+            Err(e) => {
+                self.error = Some(e);
+                return StepResult::Failure;
+            }
+        };
+
+        let should_stop =
+            self.iter_limit.reached() || self.earlystoppers.iter().any(|x| x.reached());
+        if should_stop {
+            return StepResult::Done;
+        }
+
+        let time_start = Instant::now();
+
+        let mut offspring = self.offspring(lambda);
+        match self.survivors {
+            SurvivorSelection::Plus => self.population.append(&mut offspring),
+            SurvivorSelection::Comma => *self.population = offspring,
+        }
+        // The sort is stable, so parents are preferred over offspring of equal fitness.
+        self.population.sort_by_cached_key(|x| Reverse(x.fitness()));
+        self.population.truncate(mu);
+
+        for stopper in &mut self.earlystoppers {
+            stopper.update(self.population[0].fitness());
+        }
+
+        self.iter_limit.inc();
+        self.duration = match (self.duration, elapsed_nanos(time_start)) {
+            (Some(x), Some(y)) => x.checked_add(y),
+            _ => None,
+        };
+
+        StepResult::Success
+    }
+
+    fn get(&'a self) -> SimResult<'a, T> {
+        match self.error {
+            Some(ref e) => Err(e),
+            None => match best_index(self.population) {
+                Some(best) => Ok(&self.population[best]),
+                None => Err(&Error::EmptyPopulation),
+            },
+        }
+    }
+
+    simulation_functions!();
+}
+
+/// A `Builder` for the `Simulator` type.
+#[derive(Debug)]
+pub struct SimulatorBuilder<'a, T, F>
+where
+    T: 'a + Phenotype<F>,
+    F: Fitness,
+{
+    sim: Simulator<'a, T, F>,
+}
+
+impl<'a, T, F> SimulatorBuilder<'a, T, F>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    builder_functions!(with_max_iters, with_early_stop, with_mutator);
+
+    /// Set the number of parents, μ, of the resulting `Simulator`.
+    ///
+    /// This is the size of the population after every iteration. By default, μ is the
+    /// size of the initial population.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_mu(&mut self, mu: usize) -> &mut Self {
+        self.sim.mu = Some(mu);
+        self
+    }
+
+    /// Set the number of offspring, λ, of the resulting `Simulator`.
+    ///
+    /// By default, λ is 7μ.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_lambda(&mut self, lambda: usize) -> &mut Self {
+        self.sim.lambda = Some(lambda);
+        self
+    }
+
+    /// Set from which individuals the survivors are chosen.
+    /// The default is `SurvivorSelection::Plus`.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_survivor_selection(&mut self, survivors: SurvivorSelection) -> &mut Self {
+        self.sim.survivors = survivors;
+        self
+    }
+
+    /// Set the crossover operator of the resulting `Simulator`.
+    ///
+    /// If a crossover operator is set, every offspring is created by recombining two
+    /// random parents. Otherwise, offspring are mutated copies of a single parent.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_crossover(&mut self, op: Box<dyn CrossoverOperator<T>>) -> &mut Self {
+        self.sim.crossover = Some(op);
+        self
+    }
+
+    /// Seed the random number generator of the resulting `Simulator`.
+    ///
+    /// The choice of parents and the built-in operators draw from this generator, so
+    /// two runs with the same seed and the same settings produce the same result.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_seed(&mut self, seed: u64) -> &mut Self {
+        self.sim.rng = seeded_rng(seed);
+        self
+    }
+}
+
+impl<'a, T, F> Builder<Simulator<'a, T, F>> for SimulatorBuilder<'a, T, F>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    fn build(self) -> Simulator<'a, T, F> {
+        self.sim
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SurvivorSelection;
+    use pheno::Phenotype;
+    use sim::*;
+    use test::{MyFitness, Test};
+
+    #[test]
+    fn test_plus() {
+        let mut population: Vec<Test> = (0..10).map(|i| Test { f: i }).collect();
+        let mut builder = es::Simulator::builder(&mut population);
+        builder.with_lambda(20).with_max_iters(5);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+        assert_eq!(s.iterations(), 5);
+        let population = s.population();
+        assert_eq!(population.len(), 10);
+        // Offspring are never fitter than their parents, so the parents survive.
+        assert_eq!(population[0].f, 9);
+        assert!(population
+            .windows(2)
+            .all(|w| w[0].fitness() >= w[1].fitness()));
+    }
+
+    #[test]
+    fn test_comma() {
+        let mut population: Vec<Test> = (0..10).map(|i| Test { f: i }).collect();
+        let mut builder = es::Simulator::builder(&mut population);
+        builder
+            .with_mu(5)
+            .with_lambda(20)
+            .with_survivor_selection(SurvivorSelection::Comma)
+            .with_max_iters(3);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+        let population = s.population();
+        assert_eq!(population.len(), 5);
+        // Every offspring is mutated towards zero, so the best individual gets lost.
+        assert!(population[0].f < 9);
+        assert_eq!(s.get().unwrap().f, population[0].f);
+    }
+
+    #[test]
+    fn test_seed() {
+        let run = |seed| {
+            let mut population: Vec<Test> = (0..10).map(|i| Test { f: i }).collect();
+            {
+                let mut builder = es::Simulator::builder(&mut population);
+                builder
+                    .with_mu(5)
+                    .with_lambda(20)
+                    .with_survivor_selection(SurvivorSelection::Comma)
+                    .with_seed(seed)
+                    .with_max_iters(3);
+                builder.build().run();
+            }
+            population
+        };
+        assert_eq!(run(42), run(42));
+    }
+
+    #[test]
+    fn test_comma_lambda_too_small() {
+        let mut population: Vec<Test> = (0..10).map(|i| Test { f: i }).collect();
+        let mut builder = es::Simulator::builder(&mut population);
+        builder
+            .with_lambda(5)
+            .with_survivor_selection(SurvivorSelection::Comma);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Failure);
+        assert!(s.get().is_err());
+    }
+
+    #[test]
+    fn test_early_stopping() {
+        let mut population: Vec<Test> = (0..10).map(|_| Test { f: 0 }).collect();
+        let mut builder = es::Simulator::builder(&mut population);
+        builder
+            .with_early_stop(MyFitness { f: 10 }, 3)
+            .with_max_iters(10);
+        let mut s = builder.build();
+        s.run();
+        assert_eq!(s.iterations(), 3);
+    }
+}
@@ -0,0 +1,235 @@
+// file: handle.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains handles that allow other threads to observe a running simulation.
+//!
+//! A `BestHandle` is a cheaply cloneable, thread-safe cell holding the best
+//! individual found so far. Register it with a simulator builder, move a clone
+//! to another thread (e.g. a user interface), and read it at any time while
+//! the simulation runs.
+//!
+//! A `SimulationHandle` controls a simulation running on a background thread,
+//! as started by `seq::spawn`.
+
+use error::Error;
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+use std::thread::JoinHandle;
+
+/// A thread-safe handle to the best individual found so far by a simulation.
+///
+/// Clones of a `BestHandle` share the same underlying value.
+#[derive(Debug)]
+pub struct BestHandle<T> {
+    inner: Arc<RwLock<Option<(T, u64)>>>,
+}
+
+impl<T> BestHandle<T> {
+    /// Create a new, empty `BestHandle`.
+    pub fn new() -> BestHandle<T> {
+        BestHandle {
+            inner: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Replace the stored individual.
+    pub(crate) fn set(&self, best: T, iteration: u64) {
+        let mut guard = match self.inner.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        *guard = Some((best, iteration));
+    }
+}
+
+impl<T: Clone> BestHandle<T> {
+    /// Get a copy of the best individual found so far, or `None` if the
+    /// simulation has not run yet.
+    pub fn get(&self) -> Option<T> {
+        self.get_with_iteration().map(|(best, _)| best)
+    }
+
+    /// Get a copy of the best individual found so far, together with the
+    /// number of iterations executed when it was found.
+    pub fn get_with_iteration(&self) -> Option<(T, u64)> {
+        let guard = match self.inner.read() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        guard.clone()
+    }
+}
+
+impl<T> Clone for BestHandle<T> {
+    fn clone(&self) -> Self {
+        BestHandle {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> Default for BestHandle<T> {
+    fn default() -> Self {
+        BestHandle::new()
+    }
+}
+
+/// The requested state of a background simulation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum RunState {
+    Running,
+    Paused,
+    Stopped,
+}
+
+/// Shared control state between a `SimulationHandle` and its worker thread.
+#[derive(Debug)]
+pub(crate) struct Control {
+    state: Mutex<RunState>,
+    changed: Condvar,
+}
+
+impl Control {
+    pub(crate) fn new() -> Control {
+        Control {
+            state: Mutex::new(RunState::Running),
+            changed: Condvar::new(),
+        }
+    }
+
+    fn set(&self, state: RunState) {
+        let mut guard = match self.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        // A stopped simulation cannot be resumed or paused again.
+        if *guard != RunState::Stopped {
+            *guard = state;
+        }
+        self.changed.notify_all();
+    }
+
+    /// Block while the simulation is paused.
+    ///
+    /// Returns `false` if the simulation should stop.
+    pub(crate) fn proceed(&self) -> bool {
+        let mut guard = match self.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        while *guard == RunState::Paused {
+            guard = match self.changed.wait(guard) {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+        }
+        *guard == RunState::Running
+    }
+}
+
+/// A handle to a simulation running on a background thread.
+///
+/// Obtain one by calling `seq::spawn`.
+#[derive(Debug)]
+pub struct SimulationHandle<T> {
+    control: Arc<Control>,
+    best: BestHandle<T>,
+    thread: JoinHandle<Result<Vec<T>, Error>>,
+}
+
+impl<T> SimulationHandle<T> {
+    pub(crate) fn new(
+        control: Arc<Control>,
+        best: BestHandle<T>,
+        thread: JoinHandle<Result<Vec<T>, Error>>,
+    ) -> SimulationHandle<T> {
+        SimulationHandle {
+            control,
+            best,
+            thread,
+        }
+    }
+
+    /// Pause the simulation after the step that is currently running.
+    pub fn pause(&self) {
+        self.control.set(RunState::Paused);
+    }
+
+    /// Resume a paused simulation.
+    pub fn resume(&self) {
+        self.control.set(RunState::Running);
+    }
+
+    /// Stop the simulation after the step that is currently running.
+    ///
+    /// A stopped simulation cannot be resumed. Use `await_result` to
+    /// retrieve the population.
+    pub fn stop(&self) {
+        self.control.set(RunState::Stopped);
+    }
+
+    /// Get a `BestHandle` that tracks the best individual found so far.
+    pub fn best_handle(&self) -> BestHandle<T> {
+        self.best.clone()
+    }
+
+    /// Wait for the simulation to finish and return the final population.
+    ///
+    /// Returns the error of the simulator if it failed, or
+    /// `Error::ThreadPanicked` if the worker thread panicked.
+    pub fn await_result(self) -> Result<Vec<T>, Error> {
+        match self.thread.join() {
+            Ok(result) => result,
+            Err(_) => Err(Error::ThreadPanicked),
+        }
+    }
+}
+
+impl<T: Clone> SimulationHandle<T> {
+    /// Get a copy of the best individual found so far, or `None` if the
+    /// simulation has not made any progress yet.
+    pub fn best_snapshot(&self) -> Option<T> {
+        self.best.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BestHandle;
+    use std::thread;
+
+    #[test]
+    fn test_empty() {
+        let handle: BestHandle<i32> = BestHandle::new();
+        assert_eq!(handle.get(), None);
+    }
+
+    #[test]
+    fn test_shared_between_clones() {
+        let handle = BestHandle::new();
+        let reader = handle.clone();
+        handle.set(5, 3);
+        assert_eq!(reader.get_with_iteration(), Some((5, 3)));
+    }
+
+    #[test]
+    fn test_read_from_other_thread() {
+        let handle = BestHandle::new();
+        handle.set(7, 1);
+        let reader = handle.clone();
+        let value = thread::spawn(move || reader.get()).join().unwrap();
+        assert_eq!(value, Some(7));
+    }
+}
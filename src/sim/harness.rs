@@ -0,0 +1,223 @@
+// file: harness.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains a harness for testing genetic operators against invariants.
+//!
+//! `assert_operator_preserves` applies an operator to every sample, pairing
+//! samples up for crossover, and panics as soon as the result violates the
+//! invariant. `seeded_samples` creates reproducible inputs, so that a failing
+//! test can be debugged. The operators themselves still use the thread local
+//! random number generator, so running a test several times fuzzes them further.
+//!
+//! The invariants `same_length`, `within_bounds` and `is_permutation` cover the
+//! most common requirements on `Genome` operators.
+//!
+//! ```
+//! # extern crate rand;
+//! # extern crate rsgenetic;
+//! use rand::Rng;
+//! use rsgenetic::sim::harness::*;
+//! use rsgenetic::sim::mutation::{MutationOperator, SwapMutation};
+//! # use rsgenetic::pheno::Genome;
+//! # #[derive(Clone, Debug)]
+//! # struct Route(Vec<u8>);
+//! # impl Genome for Route {
+//! #     type Gene = u8;
+//! #     fn genes(&self) -> &[u8] { &self.0 }
+//! #     fn with_genes(&self, genes: Vec<u8>) -> Route { Route(genes) }
+//! # }
+//!
+//! let samples = seeded_samples(42, 100, |rng| {
+//!     let mut cities: Vec<u8> = (0..10).collect();
+//!     rng.shuffle(&mut cities);
+//!     Route(cities)
+//! });
+//! let op: Box<dyn MutationOperator<Route>> = Box::new(SwapMutation::new());
+//! assert_operator_preserves(&op, is_permutation(), &samples);
+//! ```
+
+use super::crossover::CrossoverOperator;
+use super::mutation::MutationOperator;
+use pheno::Genome;
+use rand::{Rng, SeedableRng, StdRng};
+use std::fmt::Debug;
+
+/// A genetic operator that can be tested by the harness.
+///
+/// This is implemented for boxed mutation and crossover operators,
+/// which is how they are passed to the simulators.
+pub trait Operator<T> {
+    /// The number of parents the operator takes.
+    fn arity(&self) -> usize;
+    /// Apply the operator to `arity` parents.
+    fn apply(&self, parents: &[&T]) -> T;
+}
+
+impl<T> Operator<T> for Box<dyn MutationOperator<T>> {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn apply(&self, parents: &[&T]) -> T {
+        self.mutate(parents[0])
+    }
+}
+
+impl<T> Operator<T> for Box<dyn CrossoverOperator<T>> {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn apply(&self, parents: &[&T]) -> T {
+        self.crossover(parents[0], parents[1])
+    }
+}
+
+/// Create `count` samples with a random number generator seeded with `seed`.
+///
+/// The same seed and generator always produce the same samples.
+pub fn seeded_samples<T, G>(seed: usize, count: usize, mut generate: G) -> Vec<T>
+where
+    G: FnMut(&mut StdRng) -> T,
+{
+    let mut rng = StdRng::from_seed(&[seed]);
+    (0..count).map(|_| generate(&mut rng)).collect()
+}
+
+/// Apply `op` to every sample and check the result against `invariant`.
+///
+/// The invariant receives the parents and the result, and returns a description
+/// of the violation if there is one. A crossover operator combines every sample
+/// with another sample, chosen by a seeded random number generator.
+///
+/// # Panics
+///
+/// Panics with the parents, the result and the description of the violation
+/// when the invariant does not hold, or when there are no samples.
+pub fn assert_operator_preserves<T, O, I>(op: &O, invariant: I, samples: &[T])
+where
+    T: Debug,
+    O: Operator<T>,
+    I: Fn(&[&T], &T) -> Result<(), String>,
+{
+    assert!(!samples.is_empty(), "The operator harness needs samples.");
+    let mut rng = StdRng::from_seed(&[samples.len()]);
+    for (i, sample) in samples.iter().enumerate() {
+        let mut parents = vec![sample];
+        while parents.len() < op.arity() {
+            parents.push(&samples[rng.gen_range(0, samples.len())]);
+        }
+        let result = op.apply(&parents);
+        if let Err(e) = invariant(&parents, &result) {
+            panic!(
+                "Invariant violated for sample {}: {}\nparents: {:?}\nresult: {:?}",
+                i, e, parents, result
+            );
+        }
+    }
+}
+
+/// An invariant that holds if the result has as many genes as the first parent.
+pub fn same_length<T>() -> impl Fn(&[&T], &T) -> Result<(), String>
+where
+    T: Genome,
+{
+    |parents, result| {
+        let (expected, actual) = (parents[0].genes().len(), result.genes().len());
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(format!("expected {} genes, found {}", expected, actual))
+        }
+    }
+}
+
+/// An invariant that holds if every gene of the result lies in `[low, high]`.
+pub fn within_bounds<T>(low: T::Gene, high: T::Gene) -> impl Fn(&[&T], &T) -> Result<(), String>
+where
+    T: Genome,
+    T::Gene: PartialOrd + Debug,
+{
+    move |_, result| match result.genes().iter().position(|g| *g < low || *g > high) {
+        Some(i) => Err(format!(
+            "gene {} is {:?}, outside of [{:?}, {:?}]",
+            i,
+            result.genes()[i],
+            low,
+            high
+        )),
+        None => Ok(()),
+    }
+}
+
+/// An invariant that holds if the genes of the result are a permutation
+/// of the genes of the first parent.
+pub fn is_permutation<T>() -> impl Fn(&[&T], &T) -> Result<(), String>
+where
+    T: Genome,
+    T::Gene: Ord,
+{
+    |parents, result| {
+        let mut expected = parents[0].genes().to_vec();
+        let mut actual = result.genes().to_vec();
+        expected.sort();
+        actual.sort();
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(String::from(
+                "the genes are not a permutation of the parent",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sim::crossover::OnePointCrossover;
+    use sim::mutation::{DeletionMutation, SwapMutation};
+    use test::Genes;
+
+    fn samples() -> Vec<Genes> {
+        seeded_samples(7, 50, |rng| {
+            let mut genes: Vec<i64> = (0..8).collect();
+            rng.shuffle(&mut genes);
+            Genes { genes }
+        })
+    }
+
+    #[test]
+    fn test_seeded_samples() {
+        assert_eq!(samples(), samples());
+    }
+
+    #[test]
+    fn test_preserved() {
+        let swap: Box<dyn MutationOperator<Genes>> = Box::new(SwapMutation::new());
+        assert_operator_preserves(&swap, is_permutation(), &samples());
+        let one_point: Box<dyn CrossoverOperator<Genes>> = Box::new(OnePointCrossover::new());
+        assert_operator_preserves(&one_point, same_length(), &samples());
+        assert_operator_preserves(&one_point, within_bounds(0, 7), &samples());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invariant violated")]
+    fn test_violated() {
+        let delete: Box<dyn MutationOperator<Genes>> = Box::new(DeletionMutation::new(1));
+        assert_operator_preserves(&delete, same_length(), &samples());
+    }
+}
@@ -0,0 +1,1176 @@
+// file: island.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains an island model implementation of `::sim::Simulation`,
+//! called a `Simulator`.
+//!
+//! The population is split into a number of islands, which evolve independently
+//! on the rayon thread pool. Every few iterations, the best individuals of each island
+//! migrate to other islands, as determined by the `Topology`, where they replace the
+//! worst individuals. Islands preserve diversity better than a single large population,
+//! while migration still lets good genes spread.
+//!
+//! The global rayon pool is used unless another one is configured with `with_threads`
+//! or `with_thread_pool`.
+//!
+//! Since all islands finish a generation before migration, a slow island holds up the
+//! others. `Simulator::run_asynchronously` avoids this by running every island on its
+//! own thread and exchanging migrants over channels.
+//!
+//! The population passed to the builder contains all islands one after another, so it
+//! can be inspected or reused after a run, just like with the other simulators.
+//!
+//! To use a `Simulator`, you need a `SimulatorBuilder`, which you can
+//! obtain by calling `Simulator::builder()`.
+
+use super::crossover::CrossoverOperator;
+use super::earlystopper::*;
+use super::iterlimit::*;
+use super::mutation::MutationOperator;
+use super::par::ThreadPoolConfig;
+use super::replace::kill_off;
+use super::select::*;
+use super::*;
+use pheno::Fitness;
+use pheno::Phenotype;
+use rand::{Rng, SeedableRng, StdRng};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Instant;
+
+/// Determines to which islands the migrants of an island are sent.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Topology {
+    /// Island `i` sends its migrants to island `i + 1`, and the last island to the first.
+    Ring,
+    /// Every island sends its migrants to every other island.
+    FullyConnected,
+    /// Every island sends its migrants to another island, chosen at random at every migration.
+    Random,
+    /// A directed graph, given as an adjacency list: island `i` sends its migrants to
+    /// every island in the `i`th list.
+    ///
+    /// The graph must have a list for every island. Edges from an island to itself and
+    /// duplicate edges are ignored.
+    Custom(Vec<Vec<usize>>),
+}
+
+/// A single individual that migrated from one island to another.
+///
+/// Migrations are reported to the callback set with
+/// `SimulatorBuilder::with_migration_callback`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Migration<F> {
+    /// The number of iterations executed, including the one after which the migration
+    /// took place.
+    pub iteration: u64,
+    /// The island the migrant comes from.
+    pub source: usize,
+    /// The island that took in the migrant.
+    pub destination: usize,
+    /// The fitness of the migrant on its source island.
+    pub fitness: F,
+}
+
+/// Called with every individual that migrates.
+type MigrationCallback<'a, F> = Callback<dyn FnMut(&Migration<F>) + 'a>;
+
+/// Computes the fitness of an individual on a single island.
+type IslandFitness<'a, T, F> = Callback<dyn Fn(&T) -> F + Send + Sync + 'a>;
+
+/// An island model implementation of `::sim::Simulation`.
+#[derive(Debug)]
+pub struct Simulator<'a, T, F>
+where
+    T: 'a + Phenotype<F> + Send + Sync,
+    F: Fitness + Send,
+{
+    population: &'a mut Vec<T>,
+    islands: usize,
+    migration_interval: u64,
+    migrants: usize,
+    topology: Topology,
+    migration_callback: Option<MigrationCallback<'a, F>>,
+    /// The fitness function of every island, if it differs from `Phenotype::fitness`.
+    island_fitness: Vec<Option<IslandFitness<'a, T, F>>>,
+    iter_limit: IterLimit,
+    selector: Box<dyn Selector<T, F>>,
+    earlystoppers: Vec<EarlyStopper<F>>,
+    crossover: Option<Box<dyn CrossoverOperator<T>>>,
+    mutator: Option<Box<dyn MutationOperator<T>>>,
+    duration: Option<NanoSecond>,
+    error: Option<Error>,
+    /// Index of the best individual in the population, if known.
+    best: Option<usize>,
+    rng: StdRng,
+    pool: ThreadPoolConfig,
+}
+
+impl<'a, T, F> Simulation<'a, T, F> for Simulator<'a, T, F>
+where
+    T: Phenotype<F> + Send + Sync,
+    F: Fitness + Send,
+{
+    type B = SimulatorBuilder<'a, T, F>;
+
+    /// Create builder.
+    fn builder(population: &'a mut Vec<T>) -> SimulatorBuilder<'a, T, F> {
+        SimulatorBuilder {
+            sim: Simulator {
+                population,
+                islands: 4,
+                migration_interval: 10,
+                migrants: 1,
+                topology: Topology::Ring,
+                migration_callback: None,
+                island_fitness: Vec::new(),
+                iter_limit: IterLimit::new(100),
+                selector: Box::new(UnstableMaximizeSelector::new(3)),
+                earlystoppers: Vec::new(),
+                crossover: None,
+                mutator: None,
+                duration: Some(0),
+                error: None,
+                best: None,
+                rng: StdRng::random_seeded(),
+                pool: ThreadPoolConfig::default(),
+            },
+        }
+    }
+
+    fn step(&mut self) -> StepResult {
+        if let Some(e) = self.config_error() {
+            self.error = Some(e);
+            return StepResult::Failure;
+        }
+
+        let should_stop =
+            self.iter_limit.reached() || self.earlystoppers.iter().any(|x| x.reached());
+        if should_stop {
+            return StepResult::Done;
+        }
+
+        let time_start = Instant::now();
+
+        let mut islands = split(self.population, self.islands);
+        // Every island gets a generator seeded from the one of the simulator, so that
+        // the result does not depend on the order in which the islands are evolved.
+        let mut rngs: Vec<StdRng> = (0..self.islands)
+            .map(|_| StdRng::from_seed(&[self.rng.gen(), self.rng.gen()]))
+            .collect();
+        let island_fitness = &self.island_fitness;
+        let evaluate = |i: usize, x: &T| evaluate(island_fitness, i, x);
+        let result = {
+            let selector = &self.selector;
+            let crossover = &self.crossover;
+            let mutator = &self.mutator;
+            let generation = self.iter_limit.get();
+            let islands = &mut islands;
+            let rngs = &mut rngs;
+            self.pool.install(|| {
+                islands
+                    .par_iter_mut()
+                    .zip(rngs.par_iter_mut())
+                    .enumerate()
+                    .map(|(i, (island, rng))| {
+                        evolve(
+                            island,
+                            |x| evaluate(i, x),
+                            &**selector,
+                            generation,
+                            crossover,
+                            mutator,
+                            rng,
+                        )
+                    })
+                    .collect::<Result<(), Error>>()
+            })
+        };
+        if let Err(e) = result.and_then(|result| result) {
+            join(self.population, islands);
+            self.error = Some(e);
+            return StepResult::Failure;
+        }
+        self.iter_limit.inc();
+        if self.migration_interval > 0 && self.iter_limit.get() % self.migration_interval == 0 {
+            let migrations = migrate(
+                &mut islands,
+                self.migrants,
+                &self.topology,
+                evaluate,
+                self.iter_limit.get(),
+                &mut self.rng,
+            );
+            if let Some(ref mut callback) = self.migration_callback {
+                for migration in &migrations {
+                    (callback.0)(migration);
+                }
+            }
+        }
+        join(self.population, islands);
+
+        let best = best_index(self.population).unwrap();
+        self.best = Some(best);
+        for stopper in &mut self.earlystoppers {
+            stopper.update(self.population[best].fitness());
+        }
+
+        self.duration = match (self.duration, elapsed_nanos(time_start)) {
+            (Some(x), Some(y)) => x.checked_add(y),
+            _ => None,
+        };
+
+        StepResult::Success
+    }
+
+    fn get(&'a self) -> SimResult<'a, T> {
+        match self.error {
+            Some(ref e) => Err(e),
+            None => {
+                let best = match self.best.or_else(|| best_index(self.population)) {
+                    Some(best) => best,
+                    None => return Err(&Error::EmptyPopulation),
+                };
+                Ok(&self.population[best])
+            }
+        }
+    }
+
+    simulation_functions!();
+}
+
+impl<'a, T, F> Simulator<'a, T, F>
+where
+    T: Phenotype<F> + Send + Sync,
+    F: Fitness + Send,
+{
+    /// Run all remaining iterations with every island on its own thread, exchanging
+    /// migrants over channels instead of migrating after every island has finished a
+    /// generation.
+    ///
+    /// An island sends its migrants as soon as it reaches a migration iteration, and
+    /// takes in the migrants that have arrived before each of its generations, so fast
+    /// islands never wait for slow ones. Migrants that arrive after their destination
+    /// has finished are lost. The result depends on the timing of the threads, so it is
+    /// not reproducible, even with a seed.
+    ///
+    /// The thread pool of the simulator is not used, and early stopping conditions are
+    /// not checked. Migrations are reported to the migration callback, with the
+    /// iteration of the island that sent them.
+    pub fn run_asynchronously(&mut self) -> RunResult {
+        if let Some(e) = self.config_error() {
+            self.error = Some(e);
+            return RunResult::Failure;
+        }
+        if self.iter_limit.reached() {
+            return RunResult::Done;
+        }
+
+        let time_start = Instant::now();
+
+        let n = self.islands;
+        let mut islands = split(self.population, n);
+        let mut rngs: Vec<StdRng> = (0..n)
+            .map(|_| StdRng::from_seed(&[self.rng.gen(), self.rng.gen()]))
+            .collect();
+        let (senders, inboxes): (Vec<_>, Vec<_>) = (0..n).map(|_| mpsc::channel()).unzip();
+        let (events, event_receiver) = mpsc::channel();
+        let settings = AsyncSettings {
+            selector: &*self.selector,
+            crossover: &self.crossover,
+            mutator: &self.mutator,
+            topology: &self.topology,
+            migration_interval: self.migration_interval,
+            migrants: self.migrants,
+            start: self.iter_limit.get(),
+            end: self.iter_limit.max(),
+            senders,
+            failed: AtomicBool::new(false),
+        };
+        let result = {
+            let settings = &settings;
+            let island_fitness = &self.island_fitness;
+            let callback = &mut self.migration_callback;
+            thread::scope(|scope| {
+                let threads: Vec<_> = islands
+                    .iter_mut()
+                    .zip(rngs.iter_mut())
+                    .zip(inboxes)
+                    .enumerate()
+                    .map(|(i, ((island, rng), inbox))| {
+                        let events = events.clone();
+                        scope.spawn(move || {
+                            evolve_asynchronously(
+                                i,
+                                island,
+                                |x| evaluate(island_fitness, i, x),
+                                settings,
+                                rng,
+                                inbox,
+                                events,
+                            )
+                        })
+                    })
+                    .collect();
+                // The events end when every island has finished.
+                drop(events);
+                for migration in event_receiver {
+                    if let Some(ref mut callback) = *callback {
+                        (callback.0)(&migration);
+                    }
+                }
+                threads
+                    .into_iter()
+                    .try_for_each(|thread| thread.join().map_err(|_| Error::ThreadPanicked)?)
+            })
+        };
+        join(self.population, islands);
+        if let Err(e) = result {
+            self.error = Some(e);
+            return RunResult::Failure;
+        }
+        self.iter_limit.set(self.iter_limit.max());
+        self.best = best_index(self.population);
+
+        self.duration = match (self.duration, elapsed_nanos(time_start)) {
+            (Some(x), Some(y)) => x.checked_add(y),
+            _ => None,
+        };
+
+        RunResult::Done
+    }
+
+    /// Get the error in the configuration of the simulator, if any.
+    fn config_error(&self) -> Option<Error> {
+        let n = self.islands;
+        if n == 0 || self.population.len() < n {
+            return Some(Error::InvalidConfig(format!(
+                "Cannot split a population of {} individuals into {} islands.",
+                self.population.len(),
+                n
+            )));
+        }
+        if let Topology::Custom(ref adjacency) = self.topology {
+            if adjacency.len() != n || adjacency.iter().flatten().any(|&j| j >= n) {
+                return Some(Error::InvalidConfig(format!(
+                    "The custom topology is not a graph of {} islands.",
+                    n
+                )));
+            }
+        }
+        if self.island_fitness.len() > n {
+            return Some(Error::InvalidConfig(format!(
+                "A fitness function is set for island {}, but there are only {} islands.",
+                self.island_fitness.len() - 1,
+                n
+            )));
+        }
+        None
+    }
+}
+
+/// Compute the fitness of `x` on island `i`.
+fn evaluate<T, F>(island_fitness: &[Option<IslandFitness<T, F>>], i: usize, x: &T) -> F
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    match island_fitness.get(i).and_then(Option::as_ref) {
+        Some(f) => (f.0)(x),
+        None => x.fitness(),
+    }
+}
+
+/// Move the individuals of `population` into `n` islands of (almost) equal size.
+///
+/// The first `population.len() % n` islands get one extra individual.
+fn split<T>(population: &mut Vec<T>, n: usize) -> Vec<Vec<T>> {
+    let size = population.len() / n;
+    let extra = population.len() % n;
+    let mut islands = Vec::with_capacity(n);
+    for i in (0..n).rev() {
+        let len = size + if i < extra { 1 } else { 0 };
+        let at = population.len() - len;
+        islands.push(population.split_off(at));
+    }
+    islands.reverse();
+    islands
+}
+
+/// Move the individuals of all islands back into `population`, in order.
+fn join<T>(population: &mut Vec<T>, islands: Vec<Vec<T>>) {
+    for mut island in islands {
+        population.append(&mut island);
+    }
+}
+
+/// Run generation `generation` on a single island, whose fitness function is `evaluate`.
+fn evolve<T, F, E>(
+    island: &mut Vec<T>,
+    evaluate: E,
+    selector: &dyn Selector<T, F>,
+    generation: u64,
+    crossover: &Option<Box<dyn CrossoverOperator<T>>>,
+    mutator: &Option<Box<dyn MutationOperator<T>>>,
+    rng: &mut StdRng,
+) -> Result<(), Error>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+    E: Fn(&T) -> F,
+{
+    let fitness: Vec<F> = island.iter().map(evaluate).collect();
+    let mut children: Vec<T> = selector
+        .select_from(SelectionContext::new(island, &fitness, generation, rng))?
+        .into_iter()
+        .map(|(a, b)| {
+            let child = match *crossover {
+                Some(ref op) => op.crossover_with_rng(a, b, rng),
+                None => a.crossover_with_rng(b, rng),
+            };
+            match *mutator {
+                Some(ref op) => op.mutate_with_rng(&child, rng),
+                None => child.mutate_with_rng(rng),
+            }
+        })
+        .collect();
+    if !children.is_empty() {
+        kill_off(island, children.len(), rng);
+        island.append(&mut children);
+    }
+    Ok(())
+}
+
+/// Send copies of the best `count` individuals of every island to other islands,
+/// where they replace the worst individuals.
+///
+/// The order of the individuals on an island is kept. An island never loses its best
+/// individual, so it takes in at most one migrant less than its size. If more migrants
+/// arrive, the best ones are taken in.
+///
+/// `evaluate(i, x)` computes the fitness of `x` on island `i`, so migrants are
+/// re-evaluated when they arrive.
+///
+/// Returns the migrants that were taken in, as migrations after iteration `iteration`.
+fn migrate<T, F, E, R>(
+    islands: &mut [Vec<T>],
+    count: usize,
+    topology: &Topology,
+    evaluate: E,
+    iteration: u64,
+    rng: &mut R,
+) -> Vec<Migration<F>>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+    E: Fn(usize, &T) -> F,
+    R: Rng,
+{
+    let n = islands.len();
+    if n < 2 || count == 0 {
+        return Vec::new();
+    }
+    let fitness: Vec<Vec<F>> = islands
+        .iter()
+        .enumerate()
+        .map(|(i, island)| island.iter().map(|x| evaluate(i, x)).collect())
+        .collect();
+    let ranked: Vec<Vec<usize>> = fitness.iter().map(|f| ranking(f)).collect();
+    // The migrants that arrive at every island, as (island, index) pairs.
+    let mut incoming: Vec<Vec<(usize, usize)>> = (0..n).map(|_| Vec::new()).collect();
+    for (i, order) in ranked.iter().enumerate() {
+        let migrants = &order[order.len().saturating_sub(count)..];
+        for j in destinations(topology, i, n, rng) {
+            incoming[j].extend(migrants.iter().map(|&k| (i, k)));
+        }
+    }
+    // Rank the migrants that arrive at every island by their fitness on that island.
+    let incoming: Vec<Vec<(usize, usize)>> = incoming
+        .into_iter()
+        .enumerate()
+        .map(|(j, migrants)| {
+            let mut migrants: Vec<(usize, usize, F)> = migrants
+                .into_iter()
+                .map(|(i, k)| (i, k, evaluate(j, &islands[i][k])))
+                .collect();
+            migrants.sort_by(|a, b| b.2.cmp(&a.2));
+            migrants.truncate(ranked[j].len().saturating_sub(1));
+            migrants.into_iter().map(|(i, k, _)| (i, k)).collect()
+        })
+        .collect();
+    // Copy all migrants before any of them is taken in, since an island may send
+    // individuals that are replaced on the island itself.
+    let copies: Vec<Vec<T>> = incoming
+        .iter()
+        .map(|migrants| {
+            migrants
+                .iter()
+                .map(|&(i, k)| islands[i][k].clone())
+                .collect()
+        })
+        .collect();
+    for (j, copies) in copies.into_iter().enumerate() {
+        // The best migrants replace the worst individuals.
+        for (&slot, migrant) in ranked[j].iter().zip(copies) {
+            islands[j][slot] = migrant;
+        }
+    }
+    let fitness = &fitness;
+    incoming
+        .into_iter()
+        .enumerate()
+        .flat_map(|(j, migrants)| {
+            migrants.into_iter().map(move |(i, k)| Migration {
+                iteration,
+                source: i,
+                destination: j,
+                fitness: fitness[i][k].clone(),
+            })
+        })
+        .collect()
+}
+
+/// Get the islands that island `i` of `n` islands sends its migrants to.
+fn destinations<R: Rng>(topology: &Topology, i: usize, n: usize, rng: &mut R) -> Vec<usize> {
+    match *topology {
+        Topology::Ring => vec![(i + 1) % n],
+        Topology::FullyConnected => (0..n).filter(|&j| j != i).collect(),
+        Topology::Random => vec![(i + rng.gen_range(1, n)) % n],
+        Topology::Custom(ref adjacency) => {
+            let mut destinations: Vec<usize> =
+                adjacency[i].iter().cloned().filter(|&j| j != i).collect();
+            destinations.sort();
+            destinations.dedup();
+            destinations
+        }
+    }
+}
+
+/// Get the indices of `fitness`, by ascending fitness.
+fn ranking<F: Ord>(fitness: &[F]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..fitness.len()).collect();
+    order.sort_by(|&a, &b| fitness[a].cmp(&fitness[b]));
+    order
+}
+
+/// Migrants, together with their migration, as sent by `evolve_asynchronously`.
+type Migrants<T, F> = Vec<(T, Migration<F>)>;
+
+/// The settings that the islands of `Simulator::run_asynchronously` share.
+struct AsyncSettings<'s, T: 's, F: 's> {
+    selector: &'s dyn Selector<T, F>,
+    crossover: &'s Option<Box<dyn CrossoverOperator<T>>>,
+    mutator: &'s Option<Box<dyn MutationOperator<T>>>,
+    topology: &'s Topology,
+    migration_interval: u64,
+    migrants: usize,
+    /// The first generation to run.
+    start: u64,
+    /// The generation after the last one to run.
+    end: u64,
+    /// The channels to the inboxes of the islands.
+    senders: Vec<Sender<Migrants<T, F>>>,
+    /// Whether an island has failed, so that the others can stop.
+    failed: AtomicBool,
+}
+
+/// Run the generations of island `i`, whose fitness function is `evaluate`, taking in
+/// the migrants from `inbox` and reporting the ones that were taken in to `events`.
+fn evolve_asynchronously<T, F, E>(
+    i: usize,
+    island: &mut Vec<T>,
+    evaluate: E,
+    settings: &AsyncSettings<T, F>,
+    rng: &mut StdRng,
+    inbox: Receiver<Migrants<T, F>>,
+    events: Sender<Migration<F>>,
+) -> Result<(), Error>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+    E: Fn(&T) -> F,
+{
+    let n = settings.senders.len();
+    for generation in settings.start..settings.end {
+        if settings.failed.load(Ordering::Relaxed) {
+            break;
+        }
+        for migrants in inbox.try_iter() {
+            for migration in take_in(island, migrants, &evaluate) {
+                // The receiver only stops listening after every island has finished.
+                let _ = events.send(migration);
+            }
+        }
+        let result = evolve(
+            island,
+            &evaluate,
+            settings.selector,
+            generation,
+            settings.crossover,
+            settings.mutator,
+            rng,
+        );
+        if result.is_err() {
+            settings.failed.store(true, Ordering::Relaxed);
+            return result;
+        }
+        let iteration = generation + 1;
+        let interval = settings.migration_interval;
+        if n < 2 || settings.migrants == 0 || interval == 0 || iteration % interval != 0 {
+            continue;
+        }
+        let fitness: Vec<F> = island.iter().map(&evaluate).collect();
+        let ranked = ranking(&fitness);
+        for j in destinations(settings.topology, i, n, rng) {
+            let migrants = ranked
+                .iter()
+                .rev()
+                .take(settings.migrants)
+                .map(|&k| {
+                    let migration = Migration {
+                        iteration,
+                        source: i,
+                        destination: j,
+                        fitness: fitness[k].clone(),
+                    };
+                    (island[k].clone(), migration)
+                })
+                .collect();
+            // An island that has finished no longer takes in migrants.
+            let _ = settings.senders[j].send(migrants);
+        }
+    }
+    Ok(())
+}
+
+/// Let `island` take in the best of `migrants`, ranked by `evaluate`, in place of its
+/// worst individuals. Like with `migrate`, the island keeps its best individual.
+///
+/// Returns the migrations of the migrants that were taken in.
+fn take_in<T, F, E>(island: &mut [T], migrants: Migrants<T, F>, evaluate: E) -> Vec<Migration<F>>
+where
+    F: Fitness,
+    E: Fn(&T) -> F,
+{
+    let fitness: Vec<F> = island.iter().map(&evaluate).collect();
+    let mut migrants: Vec<(F, T, Migration<F>)> = migrants
+        .into_iter()
+        .map(|(x, migration)| (evaluate(&x), x, migration))
+        .collect();
+    migrants.sort_by(|a, b| b.0.cmp(&a.0));
+    migrants.truncate(island.len().saturating_sub(1));
+    ranking(&fitness)
+        .into_iter()
+        .zip(migrants)
+        .map(|(slot, (_, x, migration))| {
+            island[slot] = x;
+            migration
+        })
+        .collect()
+}
+
+/// A `Builder` for the `Simulator` type.
+#[derive(Debug)]
+pub struct SimulatorBuilder<'a, T, F>
+where
+    T: 'a + Phenotype<F> + Send + Sync,
+    F: Fitness + Send,
+{
+    sim: Simulator<'a, T, F>,
+}
+
+impl<'a, T, F> SimulatorBuilder<'a, T, F>
+where
+    T: Phenotype<F> + Send + Sync,
+    F: Fitness + Send,
+{
+    builder_functions!(
+        with_selector,
+        with_max_iters,
+        with_early_stop,
+        with_crossover,
+        with_mutator,
+        with_threads,
+        with_thread_pool
+    );
+
+    /// Set the number of islands the population is split into. The default is 4.
+    ///
+    /// The population must contain at least one individual per island.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_islands(&mut self, n: usize) -> &mut Self {
+        self.sim.islands = n;
+        self
+    }
+
+    /// Set how often and how many individuals migrate.
+    ///
+    /// Every `interval` iterations, copies of the best `count` individuals of each
+    /// island are sent to other islands, where they replace the worst individuals.
+    /// An island always keeps its best individual, so it takes in at most one migrant
+    /// less than its size. An `interval` of zero disables migration.
+    /// The default is to migrate one individual every 10 iterations.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_migration(&mut self, interval: u64, count: usize) -> &mut Self {
+        self.sim.migration_interval = interval;
+        self.sim.migrants = count;
+        self
+    }
+
+    /// Set the migration topology. The default is `Topology::Ring`.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_topology(&mut self, topology: Topology) -> &mut Self {
+        self.sim.topology = topology;
+        self
+    }
+
+    /// Set a callback that is called for every individual that migrates.
+    ///
+    /// The callback receives a `Migration` with the source and destination island and
+    /// the fitness of the migrant, which shows how good genes spread through the
+    /// topology. Only migrants that are taken in by their destination are reported.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_migration_callback<C>(&mut self, callback: C) -> &mut Self
+    where
+        C: FnMut(&Migration<F>) + 'a,
+    {
+        self.sim.migration_callback = Some(Callback(Box::new(callback)));
+        self
+    }
+
+    /// Set the fitness function of island `island`, e.g. to penalize constraint
+    /// violations with a different weight on every island.
+    ///
+    /// Selection and migration on this island use `fitness` instead of
+    /// `Phenotype::fitness`. Migrants are re-evaluated when they arrive, so they are
+    /// ranked by the fitness function of their destination. The best individual and
+    /// early stopping still use `Phenotype::fitness`.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_island_fitness<E>(&mut self, island: usize, fitness: E) -> &mut Self
+    where
+        E: Fn(&T) -> F + Send + Sync + 'a,
+    {
+        let island_fitness = &mut self.sim.island_fitness;
+        if island_fitness.len() <= island {
+            island_fitness.resize_with(island + 1, || None);
+        }
+        island_fitness[island] = Some(Callback(Box::new(fitness)));
+        self
+    }
+
+    /// Seed the random number generator of the resulting `Simulator`.
+    ///
+    /// Every island draws from a generator that is seeded from this one, so two runs
+    /// with the same seed and the same settings produce the same result, regardless
+    /// of the number of threads.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_seed(&mut self, seed: u64) -> &mut Self {
+        self.sim.rng = seeded_rng(seed);
+        self
+    }
+}
+
+impl<'a, T, F> Builder<Simulator<'a, T, F>> for SimulatorBuilder<'a, T, F>
+where
+    T: Phenotype<F> + Send + Sync,
+    F: Fitness + Send,
+{
+    fn build(self) -> Simulator<'a, T, F> {
+        self.sim
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{join, migrate, split, take_in, Migration, Topology};
+    use sim::select::*;
+    use sim::*;
+    use test::{MyFitness, Test};
+
+    fn islands(values: &[&[i64]]) -> Vec<Vec<Test>> {
+        values
+            .iter()
+            .map(|island| island.iter().map(|&f| Test { f }).collect())
+            .collect()
+    }
+
+    fn fitness(_: usize, x: &Test) -> MyFitness {
+        x.fitness()
+    }
+
+    fn values(islands: &[Vec<Test>]) -> Vec<Vec<i64>> {
+        islands
+            .iter()
+            .map(|island| island.iter().map(|x| x.f).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_split_join() {
+        let mut population: Vec<i32> = (0..10).collect();
+        let islands = split(&mut population, 3);
+        assert_eq!(
+            islands,
+            vec![vec![0, 1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]
+        );
+        join(&mut population, islands);
+        assert_eq!(population, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_migrate_ring() {
+        let mut isl = islands(&[&[1, 5, 2], &[3, 4, 6], &[7, 8, 9]]);
+        migrate(
+            &mut isl,
+            1,
+            &Topology::Ring,
+            fitness,
+            1,
+            &mut ::rand::thread_rng(),
+        );
+        assert_eq!(
+            values(&isl),
+            vec![vec![9, 5, 2], vec![5, 4, 6], vec![6, 8, 9]]
+        );
+    }
+
+    #[test]
+    fn test_migrate_fully_connected() {
+        let mut isl = islands(&[&[1, 2, 3], &[4, 5, 6], &[7, 8, 9]]);
+        migrate(
+            &mut isl,
+            1,
+            &Topology::FullyConnected,
+            fitness,
+            1,
+            &mut ::rand::thread_rng(),
+        );
+        assert_eq!(
+            values(&isl),
+            vec![vec![9, 6, 3], vec![9, 3, 6], vec![6, 3, 9]]
+        );
+    }
+
+    #[test]
+    fn test_migrate_custom() {
+        // Island 0 sends to islands 1 and 2, and island 2 sends to island 0.
+        let topology = Topology::Custom(vec![vec![1, 2, 2], vec![1], vec![0]]);
+        let mut isl = islands(&[&[1, 2, 3], &[4, 5, 6], &[7, 8, 9]]);
+        migrate(
+            &mut isl,
+            1,
+            &topology,
+            fitness,
+            1,
+            &mut ::rand::thread_rng(),
+        );
+        assert_eq!(
+            values(&isl),
+            vec![vec![9, 2, 3], vec![3, 5, 6], vec![3, 8, 9]]
+        );
+    }
+
+    #[test]
+    fn test_migrate_reports_migrations() {
+        let mut isl = islands(&[&[1, 5, 2], &[3, 4, 6]]);
+        let migrations = migrate(
+            &mut isl,
+            1,
+            &Topology::Ring,
+            fitness,
+            7,
+            &mut ::rand::thread_rng(),
+        );
+        assert_eq!(
+            migrations,
+            vec![
+                Migration {
+                    iteration: 7,
+                    source: 1,
+                    destination: 0,
+                    fitness: MyFitness { f: 6 },
+                },
+                Migration {
+                    iteration: 7,
+                    source: 0,
+                    destination: 1,
+                    fitness: MyFitness { f: 5 },
+                },
+            ]
+        );
+        assert_eq!(values(&isl), vec![vec![6, 5, 2], vec![5, 4, 6]]);
+    }
+
+    #[test]
+    fn test_migrate_replaced_migrants() {
+        // The worst individuals of island 0 are replaced, but are sent to island 1 first.
+        let mut isl = islands(&[&[1, 5, 2], &[3, 4, 6]]);
+        migrate(
+            &mut isl,
+            3,
+            &Topology::Ring,
+            fitness,
+            1,
+            &mut ::rand::thread_rng(),
+        );
+        assert_eq!(values(&isl), vec![vec![6, 5, 4], vec![5, 2, 6]]);
+    }
+
+    #[test]
+    fn test_migrate_reevaluates() {
+        // Island 1 prefers low values, so of all individuals of island 0, it takes in
+        // the ones that island 0 considers the worst.
+        let mut isl = islands(&[&[1, 5, 2], &[3, 4, 6]]);
+        let evaluate = |i, x: &Test| MyFitness {
+            f: if i == 1 { -x.f } else { x.f },
+        };
+        let migrations = migrate(
+            &mut isl,
+            3,
+            &Topology::Ring,
+            evaluate,
+            1,
+            &mut ::rand::thread_rng(),
+        );
+        assert_eq!(values(&isl), vec![vec![6, 5, 4], vec![3, 2, 1]]);
+        // The reported fitness is the one on the source island.
+        assert_eq!(migrations[0].fitness, MyFitness { f: -6 });
+        assert_eq!(migrations[2].fitness, MyFitness { f: 1 });
+    }
+
+    #[test]
+    fn test_take_in() {
+        let mut island: Vec<Test> = [4, 1, 3].iter().map(|&f| Test { f }).collect();
+        let migrants = [2, 5, 6]
+            .iter()
+            .map(|&f| {
+                let migration = Migration {
+                    iteration: 1,
+                    source: 1,
+                    destination: 0,
+                    fitness: MyFitness { f },
+                };
+                (Test { f }, migration)
+            })
+            .collect();
+        let migrations = take_in(&mut island, migrants, |x: &Test| x.fitness());
+        assert_eq!(values(&[island]), vec![vec![4, 6, 5]]);
+        assert_eq!(migrations.len(), 2);
+        assert_eq!(migrations[0].fitness, MyFitness { f: 6 });
+    }
+
+    #[test]
+    fn test_migrate_small_islands() {
+        // Every island receives more migrants than it has individuals.
+        let mut isl = islands(&[&[1, 2], &[4, 3], &[5, 6], &[8, 7], &[9]]);
+        migrate(
+            &mut isl,
+            2,
+            &Topology::FullyConnected,
+            fitness,
+            1,
+            &mut ::rand::thread_rng(),
+        );
+        assert_eq!(
+            values(&isl),
+            vec![vec![9, 2], vec![4, 9], vec![9, 6], vec![8, 9], vec![9]]
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = island::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(4)))
+            .with_islands(4)
+            .with_migration(2, 2)
+            .with_topology(Topology::Random)
+            .with_max_iters(5);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+        assert_eq!(s.iterations(), 5);
+        assert_eq!(s.population().len(), 100);
+    }
+
+    #[test]
+    fn test_migration_callback() {
+        let mut migrations = Vec::new();
+        {
+            let mut population: Vec<Test> = (0..40).map(|i| Test { f: i }).collect();
+            let mut builder = island::Simulator::builder(&mut population);
+            builder
+                .with_selector(Box::new(StochasticSelector::new(4)))
+                .with_islands(4)
+                .with_migration(2, 1)
+                .with_migration_callback(|m| migrations.push(m.clone()))
+                .with_max_iters(5);
+            builder.build().run();
+        }
+        // A ring of four islands migrates after iterations 2 and 4.
+        assert_eq!(migrations.len(), 8);
+        assert!(migrations
+            .iter()
+            .all(|m| m.destination == (m.source + 1) % 4 && m.iteration % 2 == 0));
+    }
+
+    #[test]
+    fn test_invalid_custom_topology() {
+        let mut population: Vec<Test> = (0..40).map(|i| Test { f: i }).collect();
+        let mut builder = island::Simulator::builder(&mut population);
+        builder
+            .with_islands(2)
+            .with_topology(Topology::Custom(vec![vec![1], vec![2]]));
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Failure);
+        assert!(s.get().is_err());
+    }
+
+    #[test]
+    fn test_island_fitness() {
+        let mut population: Vec<Test> = (0..40).map(|i| Test { f: i }).collect();
+        {
+            let mut builder = island::Simulator::builder(&mut population);
+            builder
+                .with_selector(Box::new(UnstableMaximizeSelector::new(4)))
+                .with_islands(2)
+                .with_migration(0, 0)
+                .with_island_fitness(1, |x: &Test| MyFitness { f: -x.f })
+                .with_max_iters(20);
+            builder.build().run();
+        }
+        // The second island evolves towards low values, the first one towards high values.
+        let low: i64 = population[20..].iter().map(|x| x.f).sum();
+        let high: i64 = population[..20].iter().map(|x| x.f).sum();
+        assert!(low < high);
+    }
+
+    #[test]
+    fn test_island_fitness_of_missing_island() {
+        let mut population: Vec<Test> = (0..40).map(|i| Test { f: i }).collect();
+        let mut builder = island::Simulator::builder(&mut population);
+        builder
+            .with_islands(2)
+            .with_island_fitness(2, |x: &Test| x.fitness());
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Failure);
+    }
+
+    #[test]
+    fn test_run_asynchronously() {
+        let mut migrations = Vec::new();
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        {
+            let mut builder = island::Simulator::builder(&mut population);
+            builder
+                .with_selector(Box::new(StochasticSelector::new(4)))
+                .with_islands(4)
+                .with_migration(2, 2)
+                .with_topology(Topology::Random)
+                .with_migration_callback(|m| migrations.push(m.clone()))
+                .with_max_iters(10);
+            let mut s = builder.build();
+            assert_eq!(s.run_asynchronously(), RunResult::Done);
+            assert_eq!(s.iterations(), 10);
+            assert!(s.get().is_ok());
+            assert_eq!(s.run_asynchronously(), RunResult::Done);
+        }
+        assert_eq!(population.len(), 100);
+        assert!(migrations
+            .iter()
+            .all(|m| m.source != m.destination && m.iteration % 2 == 0));
+    }
+
+    #[test]
+    fn test_run_asynchronously_invalid_config() {
+        let mut population: Vec<Test> = (0..3).map(|i| Test { f: i }).collect();
+        let mut builder = island::Simulator::builder(&mut population);
+        builder.with_islands(4);
+        let mut s = builder.build();
+        assert_eq!(s.run_asynchronously(), RunResult::Failure);
+        assert!(s.get().is_err());
+    }
+
+    #[test]
+    fn test_threads() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = island::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(4)))
+            .with_threads(2)
+            .with_max_iters(5);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+        assert_eq!(s.population().len(), 100);
+    }
+
+    #[test]
+    fn test_seed() {
+        let run = |threads| {
+            let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+            {
+                let mut builder = island::Simulator::builder(&mut population);
+                builder
+                    .with_selector(Box::new(StochasticSelector::new(4)))
+                    .with_migration(2, 2)
+                    .with_topology(Topology::Random)
+                    .with_threads(threads)
+                    .with_seed(42)
+                    .with_max_iters(10);
+                builder.build().run();
+            }
+            population
+        };
+        assert_eq!(run(1), run(3));
+    }
+
+    #[test]
+    fn test_early_stopping() {
+        let mut population: Vec<Test> = (0..100).map(|_| Test { f: 0 }).collect();
+        let mut builder = island::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(4)))
+            .with_early_stop(MyFitness { f: 10 }, 3)
+            .with_max_iters(10);
+        let mut s = builder.build();
+        s.run();
+        assert_eq!(s.iterations(), 3);
+    }
+
+    #[test]
+    fn test_too_many_islands() {
+        let mut population: Vec<Test> = (0..3).map(|i| Test { f: i }).collect();
+        let mut builder = island::Simulator::builder(&mut population);
+        builder.with_islands(4);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Failure);
+        assert!(s.get().is_err());
+    }
+}
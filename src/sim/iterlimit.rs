@@ -44,10 +44,30 @@ impl IterLimit {
         self.cur = 0;
     }
 
+    /// Set the current number of iterations, e.g. when resuming a run.
+    pub fn set(&mut self, cur: u64) {
+        self.cur = cur;
+    }
+
     /// Get the current number of iterations.
     pub fn get(&self) -> u64 {
         self.cur
     }
+
+    /// Set the maximum number of iterations, keeping the current number of iterations.
+    pub fn set_max(&mut self, max: u64) {
+        self.max = max;
+    }
+
+    /// Get the maximum number of iterations.
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    /// Get the number of iterations left before the maximum is reached.
+    pub fn remaining(&self) -> u64 {
+        self.max.saturating_sub(self.cur)
+    }
 }
 
 #[cfg(test)]
@@ -60,9 +80,9 @@ mod tests {
         for _ in 0..4 {
             limit.inc();
         }
-        assert_eq!(limit.reached(), false);
+        assert!(!limit.reached());
         limit.reset();
-        assert_eq!(limit.reached(), false);
+        assert!(!limit.reached());
     }
 
     #[test]
@@ -78,4 +98,27 @@ mod tests {
         assert!(limit.reached());
         assert_eq!(limit.get(), 15);
     }
+
+    #[test]
+    fn test_iter_limit_remaining() {
+        let mut limit = IterLimit::new(5);
+        assert_eq!(limit.remaining(), 5);
+        for _ in 0..3 {
+            limit.inc();
+        }
+        assert_eq!(limit.remaining(), 2);
+        for _ in 0..3 {
+            limit.inc();
+        }
+        assert_eq!(limit.remaining(), 0);
+    }
+
+    #[test]
+    fn test_iter_limit_set_max() {
+        let mut limit = IterLimit::new(5);
+        limit.set(3);
+        limit.set_max(10);
+        assert_eq!(limit.get(), 3);
+        assert_eq!(limit.remaining(), 7);
+    }
 }
@@ -0,0 +1,169 @@
+// file: macros.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Macros for the functions that the simulators and their builders share.
+//!
+//! The generated functions use the fields of the simulator by name, so a simulator
+//! using them has fields called `population`, `iter_limit`, `duration` and `error`,
+//! and, depending on the builder functions, `earlystoppers`, `selector`, `crossover`,
+//! `mutator` and `pool`.
+
+/// Implement `checked_step`, `run`, `iterations`, `time` and `population` of `Simulation`.
+macro_rules! simulation_functions {
+    () => {
+        #[allow(deprecated)]
+        fn checked_step(&mut self) -> $crate::sim::StepResult {
+            if self.error.is_some() {
+                panic!("Attempt to step a Simulator after an error!")
+            } else {
+                self.step()
+            }
+        }
+
+        #[allow(deprecated)]
+        fn run(&mut self) -> $crate::sim::RunResult {
+            // Loop until Failure or Done.
+            loop {
+                match self.step() {
+                    $crate::sim::StepResult::Success => {}
+                    $crate::sim::StepResult::Failure => return $crate::sim::RunResult::Failure,
+                    $crate::sim::StepResult::Done => return $crate::sim::RunResult::Done,
+                }
+            }
+        }
+
+        fn iterations(&self) -> u64 {
+            self.iter_limit.get()
+        }
+
+        fn time(&self) -> Option<$crate::sim::NanoSecond> {
+            self.duration
+        }
+
+        fn population(&self) -> Vec<T> {
+            self.population.clone()
+        }
+    };
+}
+
+/// Implement the given `with_*` functions of a `SimulatorBuilder` whose simulator is
+/// stored in the field `sim`.
+macro_rules! builder_functions {
+    ($($name:ident),*) => {
+        $(builder_function!($name);)*
+    };
+}
+
+macro_rules! builder_function {
+    (with_selector) => {
+        /// Set the selector of the resulting `Simulator`.
+        ///
+        /// Returns a mutable reference to itself for chaining purposes.
+        /// Does not consume the builder.
+        pub fn with_selector(
+            &mut self,
+            sel: Box<dyn $crate::sim::select::Selector<T, F>>,
+        ) -> &mut Self {
+            self.sim.selector = sel;
+            self
+        }
+    };
+    (with_max_iters) => {
+        /// Set the maximum number of iterations of the resulting `Simulator`.
+        ///
+        /// The `Simulator` will stop running after this number of iterations.
+        ///
+        /// Returns a mutable reference to itself for chaining purposes.
+        /// Does not consume the builder.
+        pub fn with_max_iters(&mut self, i: u64) -> &mut Self {
+            self.sim.iter_limit.set_max(i);
+            self
+        }
+    };
+    (with_early_stop) => {
+        /// Set early stopping. If for `n_iters` iterations, the change in the highest fitness
+        /// is smaller than `delta`, the simulator will stop running.
+        ///
+        /// Calling this function multiple times adds multiple early stopping conditions.
+        /// The simulator stops as soon as any of them is met.
+        ///
+        /// Returns a mutable reference to itself for chaining purposes.
+        /// Does not consume the builder.
+        pub fn with_early_stop(&mut self, delta: F, n_iters: u64) -> &mut Self {
+            self.sim
+                .earlystoppers
+                .push($crate::sim::earlystopper::EarlyStopper::new(delta, n_iters));
+            self
+        }
+    };
+    (with_crossover) => {
+        /// Set the crossover operator of the resulting `Simulator`.
+        ///
+        /// The operator replaces `Phenotype::crossover` when creating children.
+        ///
+        /// Returns a mutable reference to itself for chaining purposes.
+        /// Does not consume the builder.
+        pub fn with_crossover(
+            &mut self,
+            op: Box<dyn $crate::sim::crossover::CrossoverOperator<T>>,
+        ) -> &mut Self {
+            self.sim.crossover = Some(op);
+            self
+        }
+    };
+    (with_mutator) => {
+        /// Set the mutation operator of the resulting `Simulator`.
+        ///
+        /// The operator replaces `Phenotype::mutate` when mutating children.
+        ///
+        /// Returns a mutable reference to itself for chaining purposes.
+        /// Does not consume the builder.
+        pub fn with_mutator(
+            &mut self,
+            op: Box<dyn $crate::sim::mutation::MutationOperator<T>>,
+        ) -> &mut Self {
+            self.sim.mutator = Some(op);
+            self
+        }
+    };
+    (with_threads) => {
+        /// Run the resulting `Simulator` on a new rayon thread pool with `n` threads,
+        /// instead of the global pool. If `n` is zero, rayon chooses the number of threads.
+        ///
+        /// The pool is built when the simulator first steps; if that fails, the step fails.
+        ///
+        /// Returns a mutable reference to itself for chaining purposes.
+        /// Does not consume the builder.
+        pub fn with_threads(&mut self, n: usize) -> &mut Self {
+            self.sim.pool.set_threads(n);
+            self
+        }
+    };
+    (with_thread_pool) => {
+        /// Run the resulting `Simulator` on an existing rayon thread pool, instead of the
+        /// global pool, e.g. to share the pool of the application.
+        ///
+        /// Returns a mutable reference to itself for chaining purposes.
+        /// Does not consume the builder.
+        pub fn with_thread_pool(
+            &mut self,
+            pool: ::std::sync::Arc<::rayon::ThreadPool>,
+        ) -> &mut Self {
+            self.sim.pool.set_pool(pool);
+            self
+        }
+    };
+}
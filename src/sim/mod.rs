@@ -14,12 +14,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use error::Error;
 use pheno::{Fitness, Phenotype};
+use rand::{ChaChaRng, Isaac64Rng, IsaacRng, Rng, SeedableRng, StdRng, XorShiftRng};
+use std::fmt;
+use std::time::{Duration, Instant};
 
+#[macro_use]
+mod macros;
+
+pub mod checkpoint;
+pub mod crossover;
 mod earlystopper;
+pub mod es;
+pub mod handle;
+pub mod harness;
+pub mod island;
 mod iterlimit;
+pub mod mutation;
+pub mod par;
+pub mod parameterless;
+pub mod pipeline;
+pub mod repair;
+pub mod replace;
+pub mod report;
 pub mod select;
 pub mod seq;
+pub mod termination;
 pub mod types;
 
 /// A `Builder` can create new instances of an object.
@@ -31,11 +52,110 @@ pub trait Builder<T: ?Sized> {
         T: Sized;
 }
 
+/// A user-supplied callback stored inside a `Simulator`.
+///
+/// Wrapping the boxed closure allows simulators to keep deriving `Debug`.
+struct Callback<C: ?Sized>(Box<C>);
+
+impl<C: ?Sized> fmt::Debug for Callback<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+/// An individual together with its fitness, so that the fitness is only computed once.
+#[derive(Clone, Debug)]
+struct Evaluated<T, F> {
+    individual: T,
+    fitness: F,
+}
+
+/// Get the index of the highest value in `fitness`, or `None` if it is empty.
+///
+/// Like `best_index`, ties are broken in favour of the lowest index.
+fn best_of<F: Ord>(fitness: &[F]) -> Option<usize> {
+    let mut best: Option<usize> = None;
+    for (i, f) in fitness.iter().enumerate() {
+        if best.map_or(true, |b| *f > fitness[b]) {
+            best = Some(i);
+        }
+    }
+    best
+}
+
+/// Get the index of the best individual in `population`, or `None` if it is empty.
+///
+/// When several individuals share the highest fitness, the one with the lowest
+/// index is returned, so that the reported winner is deterministic.
+fn best_index<T, F>(population: &[T]) -> Option<usize>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    let mut best: Option<(usize, F)> = None;
+    for (i, x) in population.iter().enumerate() {
+        let fitness = x.fitness();
+        let better = match best {
+            Some((_, ref b)) => fitness > *b,
+            None => true,
+        };
+        if better {
+            best = Some((i, fitness));
+        }
+    }
+    best.map(|(i, _)| i)
+}
+
+/// A random number generator that can be created with a random seed.
+///
+/// `seq::Simulator` needs this to implement `Simulation::builder` for its random
+/// number generator. It is implemented for the generators in `rand`; to use another
+/// generator, implement this trait for it or pass it to `seq::Simulator::builder_with_rng`.
+pub trait RandomSeeded: Rng + Sized {
+    /// Create a generator with a random seed.
+    fn random_seeded() -> Self;
+}
+
+impl RandomSeeded for StdRng {
+    fn random_seeded() -> StdRng {
+        let mut rng = ::rand::thread_rng();
+        let seed: Vec<usize> = (0..4).map(|_| rng.gen()).collect();
+        StdRng::from_seed(&seed[..])
+    }
+}
+
+macro_rules! implement_random_seeded {
+    ( $($t:ty),* ) => {
+        $(
+            impl RandomSeeded for $t {
+                fn random_seeded() -> $t {
+                    ::rand::thread_rng().gen()
+                }
+            }
+        )*
+    };
+}
+
+implement_random_seeded!(XorShiftRng, IsaacRng, Isaac64Rng, ChaChaRng);
+
+/// Create a `StdRng` from a 64-bit seed, as used by the `with_seed` builder functions.
+fn seeded_rng(seed: u64) -> StdRng {
+    StdRng::from_seed(&[(seed & 0xffff_ffff) as usize, (seed >> 32) as usize])
+}
+
+/// Get the number of nanoseconds elapsed since `start`, or `None` in case of an overflow.
+fn elapsed_nanos(start: Instant) -> Option<NanoSecond> {
+    let elapsed = start.elapsed();
+    (elapsed.as_secs() as NanoSecond)
+        .checked_mul(1_000_000_000)
+        .and_then(|x| x.checked_add(NanoSecond::from(elapsed.subsec_nanos())))
+}
+
 /// Simulation run time is defined in nanoseconds.
 pub type NanoSecond = i64;
 /// The result of a simulation, containing the best phenotype
-/// or an error message.
-pub type SimResult<'a, T> = Result<&'a T, &'a str>;
+/// or the error that stopped it.
+pub type SimResult<'a, T> = Result<&'a T, &'a Error>;
 
 /// The result of running a single step.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -57,6 +177,22 @@ pub enum RunResult {
     Done,
 }
 
+/// A summary of a partial run, as returned by `Simulation::run_for`
+/// and `Simulation::run_for_duration`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RunSummary {
+    /// The result of the last step that was made:
+    ///
+    /// * `StepResult::Success` if the simulation can continue.
+    /// * `StepResult::Failure` if an error occurred. Check the result of `get()`.
+    /// * `StepResult::Done` if the simulation finished.
+    pub status: StepResult,
+    /// The number of steps that were made during this partial run.
+    pub steps: u64,
+    /// The total number of iterations the `Simulation` has executed so far.
+    pub iterations: u64,
+}
+
 /// A `Simulation` is an execution of a genetic algorithm.
 pub trait Simulation<'a, T, F>
 where
@@ -99,20 +235,60 @@ where
     /// Unlike `step`, this function will panic if it is called after a failure.
     /// To avoid this panic, match `StepResult` for `StepResult::Failure` and exit gracefully.
     fn checked_step(&mut self) -> StepResult;
+    /// Make at most `n_steps` steps in the simulation, stopping early on
+    /// failure or when the simulation is done.
+    ///
+    /// This sits between `checked_step` and `run`, allowing applications to
+    /// interleave work on the simulation with other tasks. Like `checked_step`,
+    /// this function will panic if it is called after a failure.
+    fn run_for(&mut self, n_steps: u64) -> RunSummary {
+        let mut status = StepResult::Success;
+        let mut steps = 0;
+        while steps < n_steps {
+            status = self.checked_step();
+            if status != StepResult::Success {
+                break;
+            }
+            steps += 1;
+        }
+        RunSummary {
+            status,
+            steps,
+            iterations: self.iterations(),
+        }
+    }
+    /// Make steps in the simulation until `duration` has elapsed, stopping early
+    /// on failure or when the simulation is done.
+    ///
+    /// The duration is checked between steps, so the call may take longer than
+    /// `duration` by at most the duration of one step. Like `checked_step`,
+    /// this function will panic if it is called after a failure.
+    fn run_for_duration(&mut self, duration: Duration) -> RunSummary {
+        let start = Instant::now();
+        let mut status = StepResult::Success;
+        let mut steps = 0;
+        while start.elapsed() < duration {
+            status = self.checked_step();
+            if status != StepResult::Success {
+                break;
+            }
+            steps += 1;
+        }
+        RunSummary {
+            status,
+            steps,
+            iterations: self.iterations(),
+        }
+    }
     /// Get the result of the latest step or of a complete run.
     ///
     /// This function will either return the best performing individual,
-    /// or an error string indicating what went wrong.
+    /// or an `Error` describing what went wrong. If several individuals
+    /// share the highest fitness, the first one in the population is returned.
     fn get(&'a self) -> SimResult<'a, T>;
     /// Get the number of nanoseconds spent running, or `None` in case of an overflow.
-    ///
-    /// When `Self` is `par::Simulator`, i.e. a parallel simulator is used,
-    /// the duration is the average duration of all child simulators.
     fn time(&self) -> Option<NanoSecond>;
     /// Get the number of iterations the `Simulator` has executed so far.
-    ///
-    /// When `Self` is `par::Simulator`, i.e. a parallel simulator is used,
-    /// this returns the number of iterations made by the parallel simulator itself.
     fn iterations(&self) -> u64;
     /// Get the current population.
     ///
@@ -120,3 +296,24 @@ where
     /// it sparingly.
     fn population(&self) -> Vec<T>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::best_index;
+    use test::Test;
+
+    #[test]
+    fn test_best_index_first_of_ties() {
+        let population: Vec<Test> = vec![1, -5, 3, 5, -1]
+            .into_iter()
+            .map(|f| Test { f })
+            .collect();
+        assert_eq!(best_index(&population), Some(1));
+    }
+
+    #[test]
+    fn test_best_index_empty() {
+        let population: Vec<Test> = Vec::new();
+        assert_eq!(best_index(&population), None);
+    }
+}
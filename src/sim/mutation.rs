@@ -0,0 +1,470 @@
+// file: mutation.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The mutation module provides a trait that can be implemented to mutate
+//! phenotypes independently of their `Phenotype::mutate` implementation.
+//!
+//! An operator is configured on a simulator builder using `with_mutator`.
+//! Any operator can be wrapped in a `WithProbability`, so that it is only applied
+//! to a fraction of the individuals. For phenotypes that implement `Genome`,
+//! `GeneMutation` mutates every gene independently, optionally with a different
+//! probability per gene. For variable-length genomes, `DuplicationMutation`,
+//! `DeletionMutation` and `TranspositionMutation` change the structure of the genome.
+//! `SwapMutation` and `TranspositionMutation` only reorder genes, so they keep
+//! permutation encodings valid.
+
+use pheno::Genome;
+use rand::Rng;
+use std::fmt;
+
+/// A `MutationOperator` creates a mutated copy of a phenotype.
+///
+/// Operators must be `Send` and `Sync`, so that boxed operators can be shared
+/// between threads.
+pub trait MutationOperator<T>: fmt::Debug + Send + Sync {
+    /// Create a mutated copy of `x`.
+    fn mutate(&self, x: &T) -> T;
+
+    /// Create a mutated copy of `x`, making every random decision with `rng`.
+    ///
+    /// Simulators that can be seeded call this function, so that runs are reproducible.
+    /// By default, `rng` is ignored and `mutate` is called, so an operator that uses
+    /// its own random number generator should override this function.
+    fn mutate_with_rng(&self, x: &T, rng: &mut dyn Rng) -> T {
+        let _ = rng;
+        self.mutate(x)
+    }
+}
+
+/// Applies another mutation operator with a fixed probability per individual.
+///
+/// Individuals that are not mutated are returned unchanged.
+#[derive(Clone, Copy, Debug)]
+pub struct WithProbability<M> {
+    op: M,
+    probability: f64,
+}
+
+impl<M> WithProbability<M> {
+    /// Create and return a wrapper that applies `op` with probability `probability`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` is not between zero and one, inclusive.
+    pub fn new(op: M, probability: f64) -> WithProbability<M> {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "Invalid mutation probability: {}. Should be between zero and one.",
+            probability
+        );
+        WithProbability { op, probability }
+    }
+}
+
+impl<T, M> MutationOperator<T> for WithProbability<M>
+where
+    T: Clone,
+    M: MutationOperator<T>,
+{
+    fn mutate(&self, x: &T) -> T {
+        self.mutate_with_rng(x, &mut ::rand::thread_rng())
+    }
+
+    fn mutate_with_rng(&self, x: &T, mut rng: &mut dyn Rng) -> T {
+        let rng = &mut rng;
+        if self.probability >= 1.0 || rng.gen::<f64>() < self.probability {
+            self.op.mutate_with_rng(x, *rng)
+        } else {
+            x.clone()
+        }
+    }
+}
+
+/// Mutates each gene of a `Genome` independently with a given probability.
+///
+/// The mutation of a single gene is defined by a function, e.g. flipping a bit or
+/// adding a small random number. It is given the random number generator of the
+/// operator, so that seeded simulators are reproducible. By default every gene has
+/// the same probability of being mutated, but per-gene probabilities can be set to
+/// focus mutation on the genes that are known to matter most.
+pub struct GeneMutation<M> {
+    probability: f64,
+    gene_probabilities: Vec<f64>,
+    mutate_gene: M,
+}
+
+impl<M> GeneMutation<M> {
+    /// Create and return an operator that mutates each gene with probability `probability`
+    /// using `mutate_gene`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` is not between zero and one, inclusive.
+    pub fn new(probability: f64, mutate_gene: M) -> GeneMutation<M> {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "Invalid mutation probability: {}. Should be between zero and one.",
+            probability
+        );
+        GeneMutation {
+            probability,
+            gene_probabilities: Vec::new(),
+            mutate_gene,
+        }
+    }
+
+    /// Set the mutation probability of each gene separately.
+    ///
+    /// The gene at index `i` is mutated with probability `probabilities[i]`. Genes beyond
+    /// the end of `probabilities` use the probability passed to `new`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any probability is not between zero and one, inclusive.
+    pub fn with_gene_probabilities(mut self, probabilities: Vec<f64>) -> GeneMutation<M> {
+        if let Some(p) = probabilities.iter().find(|p| !(0.0..=1.0).contains(*p)) {
+            panic!(
+                "Invalid mutation probability: {}. Should be between zero and one.",
+                p
+            );
+        }
+        self.gene_probabilities = probabilities;
+        self
+    }
+
+    /// Get the mutation probability of the gene at index `i`.
+    fn probability(&self, i: usize) -> f64 {
+        self.gene_probabilities
+            .get(i)
+            .cloned()
+            .unwrap_or(self.probability)
+    }
+}
+
+impl<T, M> MutationOperator<T> for GeneMutation<M>
+where
+    T: Genome,
+    M: Fn(&T::Gene, &mut dyn Rng) -> T::Gene + Send + Sync,
+{
+    fn mutate(&self, x: &T) -> T {
+        self.mutate_with_rng(x, &mut ::rand::thread_rng())
+    }
+
+    fn mutate_with_rng(&self, x: &T, mut rng: &mut dyn Rng) -> T {
+        let rng = &mut rng;
+        let genes = x
+            .genes()
+            .iter()
+            .enumerate()
+            .map(|(i, gene)| {
+                let p = self.probability(i);
+                if p >= 1.0 || rng.gen::<f64>() < p {
+                    (self.mutate_gene)(gene, &mut **rng)
+                } else {
+                    gene.clone()
+                }
+            })
+            .collect();
+        x.with_genes(genes)
+    }
+}
+
+impl<M> fmt::Debug for GeneMutation<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GeneMutation")
+            .field("probability", &self.probability)
+            .field("gene_probabilities", &self.gene_probabilities)
+            .finish()
+    }
+}
+
+/// Pick a random segment `(start, len)` of a genome of length `genome_len`,
+/// with `1 <= len <= max_len`. `genome_len` and `max_len` must be positive.
+fn segment<R: Rng>(rng: &mut R, genome_len: usize, max_len: usize) -> (usize, usize) {
+    let len = rng.gen_range(1, max_len.min(genome_len) + 1);
+    let start = rng.gen_range(0, genome_len - len + 1);
+    (start, len)
+}
+
+/// Duplicates a random segment of genes, inserting the copy right after the original.
+///
+/// The genome never grows beyond the maximum length. A genome that already has the
+/// maximum length, or that is empty, is returned unchanged.
+#[derive(Clone, Copy, Debug)]
+pub struct DuplicationMutation {
+    max_len: usize,
+}
+
+impl DuplicationMutation {
+    /// Create and return a duplication operator for genomes of at most `max_len` genes.
+    pub fn new(max_len: usize) -> DuplicationMutation {
+        DuplicationMutation { max_len }
+    }
+}
+
+impl<T> MutationOperator<T> for DuplicationMutation
+where
+    T: Genome,
+{
+    fn mutate(&self, x: &T) -> T {
+        self.mutate_with_rng(x, &mut ::rand::thread_rng())
+    }
+
+    fn mutate_with_rng(&self, x: &T, mut rng: &mut dyn Rng) -> T {
+        let rng = &mut rng;
+        let genes = x.genes();
+        if genes.is_empty() || genes.len() >= self.max_len {
+            return x.clone();
+        }
+        let (start, len) = segment(rng, genes.len(), self.max_len - genes.len());
+        let mut result = genes[..start + len].to_vec();
+        result.extend_from_slice(&genes[start..]);
+        x.with_genes(result)
+    }
+}
+
+/// Deletes a random segment of genes.
+///
+/// The genome never shrinks below the minimum length. A genome that already has the
+/// minimum length is returned unchanged.
+#[derive(Clone, Copy, Debug)]
+pub struct DeletionMutation {
+    min_len: usize,
+}
+
+impl DeletionMutation {
+    /// Create and return a deletion operator for genomes of at least `min_len` genes.
+    pub fn new(min_len: usize) -> DeletionMutation {
+        DeletionMutation { min_len }
+    }
+}
+
+impl<T> MutationOperator<T> for DeletionMutation
+where
+    T: Genome,
+{
+    fn mutate(&self, x: &T) -> T {
+        self.mutate_with_rng(x, &mut ::rand::thread_rng())
+    }
+
+    fn mutate_with_rng(&self, x: &T, mut rng: &mut dyn Rng) -> T {
+        let rng = &mut rng;
+        let genes = x.genes();
+        if genes.len() <= self.min_len {
+            return x.clone();
+        }
+        let (start, len) = segment(rng, genes.len(), genes.len() - self.min_len);
+        let mut result = genes[..start].to_vec();
+        result.extend_from_slice(&genes[start + len..]);
+        x.with_genes(result)
+    }
+}
+
+/// Moves a random segment of genes to another position in the genome.
+///
+/// The length of the genome does not change.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TranspositionMutation;
+
+impl TranspositionMutation {
+    /// Create and return a transposition operator.
+    pub fn new() -> TranspositionMutation {
+        TranspositionMutation
+    }
+}
+
+impl<T> MutationOperator<T> for TranspositionMutation
+where
+    T: Genome,
+{
+    fn mutate(&self, x: &T) -> T {
+        self.mutate_with_rng(x, &mut ::rand::thread_rng())
+    }
+
+    fn mutate_with_rng(&self, x: &T, mut rng: &mut dyn Rng) -> T {
+        let rng = &mut rng;
+        let genes = x.genes();
+        if genes.len() < 2 {
+            return x.clone();
+        }
+        let (start, len) = segment(rng, genes.len(), genes.len() - 1);
+        let mut rest = genes[..start].to_vec();
+        rest.extend_from_slice(&genes[start + len..]);
+        let at = rng.gen_range(0, rest.len() + 1);
+        let mut result = rest[..at].to_vec();
+        result.extend_from_slice(&genes[start..start + len]);
+        result.extend_from_slice(&rest[at..]);
+        x.with_genes(result)
+    }
+}
+
+/// Swaps two randomly chosen genes.
+///
+/// The length of the genome does not change, and neither does the number of
+/// occurrences of every gene.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SwapMutation;
+
+impl SwapMutation {
+    /// Create and return a swap operator.
+    pub fn new() -> SwapMutation {
+        SwapMutation
+    }
+}
+
+impl<T> MutationOperator<T> for SwapMutation
+where
+    T: Genome,
+{
+    fn mutate(&self, x: &T) -> T {
+        self.mutate_with_rng(x, &mut ::rand::thread_rng())
+    }
+
+    fn mutate_with_rng(&self, x: &T, mut rng: &mut dyn Rng) -> T {
+        let rng = &mut rng;
+        let mut genes = x.genes().to_vec();
+        if genes.len() < 2 {
+            return x.clone();
+        }
+        let i = rng.gen_range(0, genes.len());
+        let j = rng.gen_range(0, genes.len());
+        genes.swap(i, j);
+        x.with_genes(genes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test::Genes;
+
+    #[derive(Debug)]
+    struct Increment;
+
+    impl MutationOperator<i32> for Increment {
+        fn mutate(&self, x: &i32) -> i32 {
+            x + 1
+        }
+    }
+
+    #[test]
+    fn test_probability_bounds() {
+        assert_eq!(WithProbability::new(Increment, 0.0).mutate(&1), 1);
+        assert_eq!(WithProbability::new(Increment, 1.0).mutate(&1), 2);
+    }
+
+    #[test]
+    fn test_probability() {
+        let op = WithProbability::new(Increment, 0.5);
+        let mutated = (0..1000).filter(|_| op.mutate(&0) == 1).count();
+        assert!(mutated > 300 && mutated < 700);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_probability() {
+        WithProbability::new(Increment, 1.5);
+    }
+
+    #[test]
+    fn test_gene_mutation() {
+        let op = GeneMutation::new(1.0, |x: &i64, _: &mut dyn Rng| x + 1);
+        let child = op.mutate(&Genes {
+            genes: vec![0, 1, 2],
+        });
+        assert_eq!(child.genes, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_gene_probabilities() {
+        let op = GeneMutation::new(0.0, |x: &i64, _: &mut dyn Rng| x + 1)
+            .with_gene_probabilities(vec![1.0, 0.0]);
+        for _ in 0..10 {
+            let child = op.mutate(&Genes {
+                genes: vec![0, 0, 0],
+            });
+            assert_eq!(child.genes, vec![1, 0, 0]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_gene_probability() {
+        GeneMutation::new(0.5, |x: &i64, _: &mut dyn Rng| x + 1)
+            .with_gene_probabilities(vec![0.5, -1.0]);
+    }
+
+    #[test]
+    fn test_duplication() {
+        let op = DuplicationMutation::new(8);
+        let parent = Genes {
+            genes: vec![0, 1, 2, 3, 4],
+        };
+        for _ in 0..100 {
+            let child = op.mutate(&parent);
+            assert!(child.genes.len() > 5 && child.genes.len() <= 8);
+            // The duplicated segment ends where its copy starts.
+            let extra = child.genes.len() - 5;
+            let copy = (0..=5 - extra).any(|start| {
+                child.genes[..start + extra] == parent.genes[..start + extra]
+                    && child.genes[start + extra..] == parent.genes[start..]
+            });
+            assert!(copy);
+        }
+        let full = Genes { genes: vec![0; 8] };
+        assert_eq!(op.mutate(&full), full);
+    }
+
+    #[test]
+    fn test_deletion() {
+        let op = DeletionMutation::new(3);
+        let parent = Genes {
+            genes: vec![0, 1, 2, 3, 4],
+        };
+        for _ in 0..100 {
+            let child = op.mutate(&parent);
+            assert!(child.genes.len() >= 3 && child.genes.len() < 5);
+            // The remaining genes keep their order.
+            assert!(child.genes.windows(2).all(|w| w[0] < w[1]));
+        }
+        let short = Genes { genes: vec![0; 3] };
+        assert_eq!(op.mutate(&short), short);
+    }
+
+    #[test]
+    fn test_transposition() {
+        let op = TranspositionMutation::new();
+        for _ in 0..100 {
+            let mut child = op.mutate(&Genes {
+                genes: vec![0, 1, 2, 3, 4],
+            });
+            child.genes.sort();
+            assert_eq!(child.genes, vec![0, 1, 2, 3, 4]);
+        }
+    }
+
+    #[test]
+    fn test_swap() {
+        let op = SwapMutation::new();
+        for _ in 0..100 {
+            let child = op.mutate(&Genes {
+                genes: vec![0, 1, 2, 3, 4],
+            });
+            let moved = (0..5).filter(|&i| child.genes[i] != i as i64).count();
+            assert!(moved == 0 || moved == 2);
+        }
+    }
+}
@@ -0,0 +1,492 @@
+// file: par.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains a parallel implementation of `::sim::Simulation`,
+//! called a `Simulator`.
+//!
+//! Fitness evaluation, crossover and mutation are spread over the rayon
+//! thread pool, which pays off when these operations are expensive.
+//! The fitness of every individual is computed once per iteration and passed to
+//! the selector, which itself runs on a single thread of the pool.
+//! The global pool is used unless another one is configured with `with_threads`
+//! or `with_thread_pool`.
+//!
+//! To use a `Simulator`, you need a `SimulatorBuilder`, which you can
+//! obtain by calling `Simulator::builder()`.
+
+use super::crossover::CrossoverOperator;
+use super::earlystopper::*;
+use super::iterlimit::*;
+use super::mutation::MutationOperator;
+use super::replace::kill_off;
+use super::select::*;
+use super::*;
+use error::Error;
+use pheno::Fitness;
+use pheno::Phenotype;
+use rand::StdRng;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// A parallel implementation of `::sim::Simulation`.
+/// The genetic operators are run on the rayon thread pool.
+#[derive(Debug)]
+pub struct Simulator<'a, T, F>
+where
+    T: 'a + Phenotype<F> + Send + Sync,
+    F: Fitness + Send + Sync,
+{
+    population: &'a mut Vec<T>,
+    iter_limit: IterLimit,
+    selector: Box<dyn Selector<T, F>>,
+    earlystoppers: Vec<EarlyStopper<F>>,
+    crossover: Option<Box<dyn CrossoverOperator<T>>>,
+    mutator: Option<Box<dyn MutationOperator<T>>>,
+    duration: Option<NanoSecond>,
+    error: Option<Error>,
+    /// Index of the best individual in the population, if known.
+    best: Option<usize>,
+    /// Fitness of every individual in the population, if known.
+    fitness: Option<Vec<F>>,
+    rng: StdRng,
+    pool: ThreadPoolConfig,
+}
+
+impl<'a, T, F> Simulation<'a, T, F> for Simulator<'a, T, F>
+where
+    T: Phenotype<F> + Send + Sync,
+    F: Fitness + Send + Sync,
+{
+    type B = SimulatorBuilder<'a, T, F>;
+
+    /// Create builder.
+    fn builder(population: &'a mut Vec<T>) -> SimulatorBuilder<'a, T, F> {
+        SimulatorBuilder {
+            sim: Simulator {
+                population,
+                iter_limit: IterLimit::new(100),
+                selector: Box::new(UnstableMaximizeSelector::new(3)),
+                earlystoppers: Vec::new(),
+                crossover: None,
+                mutator: None,
+                duration: Some(0),
+                error: None,
+                best: None,
+                fitness: None,
+                rng: StdRng::random_seeded(),
+                pool: ThreadPoolConfig::default(),
+            },
+        }
+    }
+
+    fn step(&mut self) -> StepResult {
+        if self.population.is_empty() {
+            self.error = Some(Error::EmptyPopulation);
+            return StepResult::Failure;
+        }
+
+        let should_stop =
+            self.iter_limit.reached() || self.earlystoppers.iter().any(|x| x.reached());
+        if should_stop {
+            return StepResult::Done;
+        }
+
+        let time_start = Instant::now();
+
+        if let Err(e) = self.evaluate() {
+            self.error = Some(e);
+            return StepResult::Failure;
+        }
+        let result = {
+            let population = &*self.population;
+            let fitness = self.fitness.as_ref().unwrap();
+            let selector = &self.selector;
+            let crossover = &self.crossover;
+            let mutator = &self.mutator;
+            let generation = self.iter_limit.get();
+            let rng = &mut self.rng;
+            self.pool.install(|| {
+                let context = SelectionContext::new(population, fitness, generation, rng);
+                let parents = selector.select_from(context)?;
+                Ok(parents
+                    .par_iter()
+                    .map(|&(a, b)| {
+                        let child = match *crossover {
+                            Some(ref op) => op.crossover(a, b),
+                            None => a.crossover(b),
+                        };
+                        match *mutator {
+                            Some(ref op) => op.mutate(&child),
+                            None => child.mutate(),
+                        }
+                    })
+                    .collect())
+            })
+        };
+        let mut children: Vec<T> = match result.and_then(|children| children) {
+            Ok(children) => children,
+            Err(e) => {
+                self.error = Some(e);
+                return StepResult::Failure;
+            }
+        };
+        // Kill off parts of the population at random to make room for the children
+        kill_off(self.population, children.len(), &mut self.rng);
+        self.population.append(&mut children);
+        self.fitness = None;
+
+        if let Err(e) = self.evaluate() {
+            self.error = Some(e);
+            return StepResult::Failure;
+        }
+        let fitness = self.fitness.as_ref().unwrap();
+        let best = best_of(fitness).unwrap();
+        self.best = Some(best);
+        for stopper in &mut self.earlystoppers {
+            stopper.update(fitness[best].clone());
+        }
+
+        self.iter_limit.inc();
+        self.duration = match (self.duration, elapsed_nanos(time_start)) {
+            (Some(x), Some(y)) => x.checked_add(y),
+            _ => None,
+        };
+
+        StepResult::Success
+    }
+
+    fn get(&'a self) -> SimResult<'a, T> {
+        match self.error {
+            Some(ref e) => Err(e),
+            None => {
+                let best = match self.best {
+                    Some(best) => best,
+                    None => {
+                        let population = &*self.population;
+                        self.pool.run(|| par_best(population))
+                    }
+                };
+                Ok(&self.population[best])
+            }
+        }
+    }
+
+    simulation_functions!();
+}
+
+impl<'a, T, F> Simulator<'a, T, F>
+where
+    T: Phenotype<F> + Send + Sync,
+    F: Fitness + Send + Sync,
+{
+    /// Compute the fitness of every individual in parallel, unless it is known.
+    ///
+    /// The fitness is cached until the population changes, so that selection and
+    /// early stopping do not evaluate the population again.
+    fn evaluate(&mut self) -> Result<(), Error> {
+        if self.fitness.is_none() {
+            let population = &*self.population;
+            let fitness = self
+                .pool
+                .install(|| population.par_iter().map(|x| x.fitness()).collect())?;
+            self.fitness = Some(fitness);
+        }
+        Ok(())
+    }
+}
+
+/// Evaluate the fitness of every individual in parallel, and return the index
+/// of the best one.
+///
+/// Like `best_index`, ties are broken in favour of the lowest index.
+/// `population` must not be empty.
+fn par_best<T, F>(population: &[T]) -> usize
+where
+    T: Phenotype<F> + Sync,
+    F: Fitness + Send + Sync,
+{
+    population
+        .par_iter()
+        .enumerate()
+        .map(|(i, x)| (i, x.fitness()))
+        .reduce_with(|a, b| if b.1 > a.1 { b } else { a })
+        .unwrap()
+        .0
+}
+
+/// The rayon thread pool a parallel simulator runs on.
+///
+/// By default, the global pool is used. A pool with a fixed number of threads is
+/// built when it is first needed, so that building it can fail the step.
+#[derive(Debug, Default)]
+pub(super) struct ThreadPoolConfig {
+    threads: Option<usize>,
+    pool: Option<Arc<ThreadPool>>,
+}
+
+impl ThreadPoolConfig {
+    /// Use a new pool with `n` threads.
+    pub(super) fn set_threads(&mut self, n: usize) {
+        self.threads = Some(n);
+        self.pool = None;
+    }
+
+    /// Use an existing pool.
+    pub(super) fn set_pool(&mut self, pool: Arc<ThreadPool>) {
+        self.threads = None;
+        self.pool = Some(pool);
+    }
+
+    /// Run `op` on the configured pool, building it first if needed.
+    pub(super) fn install<OP, R>(&mut self, op: OP) -> Result<R, Error>
+    where
+        OP: FnOnce() -> R + Send,
+        R: Send,
+    {
+        if let (None, Some(n)) = (self.pool.as_ref(), self.threads) {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(n)
+                .build()
+                .map_err(|e| Error::ThreadPool(e.to_string()))?;
+            self.pool = Some(Arc::new(pool));
+        }
+        Ok(self.run(op))
+    }
+
+    /// Run `op` on the configured pool if it has been built, and on the current one otherwise.
+    pub(super) fn run<OP, R>(&self, op: OP) -> R
+    where
+        OP: FnOnce() -> R + Send,
+        R: Send,
+    {
+        match self.pool {
+            Some(ref pool) => pool.install(op),
+            None => op(),
+        }
+    }
+}
+
+/// A `Builder` for the `Simulator` type.
+#[derive(Debug)]
+pub struct SimulatorBuilder<'a, T, F>
+where
+    T: 'a + Phenotype<F> + Send + Sync,
+    F: Fitness + Send + Sync,
+{
+    sim: Simulator<'a, T, F>,
+}
+
+impl<'a, T, F> SimulatorBuilder<'a, T, F>
+where
+    T: Phenotype<F> + Send + Sync,
+    F: Fitness + Send + Sync,
+{
+    builder_functions!(
+        with_selector,
+        with_max_iters,
+        with_early_stop,
+        with_crossover,
+        with_mutator,
+        with_threads,
+        with_thread_pool
+    );
+
+    /// Seed the random number generator of the resulting `Simulator`.
+    ///
+    /// Selection and replacement draw from this generator. Crossover and mutation run
+    /// in parallel, so they are not covered.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_seed(&mut self, seed: u64) -> &mut Self {
+        self.sim.rng = seeded_rng(seed);
+        self
+    }
+}
+
+impl<'a, T, F> Builder<Simulator<'a, T, F>> for SimulatorBuilder<'a, T, F>
+where
+    T: Phenotype<F> + Send + Sync,
+    F: Fitness + Send + Sync,
+{
+    fn build(self) -> Simulator<'a, T, F> {
+        self.sim
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::par_best;
+    use pheno::Phenotype;
+    use rayon::{self, ThreadPoolBuilder};
+    use sim::crossover::CrossoverOperator;
+    use sim::select::*;
+    use sim::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use test::{MyFitness, Test};
+
+    #[test]
+    fn test_par_best_first_of_ties() {
+        let population: Vec<Test> = vec![1, -5, 3, 5, -1]
+            .into_iter()
+            .map(|f| Test { f })
+            .collect();
+        assert_eq!(par_best(&population), 1);
+    }
+
+    #[test]
+    fn test_max_iters() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = par::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_max_iters(5);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+        assert_eq!(s.iterations(), 5);
+        assert_eq!(s.population().len(), 100);
+    }
+
+    #[test]
+    fn test_early_stopping() {
+        let mut population: Vec<Test> = (0..100).map(|_| Test { f: 0 }).collect();
+        let mut builder = par::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_early_stop(MyFitness { f: 10 }, 3)
+            .with_max_iters(10);
+        let mut s = builder.build();
+        s.run();
+        assert_eq!(s.iterations(), 3);
+    }
+
+    #[test]
+    fn test_selector_error_propagate() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = par::Simulator::builder(&mut population);
+        builder.with_selector(Box::new(StochasticSelector::new(0)));
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Failure);
+        assert!(s.get().is_err());
+    }
+
+    #[test]
+    fn test_get_best() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = par::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_max_iters(3);
+        let mut s = builder.build();
+        s.run();
+        let best = s.get().unwrap().fitness();
+        assert_eq!(Some(best), s.population().iter().map(|x| x.fitness()).max());
+    }
+
+    /// Checks that crossover runs on a pool with the given number of threads.
+    #[derive(Debug)]
+    struct OnPool(usize);
+
+    impl CrossoverOperator<Test> for OnPool {
+        fn crossover(&self, a: &Test, b: &Test) -> Test {
+            assert_eq!(rayon::current_num_threads(), self.0);
+            a.crossover(b)
+        }
+    }
+
+    #[test]
+    fn test_thread_pool() {
+        let pool = Arc::new(ThreadPoolBuilder::new().num_threads(3).build().unwrap());
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = par::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_crossover(Box::new(OnPool(3)))
+            .with_thread_pool(pool)
+            .with_max_iters(3);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+    }
+
+    #[test]
+    fn test_threads() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = par::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_crossover(Box::new(OnPool(2)))
+            .with_threads(2)
+            .with_max_iters(3);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+    }
+
+    #[test]
+    fn test_seed() {
+        let run = |seed| {
+            let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+            {
+                let mut builder = par::Simulator::builder(&mut population);
+                builder
+                    .with_selector(Box::new(StochasticSelector::new(10)))
+                    .with_seed(seed)
+                    .with_max_iters(10);
+                builder.build().run();
+            }
+            population
+        };
+        assert_eq!(run(42), run(42));
+    }
+
+    static EVALUATIONS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Counts the number of times its fitness is computed.
+    #[derive(Clone, Debug)]
+    struct Counted(i64);
+
+    impl Phenotype<MyFitness> for Counted {
+        fn fitness(&self) -> MyFitness {
+            EVALUATIONS.fetch_add(1, Ordering::SeqCst);
+            MyFitness { f: self.0 }
+        }
+
+        fn crossover(&self, other: &Counted) -> Counted {
+            Counted(self.0.max(other.0))
+        }
+
+        fn mutate(&self) -> Counted {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_fitness_cached() {
+        let mut population: Vec<Counted> = (0..100).map(Counted).collect();
+        let mut builder = par::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(TournamentSelector::new_checked(10, 3).unwrap()))
+            .with_early_stop(MyFitness { f: 1 }, 50);
+        let mut s = builder.build();
+        s.checked_step();
+        let before = EVALUATIONS.load(Ordering::SeqCst);
+        s.checked_step();
+        // The selector and early stopping share the fitness of the population.
+        assert_eq!(EVALUATIONS.load(Ordering::SeqCst) - before, 100);
+    }
+}
@@ -0,0 +1,315 @@
+// file: parameterless.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains `Parameterless`, a runner that removes the need to choose a population size.
+//!
+//! Following the parameter-less GA of Harik and Lobo, populations of doubling sizes
+//! are run side by side. For every generation of a population, each smaller population
+//! runs `ratio` generations, so small populations get a large share of the budget.
+//! A population is discarded when a larger one has at least the same mean fitness,
+//! because the smaller one is then unlikely to contribute anything, or when its
+//! `Simulator` is done.
+
+use super::seq::{Simulator, SimulatorBuilder};
+use super::*;
+use error::Error;
+use pheno::{NumericFitness, Phenotype};
+use std::fmt;
+
+type Initializer<'s, T> = Box<dyn FnMut(usize) -> Vec<T> + 's>;
+type Configure<'s, T, F> = Box<dyn for<'b> FnMut(&mut SimulatorBuilder<'b, T, F>, usize) + 's>;
+
+/// Runs populations of increasing size until a generation budget is used up.
+pub struct Parameterless<'s, T, F>
+where
+    T: Phenotype<F>,
+    F: NumericFitness,
+{
+    initial_size: usize,
+    ratio: u64,
+    max_populations: usize,
+    max_generations: u64,
+    init: Initializer<'s, T>,
+    configure: Option<Configure<'s, T, F>>,
+}
+
+impl<'s, T, F> Parameterless<'s, T, F>
+where
+    T: Phenotype<F>,
+    F: NumericFitness,
+{
+    /// Create a new runner.
+    ///
+    /// `init` is called with a population size and should return a random
+    /// population of that size.
+    ///
+    /// By default, the first population has 16 individuals, the ratio is 4,
+    /// at most 8 populations are started and at most 1000 generations are run
+    /// in total.
+    pub fn new<I>(init: I) -> Parameterless<'s, T, F>
+    where
+        I: FnMut(usize) -> Vec<T> + 's,
+    {
+        Parameterless {
+            initial_size: 16,
+            ratio: 4,
+            max_populations: 8,
+            max_generations: 1000,
+            init: Box::new(init),
+            configure: None,
+        }
+    }
+
+    /// Set a closure that configures the `Simulator` of every population.
+    ///
+    /// `configure` is called with the builder and the size of the population,
+    /// so that e.g. the selector can be scaled with the population.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    pub fn with_configure<C>(&mut self, configure: C) -> &mut Self
+    where
+        C: for<'b> FnMut(&mut SimulatorBuilder<'b, T, F>, usize) + 's,
+    {
+        self.configure = Some(Box::new(configure));
+        self
+    }
+
+    /// Set the size of the first population. Every next population is twice as large.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    pub fn with_initial_size(&mut self, size: usize) -> &mut Self {
+        self.initial_size = size;
+        self
+    }
+
+    /// Set the number of generations a population runs for every generation
+    /// of the next larger population. Must be at least 2.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    pub fn with_ratio(&mut self, ratio: u64) -> &mut Self {
+        self.ratio = ratio;
+        self
+    }
+
+    /// Set the maximum number of populations that are started.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    pub fn with_max_populations(&mut self, n: usize) -> &mut Self {
+        self.max_populations = n;
+        self
+    }
+
+    /// Set the total number of generations, summed over all populations.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    pub fn with_max_generations(&mut self, n: u64) -> &mut Self {
+        self.max_generations = n;
+        self
+    }
+
+    /// Run populations until the generation budget is used up, or until every
+    /// population has been discarded and no new one may be started.
+    ///
+    /// If a `Simulator` fails, its error is returned.
+    pub fn run(&mut self) -> Result<ParameterlessSummary<T>, Error> {
+        if self.initial_size == 0 {
+            return Err(Error::InvalidConfig(String::from(
+                "The initial population size must be positive.",
+            )));
+        }
+        if self.ratio < 2 {
+            return Err(Error::InvalidConfig(format!(
+                "Invalid ratio: {}. Should be at least 2.",
+                self.ratio
+            )));
+        }
+        let mut slots: Vec<Vec<T>> = (0..self.max_populations).map(|_| Vec::new()).collect();
+        let mut free = slots.iter_mut();
+        let mut sims: Vec<Option<Simulator<T, F>>> = Vec::new();
+        let mut summary: Option<ParameterlessSummary<T>> = None;
+        let mut generations = 0;
+
+        'budget: while generations < self.max_generations {
+            // Step the smallest population, and a larger one every `ratio` generations.
+            let mut i = match sims.iter().position(Option::is_some) {
+                Some(i) => i,
+                None => sims.len(),
+            };
+            loop {
+                if i == sims.len() {
+                    let slot = match free.next() {
+                        Some(slot) => slot,
+                        None => break,
+                    };
+                    *slot = (self.init)(self.initial_size << i);
+                    let mut builder = Simulator::builder(slot);
+                    if let Some(ref mut configure) = self.configure {
+                        configure(&mut builder, self.initial_size << i);
+                    }
+                    sims.push(Some(builder.build()));
+                }
+                let done = {
+                    let sim = sims[i].as_mut().unwrap();
+                    match sim.checked_step() {
+                        StepResult::Success => false,
+                        StepResult::Done => true,
+                        StepResult::Failure => {
+                            return Err(sim
+                                .error()
+                                .cloned()
+                                .expect("a failed step records its error"));
+                        }
+                    }
+                };
+                if done {
+                    sims[i] = None;
+                    break;
+                }
+                generations += 1;
+                let sim = sims[i].as_ref().unwrap();
+                let best = sim.best().unwrap();
+                let improved = match summary {
+                    Some(ref s) => best.fitness() > s.best.fitness(),
+                    None => true,
+                };
+                if improved {
+                    summary = Some(ParameterlessSummary {
+                        best: best.clone(),
+                        population_size: self.initial_size << i,
+                        generations: 0,
+                        populations: 0,
+                    });
+                }
+                if generations >= self.max_generations {
+                    break 'budget;
+                }
+                if sim.iterations() % self.ratio != 0 {
+                    break;
+                }
+                i = match (i + 1..sims.len()).find(|&j| sims[j].is_some()) {
+                    Some(j) => j,
+                    None => sims.len(),
+                };
+            }
+            discard_dominated(&mut sims);
+            if sims.iter().all(Option::is_none) && sims.len() == self.max_populations {
+                break;
+            }
+        }
+
+        match summary {
+            Some(mut s) => {
+                s.generations = generations;
+                s.populations = sims.len();
+                Ok(s)
+            }
+            None => Err(Error::InvalidConfig(String::from("No population was run."))),
+        }
+    }
+}
+
+/// Discard every population whose mean fitness is not higher than that of a larger population.
+fn discard_dominated<T, F>(sims: &mut [Option<Simulator<T, F>>])
+where
+    T: Phenotype<F>,
+    F: NumericFitness,
+{
+    let means: Vec<Option<f64>> = sims
+        .iter()
+        .map(|sim| sim.as_ref().and_then(|s| s.mean_fitness()))
+        .collect();
+    for i in 0..sims.len() {
+        if let Some(mean) = means[i] {
+            if means[i + 1..].iter().any(|m| m.is_some_and(|m| m >= mean)) {
+                sims[i] = None;
+            }
+        }
+    }
+}
+
+impl<'s, T, F> fmt::Debug for Parameterless<'s, T, F>
+where
+    T: Phenotype<F>,
+    F: NumericFitness,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Parameterless")
+            .field("initial_size", &self.initial_size)
+            .field("ratio", &self.ratio)
+            .field("max_populations", &self.max_populations)
+            .field("max_generations", &self.max_generations)
+            .finish()
+    }
+}
+
+/// The outcome of a `Parameterless` run.
+#[derive(Clone, Debug)]
+pub struct ParameterlessSummary<T> {
+    /// The best individual found in any population.
+    pub best: T,
+    /// The size of the population in which `best` was found.
+    pub population_size: usize,
+    /// The total number of generations run, summed over all populations.
+    pub generations: u64,
+    /// The number of populations that were started.
+    pub populations: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Parameterless;
+    use sim::select::*;
+    use test::{MyFitness, Test};
+
+    #[test]
+    fn test_budget() {
+        let mut runner: Parameterless<Test, MyFitness> =
+            Parameterless::new(|n| (0..n as i64).map(|i| Test { f: i + 50 }).collect());
+        runner
+            .with_configure(|b, n| {
+                b.with_selector(Box::new(StochasticSelector::new(n / 4)))
+                    .with_max_iters(1000);
+            })
+            .with_max_generations(100);
+        let summary = runner.run().unwrap();
+        assert_eq!(summary.generations, 100);
+        // 4 generations of the first population for every one of the second, and so on.
+        assert!(summary.populations >= 3);
+    }
+
+    #[test]
+    fn test_all_populations_done() {
+        let mut runner: Parameterless<Test, MyFitness> =
+            Parameterless::new(|n| (0..n as i64).map(|i| Test { f: i }).collect());
+        runner
+            .with_configure(|b, n| {
+                b.with_selector(Box::new(StochasticSelector::new(n / 4)))
+                    .with_max_iters(2);
+            })
+            .with_max_populations(2);
+        let summary = runner.run().unwrap();
+        assert_eq!(summary.generations, 4);
+        assert_eq!(summary.populations, 2);
+    }
+
+    #[test]
+    fn test_invalid_ratio() {
+        let mut runner: Parameterless<Test, MyFitness> =
+            Parameterless::new(|n| (0..n as i64).map(|i| Test { f: i }).collect());
+        runner.with_ratio(1);
+        assert!(runner.run().is_err());
+    }
+}
@@ -0,0 +1,194 @@
+// file: pipeline.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains a `Pipeline`, which runs several `Simulator` stages one after another
+//! on the same population.
+//!
+//! Each stage is configured by a closure that receives a fresh `SimulatorBuilder`,
+//! so every stage can have its own selector and termination settings. The population
+//! left behind by one stage is the starting population of the next, which makes
+//! coarse-to-fine workflows straightforward.
+
+use super::seq::{Simulator, SimulatorBuilder};
+use super::*;
+use error::Error;
+use pheno::{Fitness, Phenotype};
+use std::fmt;
+
+type Stage<'s, T, F> = Box<dyn for<'b> FnMut(&mut SimulatorBuilder<'b, T, F>) + 's>;
+
+/// Runs a sequence of `Simulator` stages, passing the population along.
+pub struct Pipeline<'s, T, F>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    stages: Vec<Stage<'s, T, F>>,
+}
+
+impl<'s, T, F> Pipeline<'s, T, F>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    /// Create a new, empty `Pipeline`.
+    pub fn new() -> Pipeline<'s, T, F> {
+        Pipeline { stages: Vec::new() }
+    }
+
+    /// Add a stage to the end of the pipeline.
+    ///
+    /// `configure` is called with the builder of the stage's `Simulator`
+    /// right before the stage runs.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    pub fn with_stage<C>(&mut self, configure: C) -> &mut Self
+    where
+        C: for<'b> FnMut(&mut SimulatorBuilder<'b, T, F>) + 's,
+    {
+        self.stages.push(Box::new(configure));
+        self
+    }
+
+    /// Get the number of stages in the pipeline.
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Check whether the pipeline has no stages.
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Run every stage in order on `population`.
+    ///
+    /// If a stage fails, the remaining stages are not run and the error
+    /// of the failing `Simulator` is returned.
+    pub fn run(&mut self, population: &mut Vec<T>) -> Result<PipelineSummary, Error> {
+        let mut stages = Vec::with_capacity(self.stages.len());
+        for configure in &mut self.stages {
+            let mut builder = Simulator::builder(&mut *population);
+            configure(&mut builder);
+            let mut sim = builder.build();
+            if sim.run() == RunResult::Failure {
+                return Err(sim
+                    .error()
+                    .cloned()
+                    .expect("a failed step records its error"));
+            }
+            stages.push(StageSummary {
+                iterations: sim.iterations(),
+                time: sim.time(),
+            });
+        }
+        Ok(PipelineSummary { stages })
+    }
+}
+
+impl<'s, T, F> Default for Pipeline<'s, T, F>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    fn default() -> Self {
+        Pipeline::new()
+    }
+}
+
+impl<'s, T, F> fmt::Debug for Pipeline<'s, T, F>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("stages", &self.stages.len())
+            .finish()
+    }
+}
+
+/// The outcome of a single pipeline stage.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StageSummary {
+    /// The number of iterations the stage executed.
+    pub iterations: u64,
+    /// The number of nanoseconds the stage spent running, or `None` in case of an overflow.
+    pub time: Option<NanoSecond>,
+}
+
+/// The combined outcome of a `Pipeline` run.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PipelineSummary {
+    stages: Vec<StageSummary>,
+}
+
+impl PipelineSummary {
+    /// Get the summaries of the individual stages, in the order they ran.
+    pub fn stages(&self) -> &[StageSummary] {
+        &self.stages
+    }
+
+    /// Get the total number of iterations over all stages.
+    pub fn iterations(&self) -> u64 {
+        self.stages.iter().map(|s| s.iterations).sum()
+    }
+
+    /// Get the total number of nanoseconds spent running, or `None` in case of an overflow.
+    pub fn time(&self) -> Option<NanoSecond> {
+        let zero: NanoSecond = 0;
+        self.stages
+            .iter()
+            .try_fold(zero, |acc, s| s.time.and_then(|t| acc.checked_add(t)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pipeline;
+    use sim::select::*;
+    use test::{MyFitness, Test};
+
+    #[test]
+    fn test_stages_run_in_order() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut pipeline: Pipeline<Test, MyFitness> = Pipeline::new();
+        pipeline
+            .with_stage(|b| {
+                b.with_selector(Box::new(StochasticSelector::new(10)))
+                    .with_max_iters(3);
+            })
+            .with_stage(|b| {
+                b.with_selector(Box::new(UnstableMaximizeSelector::new(4)))
+                    .with_max_iters(2);
+            });
+        let summary = pipeline.run(&mut population).unwrap();
+        assert_eq!(summary.stages().len(), 2);
+        assert_eq!(summary.stages()[0].iterations, 3);
+        assert_eq!(summary.stages()[1].iterations, 2);
+        assert_eq!(summary.iterations(), 5);
+        assert_eq!(population.len(), 100);
+    }
+
+    #[test]
+    fn test_failing_stage_stops_pipeline() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut pipeline: Pipeline<Test, MyFitness> = Pipeline::new();
+        pipeline.with_stage(|b| {
+            b.with_selector(Box::new(StochasticSelector::new(0)));
+        });
+        assert!(pipeline.run(&mut population).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+// file: repair.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The repair module provides wrappers that keep the offspring of other operators
+//! feasible, by projecting them back onto the feasible region after every operation.
+//!
+//! `SimplexProjection` keeps genomes of real numbers on the probability simplex, i.e.
+//! all genes are non-negative and sum to one. This is needed for mixture weights and
+//! allocation problems, whose offspring would otherwise drift off the simplex.
+
+use super::crossover::CrossoverOperator;
+use super::mutation::MutationOperator;
+use pheno::Genome;
+use rand::Rng;
+
+/// Get the point on the probability simplex that is closest to `x`.
+///
+/// The result is non-negative and sums to one. An empty input yields an empty result.
+/// This uses the sort-based algorithm of Duchi et al. (2008).
+pub fn project_simplex(x: &[f64]) -> Vec<f64> {
+    if x.is_empty() {
+        return Vec::new();
+    }
+    let mut sorted = x.to_vec();
+    sorted.sort_by(|a, b| b.total_cmp(a));
+    let mut sum = 0.0;
+    let mut theta = 0.0;
+    for (j, &u) in sorted.iter().enumerate() {
+        sum += u;
+        let t = (sum - 1.0) / (j + 1) as f64;
+        if u - t > 0.0 {
+            theta = t;
+        }
+    }
+    x.iter().map(|&v| (v - theta).max(0.0)).collect()
+}
+
+/// Projects the offspring of another operator onto the probability simplex.
+///
+/// Wraps a `CrossoverOperator` or a `MutationOperator` for genomes with `f64` genes.
+#[derive(Clone, Copy, Debug)]
+pub struct SimplexProjection<O> {
+    op: O,
+}
+
+impl<O> SimplexProjection<O> {
+    /// Create and return a wrapper that projects the offspring of `op`.
+    pub fn new(op: O) -> SimplexProjection<O> {
+        SimplexProjection { op }
+    }
+}
+
+impl<T, O> CrossoverOperator<T> for SimplexProjection<O>
+where
+    T: Genome<Gene = f64>,
+    O: CrossoverOperator<T>,
+{
+    fn crossover(&self, a: &T, b: &T) -> T {
+        self.crossover_with_rng(a, b, &mut ::rand::thread_rng())
+    }
+
+    fn crossover_with_rng(&self, a: &T, b: &T, rng: &mut dyn Rng) -> T {
+        let child = self.op.crossover_with_rng(a, b, rng);
+        child.with_genes(project_simplex(child.genes()))
+    }
+}
+
+impl<T, O> MutationOperator<T> for SimplexProjection<O>
+where
+    T: Genome<Gene = f64>,
+    O: MutationOperator<T>,
+{
+    fn mutate(&self, x: &T) -> T {
+        self.mutate_with_rng(x, &mut ::rand::thread_rng())
+    }
+
+    fn mutate_with_rng(&self, x: &T, rng: &mut dyn Rng) -> T {
+        let child = self.op.mutate_with_rng(x, rng);
+        child.with_genes(project_simplex(child.genes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &[f64], b: &[f64]) {
+        assert_eq!(a.len(), b.len());
+        assert!(a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_project_on_simplex() {
+        assert_close(&project_simplex(&[0.2, 0.3, 0.5]), &[0.2, 0.3, 0.5]);
+    }
+
+    #[test]
+    fn test_project_off_simplex() {
+        assert_close(&project_simplex(&[1.0, 1.0]), &[0.5, 0.5]);
+        assert_close(&project_simplex(&[2.0, 0.0, -1.0]), &[1.0, 0.0, 0.0]);
+        assert_close(&project_simplex(&[0.5, 0.2, -0.5]), &[0.65, 0.35, 0.0]);
+    }
+
+    #[derive(Clone, Debug)]
+    struct Weights(Vec<f64>);
+
+    impl Genome for Weights {
+        type Gene = f64;
+
+        fn genes(&self) -> &[f64] {
+            &self.0
+        }
+
+        fn with_genes(&self, genes: Vec<f64>) -> Weights {
+            Weights(genes)
+        }
+    }
+
+    #[derive(Debug)]
+    struct Double;
+
+    impl MutationOperator<Weights> for Double {
+        fn mutate(&self, x: &Weights) -> Weights {
+            Weights(x.0.iter().map(|w| 2.0 * w).collect())
+        }
+    }
+
+    #[test]
+    fn test_wrapped_mutation() {
+        let op = SimplexProjection::new(Double);
+        let child = op.mutate(&Weights(vec![0.25, 0.75]));
+        assert_close(&child.0, &[0.0, 1.0]);
+    }
+}
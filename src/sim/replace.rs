@@ -0,0 +1,414 @@
+// file: replace.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The replacement module provides a trait that can be implemented to decide which
+//! individuals survive to the next iteration, which is also known as survivor selection.
+//! This module also provides the common replacement policies.
+//!
+//! A policy is configured on a `seq::SimulatorBuilder` using `with_replacement`.
+//! The population is ordered from oldest to youngest: policies keep the relative
+//! order of the survivors and append the children at the end.
+
+use pheno::{Fitness, Phenotype};
+use rand::seq::sample_indices;
+use rand::Rng;
+use std::fmt::Debug;
+
+/// A `ReplacementPolicy` makes room for the children of an iteration in the population.
+///
+/// Policies must be `Send` and `Sync`, so that boxed policies can be shared between threads.
+pub trait ReplacementPolicy<T, F>: Debug + Send + Sync
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    /// Remove individuals from `population` and add `children` to it.
+    ///
+    /// Survivors should keep their relative order, and the children should be
+    /// appended at the end.
+    fn replace(&self, population: &mut Vec<T>, children: Vec<T>);
+
+    /// Remove individuals from `population` and add `children` to it,
+    /// making every random decision with `rng`.
+    ///
+    /// Simulators that can be seeded call this function, so that runs are reproducible.
+    /// By default, `rng` is ignored and `replace` is called, so a policy that uses
+    /// its own random number generator should override this function.
+    fn replace_with_rng(&self, population: &mut Vec<T>, children: Vec<T>, rng: &mut dyn Rng) {
+        let _ = rng;
+        self.replace(population, children)
+    }
+
+    /// Remove individuals from `population` and add `children` to it, without removing
+    /// the individuals marked in `protected`, making every random decision with `rng`.
+    ///
+    /// Simulators call this function when elitism is enabled. The built-in policies
+    /// keep all survivors, protected or not, in their relative order. By default, the
+    /// protected individuals are set aside, `replace_with_rng` is called for the others,
+    /// and the protected individuals are put back in front of the survivors, so a
+    /// policy that depends on the order of the population should override this function.
+    fn replace_protected(
+        &self,
+        population: &mut Vec<T>,
+        children: Vec<T>,
+        protected: &[bool],
+        rng: &mut dyn Rng,
+    ) {
+        let (mut kept, mut rest): (Vec<T>, Vec<T>) = (Vec::new(), Vec::new());
+        for (x, &p) in population.drain(..).zip(protected) {
+            if p {
+                kept.push(x);
+            } else {
+                rest.push(x);
+            }
+        }
+        self.replace_with_rng(&mut rest, children, rng);
+        kept.append(&mut rest);
+        *population = kept;
+    }
+}
+
+/// Get the indices of the individuals that are not protected, in order.
+fn unprotected(protected: &[bool]) -> Vec<usize> {
+    (0..protected.len()).filter(|&i| !protected[i]).collect()
+}
+
+/// Keep only the individuals with the given indices, in order.
+fn remove_indices<T>(population: &mut Vec<T>, indices: &[usize]) {
+    let mut removed = vec![false; population.len()];
+    for &i in indices {
+        removed[i] = true;
+    }
+    let mut i = 0;
+    population.retain(|_| {
+        i += 1;
+        !removed[i - 1]
+    });
+}
+
+/// Replaces random individuals, which are spread evenly over the population
+/// using stochastic universal sampling.
+///
+/// This is the default policy.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReplaceRandom;
+
+impl ReplaceRandom {
+    /// Create and return a random replacement policy.
+    pub fn new() -> ReplaceRandom {
+        ReplaceRandom
+    }
+}
+
+impl<T, F> ReplacementPolicy<T, F> for ReplaceRandom
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    fn replace(&self, population: &mut Vec<T>, children: Vec<T>) {
+        self.replace_with_rng(population, children, &mut ::rand::thread_rng())
+    }
+
+    fn replace_with_rng(
+        &self,
+        population: &mut Vec<T>,
+        mut children: Vec<T>,
+        mut rng: &mut dyn Rng,
+    ) {
+        kill_off(population, children.len(), &mut rng);
+        population.append(&mut children);
+    }
+
+    fn replace_protected(
+        &self,
+        population: &mut Vec<T>,
+        mut children: Vec<T>,
+        protected: &[bool],
+        mut rng: &mut dyn Rng,
+    ) {
+        let candidates = unprotected(protected);
+        let killed = spread(candidates.len(), children.len(), &mut rng);
+        let removed: Vec<usize> = (0..candidates.len())
+            .filter(|&k| killed[k])
+            .map(|k| candidates[k])
+            .collect();
+        remove_indices(population, &removed);
+        population.append(&mut children);
+    }
+}
+
+/// Replaces the least fit individuals.
+///
+/// This policy has a strong selection pressure: the fittest individual always survives.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReplaceWorst;
+
+impl ReplaceWorst {
+    /// Create and return a replace-worst policy.
+    pub fn new() -> ReplaceWorst {
+        ReplaceWorst
+    }
+}
+
+impl<T, F> ReplacementPolicy<T, F> for ReplaceWorst
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    fn replace(&self, population: &mut Vec<T>, children: Vec<T>) {
+        let protected = vec![false; population.len()];
+        self.replace_protected(population, children, &protected, &mut ::rand::thread_rng())
+    }
+
+    fn replace_protected(
+        &self,
+        population: &mut Vec<T>,
+        mut children: Vec<T>,
+        protected: &[bool],
+        _: &mut dyn Rng,
+    ) {
+        let mut order: Vec<(F, usize)> = unprotected(protected)
+            .into_iter()
+            .map(|i| (population[i].fitness(), i))
+            .collect();
+        order.sort();
+        let worst: Vec<usize> = order.iter().take(children.len()).map(|&(_, i)| i).collect();
+        remove_indices(population, &worst);
+        population.append(&mut children);
+    }
+}
+
+/// Replaces the individuals that have been in the population the longest.
+///
+/// Every individual survives for the same number of iterations, regardless of its fitness.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReplaceOldest;
+
+impl ReplaceOldest {
+    /// Create and return a replace-oldest policy.
+    pub fn new() -> ReplaceOldest {
+        ReplaceOldest
+    }
+}
+
+impl<T, F> ReplacementPolicy<T, F> for ReplaceOldest
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    fn replace(&self, population: &mut Vec<T>, mut children: Vec<T>) {
+        let count = children.len().min(population.len());
+        population.drain(..count);
+        population.append(&mut children);
+    }
+
+    fn replace_protected(
+        &self,
+        population: &mut Vec<T>,
+        mut children: Vec<T>,
+        protected: &[bool],
+        _: &mut dyn Rng,
+    ) {
+        let oldest: Vec<usize> = unprotected(protected)
+            .into_iter()
+            .take(children.len())
+            .collect();
+        remove_indices(population, &oldest);
+        population.append(&mut children);
+    }
+}
+
+/// Replaces the entire population by the children.
+///
+/// If there are fewer children than individuals, random individuals of the old
+/// population survive to keep the size of the population constant.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Generational;
+
+impl Generational {
+    /// Create and return a generational replacement policy.
+    pub fn new() -> Generational {
+        Generational
+    }
+}
+
+impl<T, F> ReplacementPolicy<T, F> for Generational
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    fn replace(&self, population: &mut Vec<T>, children: Vec<T>) {
+        self.replace_with_rng(population, children, &mut ::rand::thread_rng())
+    }
+
+    fn replace_with_rng(&self, population: &mut Vec<T>, children: Vec<T>, rng: &mut dyn Rng) {
+        let protected = vec![false; population.len()];
+        self.replace_protected(population, children, &protected, rng)
+    }
+
+    fn replace_protected(
+        &self,
+        population: &mut Vec<T>,
+        mut children: Vec<T>,
+        protected: &[bool],
+        mut rng: &mut dyn Rng,
+    ) {
+        let candidates = unprotected(protected);
+        let survivors = population
+            .len()
+            .saturating_sub(children.len())
+            .saturating_sub(population.len() - candidates.len());
+        let mut kept = sample_indices(&mut rng, candidates.len(), survivors);
+        kept.sort();
+        let removed: Vec<usize> = (0..candidates.len())
+            .filter(|k| kept.binary_search(k).is_err())
+            .map(|k| candidates[k])
+            .collect();
+        remove_indices(population, &removed);
+        population.append(&mut children);
+    }
+}
+
+/// Kill off `count` individuals using stochastic universal sampling.
+///
+/// The survivors keep their relative order. If `count` is at least the size of the
+/// population, every individual is killed off.
+pub(super) fn kill_off<T, R>(population: &mut Vec<T>, count: usize, rng: &mut R)
+where
+    R: Rng,
+{
+    let killed = spread(population.len(), count, rng);
+    let mut i = 0;
+    population.retain(|_| {
+        i += 1;
+        !killed[i - 1]
+    });
+}
+
+/// Mark `count` of `len` positions using stochastic universal sampling, so that the
+/// marked positions are spread evenly. If `count` is at least `len`, every position
+/// is marked.
+pub(super) fn spread<R>(len: usize, count: usize, rng: &mut R) -> Vec<bool>
+where
+    R: Rng,
+{
+    if count >= len {
+        return vec![true; len];
+    }
+    let mut marked = vec![false; len];
+    if count == 0 {
+        return marked;
+    }
+    let ratio = len / count;
+    let start = rng.gen_range::<usize>(0, len);
+    for k in 0..count {
+        marked[(start + k * ratio) % len] = true;
+    }
+    marked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test::Test;
+
+    fn initial() -> Vec<Test> {
+        vec![5, 1, 4, 2, 3]
+            .into_iter()
+            .map(|f| Test { f })
+            .collect()
+    }
+
+    fn children() -> Vec<Test> {
+        vec![Test { f: 10 }, Test { f: 11 }]
+    }
+
+    fn values(population: &[Test]) -> Vec<i64> {
+        population.iter().map(|x| x.f).collect()
+    }
+
+    #[test]
+    fn test_replace_random() {
+        for _ in 0..20 {
+            let mut population = initial();
+            ReplaceRandom::new().replace(&mut population, children());
+            assert_eq!(population.len(), 5);
+            assert_eq!(values(&population[3..]), vec![10, 11]);
+        }
+    }
+
+    #[test]
+    fn test_replace_worst() {
+        let mut population = initial();
+        ReplaceWorst::new().replace(&mut population, children());
+        assert_eq!(values(&population), vec![5, 4, 3, 10, 11]);
+    }
+
+    #[test]
+    fn test_replace_oldest() {
+        let mut population = initial();
+        ReplaceOldest::new().replace(&mut population, children());
+        assert_eq!(values(&population), vec![4, 2, 3, 10, 11]);
+    }
+
+    #[test]
+    fn test_protected_keep_order() {
+        let mut rng = ::rand::thread_rng();
+        let protected = [true, false, false, false, false];
+        let mut population = initial();
+        ReplaceOldest::new().replace_protected(&mut population, children(), &protected, &mut rng);
+        assert_eq!(values(&population), vec![5, 2, 3, 10, 11]);
+
+        let protected = [false, true, false, false, false];
+        let mut population = initial();
+        ReplaceWorst::new().replace_protected(&mut population, children(), &protected, &mut rng);
+        assert_eq!(values(&population), vec![5, 1, 4, 10, 11]);
+
+        let protected = [true, false, true, false, true];
+        for _ in 0..20 {
+            let mut population = initial();
+            ReplaceRandom::new().replace_protected(
+                &mut population,
+                children(),
+                &protected,
+                &mut rng,
+            );
+            assert_eq!(values(&population), vec![5, 4, 3, 10, 11]);
+
+            let mut population = initial();
+            Generational::new().replace_protected(
+                &mut population,
+                children(),
+                &protected,
+                &mut rng,
+            );
+            assert_eq!(values(&population), vec![5, 4, 3, 10, 11]);
+        }
+    }
+
+    #[test]
+    fn test_generational() {
+        let mut population = initial();
+        Generational::new().replace(&mut population, (10..15).map(|f| Test { f }).collect());
+        assert_eq!(values(&population), vec![10, 11, 12, 13, 14]);
+
+        let mut population = initial();
+        Generational::new().replace(&mut population, children());
+        assert_eq!(population.len(), 5);
+        assert_eq!(values(&population[3..]), vec![10, 11]);
+        assert!(population[..3].iter().all(|x| x.f < 10));
+    }
+}
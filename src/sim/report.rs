@@ -0,0 +1,173 @@
+// file: report.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains `RunReport`, a summary of a finished run for experiment bookkeeping.
+//!
+//! A report is created with `seq::Simulator::report`. It can be printed in a
+//! human-readable format with `Display`, and, with the `serde` feature enabled,
+//! serialized to JSON with `RunReport::to_json`.
+
+use super::NanoSecond;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde_json;
+use std::fmt;
+
+/// The reason a simulation stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum StopReason {
+    /// The maximum number of iterations was reached.
+    MaxIterations,
+    /// The maximum number of fitness evaluations was reached.
+    MaxEvaluations,
+    /// The best fitness did not improve enough for a number of iterations.
+    EarlyStop,
+    /// The genotype entropy of the population dropped below its threshold.
+    Converged,
+    /// A `Termination` condition was met.
+    Termination,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            StopReason::MaxIterations => "maximum number of iterations reached",
+            StopReason::MaxEvaluations => "maximum number of evaluations reached",
+            StopReason::EarlyStop => "no significant improvement (early stopping)",
+            StopReason::Converged => "population converged",
+            StopReason::Termination => "termination condition met",
+        })
+    }
+}
+
+/// A summary of a run: its settings, why and when it stopped, and its result.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RunReport<T, F> {
+    /// The settings of the simulator, as pairs of a name and a description.
+    pub settings: Vec<(String, String)>,
+    /// Why the run stopped, or `None` if it has not stopped (yet).
+    pub stop_reason: Option<StopReason>,
+    /// The error message, if the run failed.
+    pub error: Option<String>,
+    /// The number of iterations executed.
+    pub iterations: u64,
+    /// The number of fitness evaluations made.
+    pub evaluations: u64,
+    /// The total duration of all steps in nanoseconds, or `None` in case of an overflow.
+    pub duration: Option<NanoSecond>,
+    /// The best fitness of every generation, if the history was recorded.
+    pub history: Vec<F>,
+    /// The best individual found, if any.
+    pub best: Option<T>,
+    /// The fitness of `best`.
+    pub best_fitness: Option<F>,
+}
+
+#[cfg(feature = "serde")]
+impl<T, F> RunReport<T, F>
+where
+    T: Serialize,
+    F: Serialize,
+{
+    /// Serialize this report to JSON.
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+}
+
+impl<T, F> fmt::Display for RunReport<T, F>
+where
+    T: fmt::Debug,
+    F: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Run report")?;
+        match (&self.error, &self.stop_reason) {
+            (Some(e), _) => writeln!(f, "  failed: {}", e)?,
+            (None, Some(reason)) => writeln!(f, "  stopped: {}", reason)?,
+            (None, None) => writeln!(f, "  not finished")?,
+        }
+        writeln!(f, "  iterations: {}", self.iterations)?;
+        writeln!(f, "  evaluations: {}", self.evaluations)?;
+        match self.duration {
+            Some(d) => writeln!(f, "  duration: {} ns", d)?,
+            None => writeln!(f, "  duration: overflow")?,
+        }
+        if let (Some(best), Some(fitness)) = (&self.best, &self.best_fitness) {
+            writeln!(f, "  best: {:?} (fitness {:?})", best, fitness)?;
+        }
+        writeln!(f, "Settings")?;
+        for (name, value) in &self.settings {
+            writeln!(f, "  {}: {}", name, value)?;
+        }
+        if !self.history.is_empty() {
+            writeln!(f, "Best fitness per generation")?;
+            for (i, fitness) in self.history.iter().enumerate() {
+                writeln!(f, "  {}: {:?}", i + 1, fitness)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test::{MyFitness, Test};
+
+    fn report() -> RunReport<Test, MyFitness> {
+        RunReport {
+            settings: vec![("elitism".to_string(), "2".to_string())],
+            stop_reason: Some(StopReason::Converged),
+            error: None,
+            iterations: 3,
+            evaluations: 40,
+            duration: Some(1000),
+            history: vec![MyFitness { f: 1 }, MyFitness { f: 2 }],
+            best: Some(Test { f: 2 }),
+            best_fitness: Some(MyFitness { f: 2 }),
+        }
+    }
+
+    #[test]
+    fn test_text() {
+        let text = report().to_string();
+        assert!(text.contains("stopped: population converged"));
+        assert!(text.contains("evaluations: 40"));
+        assert!(text.contains("elitism: 2"));
+        assert!(text.contains("best: Test { f: 2 }"));
+    }
+
+    #[test]
+    fn test_text_error() {
+        let mut report = report();
+        report.error = Some("oops".to_string());
+        assert!(report.to_string().contains("failed: oops"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json() {
+        let json = report().to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["stop_reason"], "Converged");
+        assert_eq!(value["iterations"], 3);
+        assert_eq!(value["best"]["f"], 2);
+    }
+}
@@ -0,0 +1,162 @@
+// file: boltzmann.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::roulette::{check_count, spin};
+use super::*;
+use pheno::{NumericFitness, Phenotype};
+
+/// Selects phenotypes with a probability proportional to `exp(fitness / temperature)`.
+///
+/// Commonly known as *Boltzmann Selection*. The temperature is lowered every generation,
+/// starting at `initial_temperature` and multiplying by `cooling` each generation, but
+/// never below `min_temperature`. A high temperature makes selection almost uniform,
+/// a low temperature makes it almost greedy, so the selection pressure increases as the
+/// simulation progresses.
+#[derive(Clone, Copy, Debug)]
+pub struct BoltzmannSelector {
+    count: usize,
+    initial_temperature: f64,
+    cooling: f64,
+    min_temperature: f64,
+}
+
+impl BoltzmannSelector {
+    /// Create and return a Boltzmann selector.
+    ///
+    /// In total, `count` parents are selected. A phenotype can be selected more than once.
+    ///
+    /// * `count`: must be larger than zero and a multiple of 2.
+    /// * `initial_temperature`: the temperature in the first generation, must be positive.
+    /// * `cooling`: the factor by which the temperature is multiplied every generation,
+    ///   must be larger than zero and at most one.
+    ///
+    /// The minimum temperature defaults to `0.01`.
+    pub fn new(count: usize, initial_temperature: f64, cooling: f64) -> BoltzmannSelector {
+        BoltzmannSelector {
+            count,
+            initial_temperature,
+            cooling,
+            min_temperature: 0.01,
+        }
+    }
+
+    /// Set the temperature below which the selector does not cool down any further.
+    ///
+    /// * `min_temperature`: must be positive.
+    pub fn with_min_temperature(mut self, min_temperature: f64) -> BoltzmannSelector {
+        self.min_temperature = min_temperature;
+        self
+    }
+
+    /// Get the temperature in generation `generation`.
+    pub fn temperature(&self, generation: u64) -> f64 {
+        let exponent = generation.min(i32::MAX as u64) as i32;
+        (self.initial_temperature * self.cooling.powi(exponent)).max(self.min_temperature)
+    }
+}
+
+impl<T, F> Selector<T, F> for BoltzmannSelector
+where
+    T: Phenotype<F>,
+    F: NumericFitness,
+{
+    fn select_from<'a>(
+        &self,
+        mut context: SelectionContext<'a, '_, T, F>,
+    ) -> Result<Parents<&'a T>, Error> {
+        let population = context.population();
+        check_count(self.count, population.len())?;
+        if !(self.initial_temperature > 0.0 && self.min_temperature > 0.0) {
+            return Err(Error::InvalidSelectorParams(String::from(
+                "Invalid temperature: the initial and minimum temperature should be positive.",
+            )));
+        }
+        if !(self.cooling > 0.0 && self.cooling <= 1.0) {
+            return Err(Error::InvalidSelectorParams(format!(
+                "Invalid cooling factor: {}. Should be larger than zero and at most one.",
+                self.cooling
+            )));
+        }
+        let temperature = self.temperature(context.generation());
+        let fitness: Vec<f64> = context.fitness().iter().map(|f| f.to_f64()).collect();
+        // Subtracting the maximum keeps the exponentials from overflowing.
+        let max = fitness.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let weights: Vec<f64> = fitness
+            .iter()
+            .map(|f| ((f - max) / temperature).exp())
+            .collect();
+        Ok(spin(population, &weights, self.count, context.rng()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pheno::Phenotype;
+    use sim::select::*;
+    use test::{MyFitness, Test};
+
+    fn select_at<'a>(
+        selector: &BoltzmannSelector,
+        population: &'a [Test],
+        generation: u64,
+    ) -> Parents<&'a Test> {
+        let fitness: Vec<MyFitness> = population.iter().map(|x| x.fitness()).collect();
+        let mut rng = ::rand::thread_rng();
+        let context = SelectionContext::new(population, &fitness, generation, &mut rng);
+        selector.select_from(context).unwrap()
+    }
+
+    fn count_best(parents: &Parents<&Test>) -> usize {
+        parents
+            .iter()
+            .flat_map(|&(a, b)| vec![a, b])
+            .filter(|x| x.f == 9)
+            .count()
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let population: Vec<Test> = (0..10).map(|i| Test { f: i }).collect();
+        assert!(BoltzmannSelector::new(3, 1.0, 0.9)
+            .select(&population)
+            .is_err());
+        assert!(BoltzmannSelector::new(4, 0.0, 0.9)
+            .select(&population)
+            .is_err());
+        assert!(BoltzmannSelector::new(4, 1.0, 1.5)
+            .select(&population)
+            .is_err());
+    }
+
+    #[test]
+    fn test_temperature_schedule() {
+        let selector = BoltzmannSelector::new(4, 100.0, 0.5).with_min_temperature(1.0);
+        assert_eq!(selector.temperature(0), 100.0);
+        assert_eq!(selector.temperature(1), 50.0);
+        assert_eq!(selector.temperature(100), 1.0);
+    }
+
+    #[test]
+    fn test_pressure_increases() {
+        let population: Vec<Test> = (0..10).map(|i| Test { f: i }).collect();
+        let selector = BoltzmannSelector::new(1000, 1000.0, 0.5);
+        let early = count_best(&select_at(&selector, &population, 0));
+        let late = count_best(&select_at(&selector, &population, 20));
+        // Nearly uniform at first, nearly greedy later on.
+        assert!(early < 200);
+        assert!(late > 900);
+    }
+}
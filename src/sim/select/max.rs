@@ -47,21 +47,25 @@ where
     T: Phenotype<F>,
     F: Fitness,
 {
-    fn select<'a>(&self, population: &'a [T]) -> Result<Parents<&'a T>, String> {
+    fn select_from<'a>(
+        &self,
+        context: SelectionContext<'a, '_, T, F>,
+    ) -> Result<Parents<&'a T>, Error> {
+        let population = context.population();
+        let fitness = context.fitness();
         if self.count == 0 || self.count % 2 != 0 || self.count * 2 >= population.len() {
-            return Err(format!(
+            return Err(Error::InvalidSelectorParams(format!(
                 "Invalid parameter `count`: {}. Should be larger than zero, a \
                  multiple of two and less than half the population size.",
                 self.count
-            ));
+            )));
         }
-
-        let mut borrowed: Vec<&T> = population.iter().collect();
-        borrowed.sort_by(|x, y| y.fitness().cmp(&x.fitness()));
+        let mut order: Vec<usize> = (0..population.len()).collect();
+        order.sort_by(|&x, &y| fitness[y].cmp(&fitness[x]));
         let mut index = 0;
         let mut result: Parents<&T> = Vec::new();
         while index < self.count {
-            result.push((borrowed[index], borrowed[index + 1]));
+            result.push((&population[order[index]], &population[order[index + 1]]));
             index += 2;
         }
         Ok(result)
@@ -16,7 +16,6 @@
 
 use super::*;
 use pheno::{Fitness, Phenotype};
-use rayon::prelude::*;
 
 /// Selects best performing phenotypes from the population.
 #[derive(Clone, Copy, Debug)]
@@ -25,7 +24,7 @@ pub struct UnstableMaximizeSelector {
 }
 
 impl UnstableMaximizeSelector {
-    /// Create and return a maximizing selector with unstable parallel sorting.
+    /// Create and return a maximizing selector with unstable sorting.
     ///
     /// Such a selector selects only the `count` best performing phenotypes
     /// as parents.
@@ -34,33 +33,48 @@ impl UnstableMaximizeSelector {
     pub fn new(count: usize) -> UnstableMaximizeSelector {
         UnstableMaximizeSelector { count }
     }
+
+    /// Check the `count` parameter against the size of the population.
+    fn check_count(&self, population: usize) -> Result<(), Error> {
+        if self.count == 0 || self.count % 2 != 0 || self.count * 2 >= population {
+            return Err(Error::InvalidSelectorParams(format!(
+                "Invalid parameter `count`: {}. Should be larger than zero, a \
+                 multiple of two and less than half the population size.",
+                self.count
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Pair up the first `count` phenotypes of `order`.
+fn pairs<'a, T>(population: &'a [T], order: &[usize], count: usize) -> Parents<&'a T> {
+    let mut index = 0;
+    let mut result: Parents<&T> = Vec::new();
+    while index < count {
+        result.push((&population[order[index]], &population[order[index + 1]]));
+        index += 2;
+    }
+    result
 }
 
 impl<T, F> Selector<T, F> for UnstableMaximizeSelector
 where
     T: Phenotype<F>,
     F: Fitness,
-    T: Send,
-    T: Sync,
 {
-    fn select<'a>(&self, population: &'a [T]) -> Result<Parents<&'a T>, String> {
-        if self.count == 0 || self.count % 2 != 0 || self.count * 2 >= population.len() {
-            return Err(format!(
-                "Invalid parameter `count`: {}. Should be larger than zero, a \
-                 multiple of two and less than half the population size.",
-                self.count
-            ));
-        }
-
-        let mut borrowed: Vec<&T> = population.iter().collect();
-        borrowed.par_sort_unstable_by(|x, y| y.fitness().cmp(&x.fitness()));
-        let mut index = 0;
-        let mut result: Parents<&T> = Vec::new();
-        while index < self.count {
-            result.push((borrowed[index], borrowed[index + 1]));
-            index += 2;
-        }
-        Ok(result)
+    fn select_from<'a>(
+        &self,
+        context: SelectionContext<'a, '_, T, F>,
+    ) -> Result<Parents<&'a T>, Error> {
+        let population = context.population();
+        let fitness = context.fitness();
+        self.check_count(population.len())?;
+        // Ties are broken by position in the population, so that the result is
+        // deterministic despite the unstable sort.
+        let mut order: Vec<usize> = (0..population.len()).collect();
+        order.sort_unstable_by(|&x, &y| fitness[y].cmp(&fitness[x]).then_with(|| x.cmp(&y)));
+        Ok(pairs(population, &order, self.count))
     }
 }
 
@@ -68,7 +82,19 @@ where
 mod tests {
     use pheno::*;
     use sim::select::*;
-    use test::Test;
+    use test::{MyFitness, Test};
+
+    #[test]
+    fn test_select_from() {
+        let selector = UnstableMaximizeSelector::new(2);
+        let population: Vec<Test> = (0..10).map(|i| Test { f: i }).collect();
+        // The given fitness is used instead of the fitness of the phenotypes.
+        let fitness: Vec<MyFitness> = (0..10).map(|i| MyFitness { f: 9 - i }).collect();
+        let mut rng = ::rand::thread_rng();
+        let context = SelectionContext::new(&population, &fitness, 0, &mut rng);
+        let parents = selector.select_from(context).unwrap();
+        assert_eq!((parents[0].0.f, parents[0].1.f), (0, 1));
+    }
 
     #[test]
     fn test_count_zero() {
@@ -106,6 +132,15 @@ mod tests {
         assert_eq!(selector.select(&population).unwrap()[0].0.fitness().f, 99);
     }
 
+    #[test]
+    fn test_ties_by_index() {
+        let selector = UnstableMaximizeSelector::new(2);
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i % 2 }).collect();
+        let parents = selector.select(&population).unwrap()[0];
+        assert!(::std::ptr::eq(parents.0, &population[1]));
+        assert!(::std::ptr::eq(parents.1, &population[3]));
+    }
+
     #[test]
     fn test_contains_best() {
         let selector = UnstableMaximizeSelector::new(2);
@@ -20,35 +20,160 @@
 //!
 //! Each of the selection algorithms provided has a parameter `count`, which indicates the
 //! number of selected parents.
+//!
+//! Selectors that need to change their state between generations implement
+//! `StatefulSelector` instead.
 
+mod boltzmann;
 mod max;
 mod max_unstable;
+mod roulette;
 mod stochastic;
 mod tournament;
 
+use error::Error;
 use pheno::{Fitness, Phenotype};
+use rand::Rng;
+use std::fmt;
 use std::fmt::Debug;
 
+pub use self::boltzmann::BoltzmannSelector;
 #[allow(deprecated)]
 pub use self::max::MaximizeSelector;
 pub use self::max_unstable::UnstableMaximizeSelector;
+pub use self::roulette::{RankSelector, RouletteSelector};
 pub use self::stochastic::StochasticSelector;
 pub use self::tournament::TournamentSelector;
 
 /// `Parents` come in a `Vec` of two `T`'s.
 pub type Parents<T> = Vec<(T, T)>;
 
+/// Everything a selector can base its choice of parents on.
+///
+/// Simulators create a `SelectionContext` with the fitness values they have already
+/// computed, the number of iterations executed so far and their random number generator.
+pub struct SelectionContext<'a, 'b, T: 'a, F: 'b> {
+    population: &'a [T],
+    fitness: &'b [F],
+    generation: u64,
+    rng: &'b mut dyn Rng,
+}
+
+impl<'a, 'b, T, F> SelectionContext<'a, 'b, T, F> {
+    /// Create a context to select parents from `population` in generation `generation`,
+    /// given the `fitness` of every individual, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fitness` does not have the same length as `population`.
+    pub fn new(
+        population: &'a [T],
+        fitness: &'b [F],
+        generation: u64,
+        rng: &'b mut dyn Rng,
+    ) -> SelectionContext<'a, 'b, T, F> {
+        assert_eq!(population.len(), fitness.len());
+        SelectionContext {
+            population,
+            fitness,
+            generation,
+            rng,
+        }
+    }
+
+    /// Get the population to select parents from.
+    pub fn population(&self) -> &'a [T] {
+        self.population
+    }
+
+    /// Get the fitness of every individual in the population, in order.
+    pub fn fitness(&self) -> &'b [F] {
+        self.fitness
+    }
+
+    /// Get the number of iterations executed so far, so that adaptive selectors can
+    /// change their behaviour over time.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Get the random number generator to make every random decision with.
+    ///
+    /// Drawing every random number from it makes seeded runs reproducible.
+    pub fn rng(&mut self) -> &mut &'b mut dyn Rng {
+        &mut self.rng
+    }
+}
+
+impl<'a, 'b, T, F> Debug for SelectionContext<'a, 'b, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SelectionContext")
+            .field("population", &self.population.len())
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
 /// A `Selector` can select `Parents` for a new iteration of a `Simulation`.
-pub trait Selector<T, F>: Debug
+///
+/// Selectors must be `Send` and `Sync`, so that boxed selectors can be shared
+/// between threads.
+pub trait Selector<T, F>: Debug + Send + Sync
 where
     T: Phenotype<F>,
     F: Fitness,
 {
-    /// Select elements from a `population` for breeding.
+    /// Select elements from the population of `context` for breeding.
     ///
-    /// If invalid parameters are supplied or the algorithm fails, this function returns an
-    /// `Err(String)`, containing a message indicating the error.
+    /// If invalid parameters are supplied, this function returns an
+    /// `Err(Error::InvalidSelectorParams)`. If the algorithm fails for another reason,
+    /// it returns an `Err(Error::SelectorFailure)`.
     ///
     /// Otherwise it contains a vector of parent pairs wrapped in `Ok`.
-    fn select<'a>(&self, population: &'a [T]) -> Result<Parents<&'a T>, String>;
+    fn select_from<'a>(
+        &self,
+        context: SelectionContext<'a, '_, T, F>,
+    ) -> Result<Parents<&'a T>, Error>;
+
+    /// Select elements from a `population` for breeding.
+    ///
+    /// This evaluates every individual and uses the thread-local random number
+    /// generator in generation zero. Simulators call `select_from` instead.
+    fn select<'a>(&self, population: &'a [T]) -> Result<Parents<&'a T>, Error> {
+        let fitness: Vec<F> = population.iter().map(|x| x.fitness()).collect();
+        let mut rng = ::rand::thread_rng();
+        self.select_from(SelectionContext::new(population, &fitness, 0, &mut rng))
+    }
+}
+
+/// A selector that can change its state every time it selects parents.
+///
+/// This allows selectors with a temperature schedule, an adaptive selection pressure
+/// or their own random number generator. A `StatefulSelector` is configured on a
+/// `seq::SimulatorBuilder` with `with_stateful_selector`.
+pub trait StatefulSelector<T, F>: Debug + Send
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    /// Select elements from the population of `context` for breeding.
+    ///
+    /// See `Selector::select_from`.
+    fn select_mut<'a>(
+        &mut self,
+        context: SelectionContext<'a, '_, T, F>,
+    ) -> Result<Parents<&'a T>, Error>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Selector;
+    use test::{MyFitness, Test};
+
+    fn assert_send_sync<S: Send + Sync + ?Sized>() {}
+
+    #[test]
+    fn test_boxed_selector_send_sync() {
+        assert_send_sync::<Box<dyn Selector<Test, MyFitness>>>();
+    }
 }
@@ -0,0 +1,229 @@
+// file: roulette.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use pheno::{Fitness, NumericFitness, Phenotype};
+use rand::Rng;
+
+/// Selects phenotypes with a probability proportional to their fitness.
+///
+/// Commonly known as *Roulette Wheel Selection*. Fitness values are shifted so
+/// that the lowest fitness in the population has weight zero, which makes the
+/// selector work with negative fitness values as well. If all phenotypes have
+/// the same fitness, they are selected uniformly.
+///
+/// This selector requires a `NumericFitness`. For other fitness types, use the
+/// `RankSelector`, which spins the wheel based on rank instead.
+#[derive(Clone, Copy, Debug)]
+pub struct RouletteSelector {
+    count: usize,
+}
+
+impl RouletteSelector {
+    /// Create and return a roulette selector.
+    ///
+    /// In total, `count` parents are selected. A phenotype can be selected more than once.
+    ///
+    /// * `count`: must be larger than zero and a multiple of 2.
+    pub fn new(count: usize) -> RouletteSelector {
+        RouletteSelector { count }
+    }
+}
+
+impl<T, F> Selector<T, F> for RouletteSelector
+where
+    T: Phenotype<F>,
+    F: NumericFitness,
+{
+    fn select_from<'a>(
+        &self,
+        mut context: SelectionContext<'a, '_, T, F>,
+    ) -> Result<Parents<&'a T>, Error> {
+        let population = context.population();
+        check_count(self.count, population.len())?;
+        let fitness: Vec<f64> = context.fitness().iter().map(|f| f.to_f64()).collect();
+        let min = fitness.iter().cloned().fold(f64::INFINITY, f64::min);
+        let weights: Vec<f64> = fitness.iter().map(|f| f - min).collect();
+        Ok(spin(population, &weights, self.count, context.rng()))
+    }
+}
+
+/// Selects phenotypes with a probability proportional to their rank.
+///
+/// The phenotype with the lowest fitness has rank 1, the one with the highest fitness
+/// has a rank equal to the population size. Unlike the `RouletteSelector`, this works
+/// with any `Fitness` type, and the selection pressure does not depend on the scale of
+/// the fitness values.
+#[derive(Clone, Copy, Debug)]
+pub struct RankSelector {
+    count: usize,
+}
+
+impl RankSelector {
+    /// Create and return a rank selector.
+    ///
+    /// In total, `count` parents are selected. A phenotype can be selected more than once.
+    ///
+    /// * `count`: must be larger than zero and a multiple of 2.
+    pub fn new(count: usize) -> RankSelector {
+        RankSelector { count }
+    }
+}
+
+impl<T, F> Selector<T, F> for RankSelector
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    fn select_from<'a>(
+        &self,
+        mut context: SelectionContext<'a, '_, T, F>,
+    ) -> Result<Parents<&'a T>, Error> {
+        let population = context.population();
+        let fitness = context.fitness();
+        check_count(self.count, population.len())?;
+        let mut order: Vec<usize> = (0..population.len()).collect();
+        order.sort_by(|&a, &b| fitness[a].cmp(&fitness[b]));
+        let mut weights = vec![0.0; population.len()];
+        for (rank, &i) in order.iter().enumerate() {
+            weights[i] = (rank + 1) as f64;
+        }
+        Ok(spin(population, &weights, self.count, context.rng()))
+    }
+}
+
+/// Check the `count` parameter of a roulette-style selector.
+pub(super) fn check_count(count: usize, population: usize) -> Result<(), Error> {
+    if population == 0 {
+        Err(Error::EmptyPopulation)
+    } else if count == 0 || count % 2 != 0 {
+        Err(Error::InvalidSelectorParams(format!(
+            "Invalid parameter `count`: {}. Should be larger than zero and a \
+             multiple of two.",
+            count
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Select `count` parents, each with a probability proportional to its weight.
+///
+/// If all weights are zero, parents are selected uniformly.
+pub(super) fn spin<'a, T, R>(
+    population: &'a [T],
+    weights: &[f64],
+    count: usize,
+    rng: &mut R,
+) -> Parents<&'a T>
+where
+    R: Rng,
+{
+    let mut cumulative = Vec::with_capacity(weights.len());
+    let mut total = 0.0;
+    for w in weights {
+        total += w;
+        cumulative.push(total);
+    }
+    let mut pick = || {
+        if total > 0.0 {
+            let r = rng.gen::<f64>() * total;
+            // The first index whose cumulative weight exceeds `r`.
+            let i = cumulative.partition_point(|&c| c <= r);
+            &population[i.min(population.len() - 1)]
+        } else {
+            &population[rng.gen_range(0, population.len())]
+        }
+    };
+    (0..count / 2).map(|_| (pick(), pick())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use sim::select::*;
+    use test::Test;
+
+    #[test]
+    fn test_count_zero() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        assert!(RouletteSelector::new(0).select(&population).is_err());
+        assert!(RankSelector::new(0).select(&population).is_err());
+    }
+
+    #[test]
+    fn test_count_odd() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        assert!(RouletteSelector::new(5).select(&population).is_err());
+        assert!(RankSelector::new(5).select(&population).is_err());
+    }
+
+    #[test]
+    fn test_result_size() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        assert_eq!(
+            RouletteSelector::new(20).select(&population).unwrap().len(),
+            10
+        );
+        assert_eq!(RankSelector::new(20).select(&population).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_zero_weight_never_selected() {
+        // The worst phenotype has weight zero after shifting.
+        let population: Vec<Test> = vec![Test { f: 0 }, Test { f: 1 }, Test { f: 1 }];
+        let parents = RouletteSelector::new(100).select(&population).unwrap();
+        assert!(parents.iter().all(|&(a, b)| a.f == 1 && b.f == 1));
+    }
+
+    #[test]
+    fn test_equal_fitness() {
+        let population: Vec<Test> = (0..10).map(|_| Test { f: 3 }).collect();
+        assert_eq!(
+            RouletteSelector::new(4).select(&population).unwrap().len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_proportional() {
+        let population: Vec<Test> = vec![Test { f: 0 }, Test { f: 1 }, Test { f: 3 }];
+        let parents = RouletteSelector::new(4000).select(&population).unwrap();
+        let best = parents
+            .iter()
+            .flat_map(|&(a, b)| vec![a, b])
+            .filter(|x| x.f == 3)
+            .count();
+        // The best phenotype has 3/4 of the total weight.
+        assert!(best > 2700 && best < 3300);
+    }
+
+    #[test]
+    fn test_rank_prefers_fitter() {
+        let population: Vec<Test> = (0..10).map(|i| Test { f: i }).collect();
+        let parents = RankSelector::new(2000).select(&population).unwrap();
+        let best = parents
+            .iter()
+            .flat_map(|&(a, b)| vec![a, b])
+            .filter(|x| x.f == 9)
+            .count();
+        let worst = parents
+            .iter()
+            .flat_map(|&(a, b)| vec![a, b])
+            .filter(|x| x.f == 0)
+            .count();
+        assert!(best > worst);
+    }
+}
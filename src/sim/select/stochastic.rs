@@ -44,18 +44,22 @@ where
     T: Phenotype<F>,
     F: Fitness,
 {
-    fn select<'a>(&self, population: &'a [T]) -> Result<Parents<&'a T>, String> {
+    fn select_from<'a>(
+        &self,
+        mut context: SelectionContext<'a, '_, T, F>,
+    ) -> Result<Parents<&'a T>, Error> {
+        let population = context.population();
         if self.count == 0 || self.count % 2 != 0 || self.count >= population.len() {
-            return Err(format!(
+            return Err(Error::InvalidSelectorParams(format!(
                 "Invalid parameter `count`: {}. Should be larger than zero, a \
                  multiple of two and less than the population size.",
                 self.count
-            ));
+            )));
         }
 
         let ratio = population.len() / self.count;
         let mut result: Parents<&T> = Vec::new();
-        let mut i = ::rand::thread_rng().gen_range::<usize>(0, population.len());
+        let mut i = context.rng().gen_range::<usize>(0, population.len());
         let mut selected = 0;
         while selected < self.count {
             result.push((
@@ -54,11 +54,11 @@ impl TournamentSelector {
     ///
     /// * `count`: must be larger than zero, a multiple of two and less than the population size.
     /// * `participants`: must be larger than one and less than the population size.
-    pub fn new_checked(count: usize, participants: usize) -> Result<TournamentSelector, String> {
+    pub fn new_checked(count: usize, participants: usize) -> Result<TournamentSelector, Error> {
         if count == 0 || count % 2 != 0 || participants < 2 {
-            Err(String::from(
+            Err(Error::InvalidSelectorParams(String::from(
                 "count must be larger than zero and a multiple of two; participants must be larger than one",
-            ))
+            )))
         } else {
             Ok(TournamentSelector {
                 count,
@@ -73,32 +73,36 @@ where
     T: Phenotype<F>,
     F: Fitness,
 {
-    fn select<'a>(&self, population: &'a [T]) -> Result<Parents<&'a T>, String> {
+    fn select_from<'a>(
+        &self,
+        mut context: SelectionContext<'a, '_, T, F>,
+    ) -> Result<Parents<&'a T>, Error> {
+        let population = context.population();
+        let fitness = context.fitness();
         if self.count == 0 || self.count % 2 != 0 || self.count * 2 >= population.len() {
-            return Err(format!(
+            return Err(Error::InvalidSelectorParams(format!(
                 "Invalid parameter `count`: {}. Should be larger than zero, a \
                  multiple of two and less than half the population size.",
                 self.count
-            ));
+            )));
         }
         if self.participants == 0 || self.participants >= population.len() {
-            return Err(format!(
+            return Err(Error::InvalidSelectorParams(format!(
                 "Invalid parameter `participants`: {}. Should be larger than \
                  zero and less than the population size.",
                 self.participants
-            ));
+            )));
         }
 
         let mut result: Parents<&T> = Vec::new();
-        let mut rng = ::rand::thread_rng();
+        let rng = context.rng();
         for _ in 0..(self.count / 2) {
-            let mut tournament: Vec<&T> = Vec::with_capacity(self.participants);
+            let mut tournament: Vec<usize> = Vec::with_capacity(self.participants);
             for _ in 0..self.participants {
-                let index = rng.gen_range::<usize>(0, population.len());
-                tournament.push(&population[index]);
+                tournament.push(rng.gen_range::<usize>(0, population.len()));
             }
-            tournament.sort_by(|x, y| y.fitness().cmp(&x.fitness()));
-            result.push((tournament[0], tournament[1]));
+            tournament.sort_by(|&a, &b| fitness[b].cmp(&fitness[a]));
+            result.push((&population[tournament[0]], &population[tournament[1]]));
         }
         Ok(result)
     }
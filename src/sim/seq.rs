@@ -20,119 +20,426 @@
 //! To use a `Simulator`, you need a `SimulatorBuilder`, which you can
 //! obtain by calling `Simulator::builder()`.
 
+use super::checkpoint::Checkpoint;
+#[cfg(feature = "serde")]
+use super::checkpoint::CheckpointWriter;
+use super::crossover::CrossoverOperator;
 use super::earlystopper::*;
+use super::handle::{BestHandle, Control, SimulationHandle};
 use super::iterlimit::*;
+use super::mutation::MutationOperator;
+use super::replace::{ReplaceRandom, ReplacementPolicy};
+use super::report::{RunReport, StopReason};
 use super::select::*;
+use super::termination::{Termination, TerminationState};
 use super::*;
 use pheno::Fitness;
+use pheno::Genome;
+use pheno::NumericFitness;
 use pheno::Phenotype;
-use rand::Rng;
+use rand::{Rng, StdRng};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde_json;
+use stats::{genotype_entropy, HallOfFame, OperatorCounts, PopulationObserver, PopulationStats};
+use std::fmt;
+use std::hash::Hash;
+use std::io;
 use std::marker::PhantomData;
-use std::time::Instant;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Called with the new best individual and the current iteration.
+type ImprovementCallback<'a, T> = Callback<dyn FnMut(&T, u64) + 'a>;
+
+/// Called with information about every new generation.
+type GenerationCallback<'a, T, F> = Callback<dyn FnMut(&GenerationInfo<T, F>) + 'a>;
+
+/// Saves a checkpoint; this hides the serialization bounds on `T` from the simulator.
+type CheckpointCallback<'a, T> = Callback<dyn FnMut(&Checkpoint<T>) -> io::Result<()> + 'a>;
+
+/// Saves the best individual; this hides the serialization bounds on `T` from the simulator.
+type PersistCallback<'a, T> = Callback<dyn FnMut(&T) -> io::Result<()> + 'a>;
+
+/// Saves a checkpoint every `every` iterations.
+#[derive(Debug)]
+struct AutoCheckpoint<'a, T> {
+    every: u64,
+    save: CheckpointCallback<'a, T>,
+}
+
+/// Identifies a child bred by a `Simulator`: the `child`-th child created in the
+/// iteration `generation`, counting from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TraceId {
+    /// The iteration in which the child was created.
+    pub generation: u64,
+    /// The index of the child among the children of its generation.
+    pub child: usize,
+}
+
+/// How a child was bred, as recorded by `SimulatorBuilder::with_breeding_trace`.
+#[derive(Clone, Debug)]
+pub struct BreedingTrace<T, R> {
+    id: TraceId,
+    parents: (T, T),
+    child: T,
+    crossover_rate: f64,
+    mutation_rate: f64,
+    /// The state of the random number generator before the child was bred.
+    rng: R,
+}
+
+impl<T, R> BreedingTrace<T, R> {
+    /// Get the identifier of the child, to pass to `Simulator::reproduce_child`.
+    pub fn id(&self) -> TraceId {
+        self.id
+    }
+
+    /// Get the parents of the child.
+    pub fn parents(&self) -> (&T, &T) {
+        (&self.parents.0, &self.parents.1)
+    }
+
+    /// Get the child, before it was evaluated or replaced.
+    pub fn child(&self) -> &T {
+        &self.child
+    }
+}
+
+/// The breeding traces of the last `generations` generations, with the function
+/// used to copy the random number generator.
+#[derive(Debug)]
+struct TraceLog<T, R> {
+    generations: u64,
+    traces: Vec<BreedingTrace<T, R>>,
+    snapshot: fn(&R) -> R,
+}
+
+/// Information about a generation, passed to the generation callback.
+#[derive(Debug)]
+pub struct GenerationInfo<'g, T: 'g, F: 'g> {
+    /// The number of iterations executed, including the one that created this generation.
+    pub iteration: u64,
+    /// The best individual of this generation.
+    pub best: &'g T,
+    /// The fitness of `best`.
+    pub best_fitness: &'g F,
+    /// The fitness of every individual of this generation, in order.
+    pub fitness: &'g [F],
+}
+
+impl<'g, T, F> GenerationInfo<'g, T, F>
+where
+    F: NumericFitness,
+{
+    /// Compute summary statistics of the fitness values of this generation.
+    pub fn stats(&self) -> PopulationStats<F> {
+        PopulationStats::from_fitness(self.fitness.to_vec()).unwrap()
+    }
+}
+
+/// Either a regular or a stateful selector.
+enum AnySelector<T, F> {
+    Stateless(Box<dyn Selector<T, F>>),
+    Stateful(Box<dyn StatefulSelector<T, F>>),
+}
+
+impl<T, F> AnySelector<T, F>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    fn select_mut<'a>(
+        &mut self,
+        context: SelectionContext<'a, '_, T, F>,
+    ) -> Result<Parents<&'a T>, Error> {
+        match *self {
+            AnySelector::Stateless(ref s) => s.select_from(context),
+            AnySelector::Stateful(ref mut s) => s.select_mut(context),
+        }
+    }
+}
+
+impl<T, F> fmt::Debug for AnySelector<T, F>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AnySelector::Stateless(ref s) => s.fmt(f),
+            AnySelector::Stateful(ref s) => s.fmt(f),
+        }
+    }
+}
+
+/// Settings that replace the regular ones during the first iterations of a run.
+#[derive(Debug)]
+struct Warmup<T, F>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    iterations: u64,
+    selector: AnySelector<T, F>,
+    mutation_rate: f64,
+}
+
+/// Stops a simulation once the genotype entropy of the population drops below a threshold.
+#[derive(Debug)]
+struct EntropyStop<T> {
+    threshold: f64,
+    entropy: fn(&[T]) -> f64,
+}
 
 /// A sequential implementation of `::sim::Simulation`.
 /// The genetic algorithm is run in a single thread.
+///
+/// Every random decision of the simulator is drawn from a random number generator of
+/// type `R`, which is a randomly seeded `StdRng` by default. Use
+/// `Simulator::builder_with_rng` to use another generator.
 #[derive(Debug)]
-pub struct Simulator<'a, T, F>
+pub struct Simulator<'a, T, F, R = StdRng>
 where
     T: 'a + Phenotype<F>,
     F: Fitness,
+    R: Rng,
 {
     population: &'a mut Vec<T>,
     iter_limit: IterLimit,
-    selector: Box<dyn Selector<T, F>>,
-    earlystopper: Option<EarlyStopper<F>>,
+    min_iters: u64,
+    selector: AnySelector<T, F>,
+    earlystoppers: Vec<EarlyStopper<F>>,
+    termination: Option<Box<dyn Termination<F>>>,
+    terminated: bool,
+    evaluations: u64,
+    max_evaluations: Option<u64>,
+    entropy_stop: Option<EntropyStop<T>>,
     duration: Option<NanoSecond>,
-    error: Option<String>,
+    last_step: Option<NanoSecond>,
+    error: Option<Error>,
+    best_ever: Option<Evaluated<T, F>>,
+    improvement_callback: Option<ImprovementCallback<'a, T>>,
+    observers: Vec<Box<dyn PopulationObserver<T, F> + 'a>>,
+    generation_callback: Option<GenerationCallback<'a, T, F>>,
+    auto_checkpoint: Option<AutoCheckpoint<'a, T>>,
+    persist_best: Option<PersistCallback<'a, T>>,
+    hall_of_fame: Option<HallOfFame<T, F>>,
+    best_handle: Option<BestHandle<T>>,
+    mating_quantile: Option<f64>,
+    crossover: Option<Box<dyn CrossoverOperator<T>>>,
+    crossover_rate: f64,
+    mutator: Option<Box<dyn MutationOperator<T>>>,
+    mutation_rate: f64,
+    elitism: usize,
+    replacement: Box<dyn ReplacementPolicy<T, F>>,
+    warmup: Option<Warmup<T, F>>,
+    operator_counts: OperatorCounts,
+    /// Index of the best individual in the population, if known.
+    best: Option<usize>,
+    /// Fitness of every individual in the population, in order, if known.
+    fitness: Option<Vec<F>>,
+    rng: R,
+    stop_reason: Option<StopReason>,
+    history: Option<Vec<F>>,
+    trace_log: Option<TraceLog<T, R>>,
     phantom: PhantomData<&'a T>,
 }
 
-impl<'a, T, F> Simulation<'a, T, F> for Simulator<'a, T, F>
+impl<'a, T, F, R> Simulation<'a, T, F> for Simulator<'a, T, F, R>
 where
     T: Phenotype<F>,
     F: Fitness,
+    R: RandomSeeded,
 {
-    type B = SimulatorBuilder<'a, T, F>;
+    type B = SimulatorBuilder<'a, T, F, R>;
 
-    /// Create builder.
-    #[allow(deprecated)]
-    fn builder(population: &'a mut Vec<T>) -> SimulatorBuilder<'a, T, F> {
-        SimulatorBuilder {
-            sim: Simulator {
-                population,
-                iter_limit: IterLimit::new(100),
-                selector: Box::new(MaximizeSelector::new(3)),
-                earlystopper: None,
-                duration: Some(0),
-                error: None,
-                phantom: PhantomData::default(),
-            },
-        }
+    /// Create builder, with a randomly seeded random number generator.
+    fn builder(population: &'a mut Vec<T>) -> SimulatorBuilder<'a, T, F, R> {
+        Simulator::builder_with_rng(population, R::random_seeded())
     }
 
     fn step(&mut self) -> StepResult {
         let time_start;
 
         if self.population.is_empty() {
-            self.error = Some(
-                "Tried to run a simulator without a population, or the \
-                 population was empty."
-                    .to_string(),
-            );
+            self.error = Some(Error::EmptyPopulation);
             return StepResult::Failure;
         }
 
-        let should_stop = match self.earlystopper {
-            Some(ref x) => self.iter_limit.reached() || x.reached(),
-            None => self.iter_limit.reached(),
+        let stop_reason = if self.iter_limit.reached() {
+            Some(StopReason::MaxIterations)
+        } else if self.iter_limit.get() < self.min_iters {
+            None
+        } else if self.terminated {
+            Some(StopReason::Termination)
+        } else if self.max_evaluations.is_some_and(|m| self.evaluations >= m) {
+            Some(StopReason::MaxEvaluations)
+        } else if self.earlystoppers.iter().any(|x| x.reached()) {
+            Some(StopReason::EarlyStop)
+        } else if self.converged() {
+            Some(StopReason::Converged)
+        } else {
+            None
         };
 
-        if !should_stop {
+        if stop_reason.is_none() {
             time_start = Instant::now();
 
+            let initial = self.evaluations == 0;
+            if self.best_ever.is_none() {
+                // Report the best individual of the initial population.
+                if let Err(e) = self.track_improvement() {
+                    self.error = Some(e);
+                    return StepResult::Failure;
+                }
+            }
+            if initial {
+                self.evaluate();
+                self.update_hall_of_fame();
+            }
+
+            self.evaluate();
+            let excluded = match self.exclude_from_mating() {
+                Ok(excluded) => excluded,
+                Err(e) => {
+                    self.error = Some(e);
+                    return StepResult::Failure;
+                }
+            };
+            let (selector, mutation_rate) = match self.warmup {
+                Some(ref mut w) if self.iter_limit.get() < w.iterations => {
+                    (&mut w.selector, w.mutation_rate)
+                }
+                _ => (&mut self.selector, self.mutation_rate),
+            };
+
+            if let Err(e) = check_rate("crossover", self.crossover_rate)
+                .and_then(|_| check_rate("mutation", mutation_rate))
+            {
+                restore_mating_pool(excluded, self.population, &mut self.fitness);
+                self.error = Some(e);
+                return StepResult::Failure;
+            }
+
             let mut children: Vec<T>;
             {
                 // Perform selection
-                let parents = match self.selector.select(self.population) {
+                let context = SelectionContext::new(
+                    self.population,
+                    self.fitness.as_ref().unwrap(),
+                    self.iter_limit.get(),
+                    &mut self.rng,
+                );
+                let parents = match selector.select_mut(context) {
                     Ok(parents) => parents,
                     Err(e) => {
+                        restore_mating_pool(excluded, self.population, &mut self.fitness);
                         self.error = Some(e);
                         return StepResult::Failure;
                     }
                 };
                 // Create children from the selected parents and mutate them.
-                children = parents
-                    .iter()
-                    .map(|&(a, b)| a.crossover(b).mutate())
-                    .collect();
+                let mut counts = OperatorCounts {
+                    children: parents.len(),
+                    crossovers: 0,
+                    mutations: 0,
+                };
+                let generation = self.iter_limit.get() + 1;
+                let rng = &mut self.rng;
+                children = Vec::with_capacity(parents.len());
+                for (i, &(a, b)) in parents.iter().enumerate() {
+                    let state = self.trace_log.as_ref().map(|log| (log.snapshot)(rng));
+                    let (child, crossed, mutated) = breed(
+                        a,
+                        b,
+                        self.crossover.as_deref(),
+                        self.mutator.as_deref(),
+                        (self.crossover_rate, mutation_rate),
+                        rng,
+                    );
+                    counts.crossovers += crossed as usize;
+                    counts.mutations += mutated as usize;
+                    if let (Some(log), Some(rng)) = (self.trace_log.as_mut(), state) {
+                        log.traces.push(BreedingTrace {
+                            id: TraceId {
+                                generation,
+                                child: i,
+                            },
+                            parents: (a.clone(), b.clone()),
+                            child: child.clone(),
+                            crossover_rate: self.crossover_rate,
+                            mutation_rate,
+                            rng,
+                        });
+                    }
+                    children.push(child);
+                }
+                self.operator_counts = counts;
+                if let Some(ref mut log) = self.trace_log {
+                    let keep = log.generations;
+                    log.traces.retain(|t| t.id.generation + keep > generation);
+                }
             }
-            // Kill off parts of the population at random to make room for the children
-            self.kill_off(children.len());
-            self.population.append(&mut children);
-
-            if let Some(ref mut stopper) = self.earlystopper {
-                let highest_fitness = self
-                    .population
-                    .iter()
-                    .max_by_key(|x| x.fitness())
-                    .unwrap()
-                    .fitness();
-                stopper.update(highest_fitness);
+            restore_mating_pool(excluded, self.population, &mut self.fitness);
+            // Make room for the children
+            self.replace(children);
+
+            self.evaluate();
+            self.best = best_of(self.fitness.as_ref().unwrap());
+            if let Some(ref mut history) = self.history {
+                let best = &self.fitness.as_ref().unwrap()[self.best.unwrap()];
+                history.push(best.clone());
             }
 
-            self.iter_limit.inc();
-            self.duration = match self.duration {
-                Some(x) => {
-                    let elapsed = time_start.elapsed();
-                    let y = elapsed.as_secs() as NanoSecond * 1_000_000_000
-                        + u64::from(elapsed.subsec_nanos()) as NanoSecond;
-                    Some(x + y)
+            if !self.earlystoppers.is_empty() {
+                let best = self.best.unwrap();
+                for i in 0..self.earlystoppers.len() {
+                    let highest_fitness = self.fitness.as_ref().unwrap()[best].clone();
+                    self.earlystoppers[i].update(highest_fitness);
                 }
-                None => None,
+            }
+
+            self.iter_limit.inc();
+            if let Err(e) = self.track_improvement() {
+                self.error = Some(e);
+                return StepResult::Failure;
+            }
+            self.update_hall_of_fame();
+            for observer in &mut self.observers {
+                observer.observe(
+                    self.iter_limit.get(),
+                    self.population,
+                    self.fitness.as_ref().unwrap(),
+                );
+            }
+            if let Some(ref mut callback) = self.generation_callback {
+                let best = self.best.unwrap();
+                let fitness = self.fitness.as_ref().unwrap();
+                (callback.0)(&GenerationInfo {
+                    iteration: self.iter_limit.get(),
+                    best: &self.population[best],
+                    best_fitness: &fitness[best],
+                    fitness,
+                });
+            }
+            self.last_step = elapsed_nanos(time_start);
+            self.duration = match (self.duration, self.last_step) {
+                (Some(x), Some(y)) => x.checked_add(y),
+                _ => None,
             };
+            self.check_termination();
+            if let Err(e) = self.save_checkpoint() {
+                self.error = Some(e);
+                return StepResult::Failure;
+            }
 
             StepResult::Success // Not done yet, but successful
         } else {
+            self.stop_reason = stop_reason;
             StepResult::Done
         }
     }
@@ -161,7 +468,13 @@ where
     fn get(&'a self) -> SimResult<'a, T> {
         match self.error {
             Some(ref e) => Err(e),
-            None => Ok(self.population.iter().max_by_key(|x| x.fitness()).unwrap()),
+            None => {
+                let best = match self.best {
+                    Some(best) => best,
+                    None => best_index(self.population).unwrap(),
+                };
+                Ok(&self.population[best])
+            }
         }
     }
 
@@ -183,161 +496,1282 @@ where
     T: Phenotype<F>,
     F: Fitness,
 {
-    /// Kill off phenotypes using stochastic universal sampling.
-    fn kill_off(&mut self, count: usize) {
-        let ratio = self.population.len() / count;
-        let mut i = ::rand::thread_rng().gen_range::<usize>(0, self.population.len());
-        for _ in 0..count {
-            self.population.swap_remove(i);
-            i += ratio;
-            i %= self.population.len();
-        }
+    /// Create builder, with a randomly seeded `StdRng`.
+    ///
+    /// This is `Simulation::builder` for the default random number generator, so
+    /// that no type annotation is needed to select it.
+    pub fn builder(population: &'a mut Vec<T>) -> SimulatorBuilder<'a, T, F> {
+        Simulator::builder_with_rng(population, StdRng::random_seeded())
     }
 }
 
-/// A `Builder` for the `Simulator` type.
-#[derive(Debug)]
-pub struct SimulatorBuilder<'a, T, F>
-where
-    T: 'a + Phenotype<F>,
-    F: Fitness,
-{
-    sim: Simulator<'a, T, F>,
-}
-
-impl<'a, T, F> SimulatorBuilder<'a, T, F>
+impl<'a, T, F, R> Simulator<'a, T, F, R>
 where
     T: Phenotype<F>,
     F: Fitness,
+    R: Rng,
 {
-    /// Set the selector of the resulting `Simulator`.
+    /// Create a builder for a `Simulator` that draws its random decisions from `rng`.
     ///
-    /// Returns itself for chaining purposes.
-    #[deprecated(
-        note = "The consuming builder functions may be removed in a future release.
-                       Use the functions that start with `with_` instead.",
-        since = "1.8.0"
-    )]
-    pub fn set_selector(mut self, sel: Box<dyn Selector<T, F>>) -> Self {
-        self.sim.selector = sel;
-        self
+    /// Any `Rng` can be used, such as a seeded `StdRng`, a small and fast generator like
+    /// `XorShiftRng`, or a deterministic generator in tests.
+    #[allow(deprecated)]
+    pub fn builder_with_rng(population: &'a mut Vec<T>, rng: R) -> SimulatorBuilder<'a, T, F, R> {
+        SimulatorBuilder {
+            sim: Simulator {
+                population,
+                iter_limit: IterLimit::new(100),
+                min_iters: 0,
+                selector: AnySelector::Stateless(Box::new(MaximizeSelector::new(3))),
+                earlystoppers: Vec::new(),
+                termination: None,
+                terminated: false,
+                evaluations: 0,
+                max_evaluations: None,
+                entropy_stop: None,
+                duration: Some(0),
+                last_step: None,
+                error: None,
+                best_ever: None,
+                improvement_callback: None,
+                observers: Vec::new(),
+                generation_callback: None,
+                auto_checkpoint: None,
+                persist_best: None,
+                hall_of_fame: None,
+                best_handle: None,
+                mating_quantile: None,
+                crossover: None,
+                crossover_rate: 1.0,
+                mutator: None,
+                mutation_rate: 1.0,
+                elitism: 0,
+                replacement: Box::new(ReplaceRandom::new()),
+                warmup: None,
+                operator_counts: OperatorCounts::default(),
+                best: None,
+                fitness: None,
+                rng,
+                stop_reason: None,
+                history: None,
+                trace_log: None,
+                phantom: PhantomData,
+            },
+        }
     }
 
-    /// Set the selector of the resulting `Simulator`.
+    /// Notify the improvement callback and the best handle, if any, when the best
+    /// individual in the population is better than the best individual seen so far.
+    fn track_improvement(&mut self) -> Result<(), Error> {
+        if self.improvement_callback.is_none()
+            && self.best_handle.is_none()
+            && self.persist_best.is_none()
+        {
+            return Ok(());
+        }
+        self.evaluate();
+        if self.best.is_none() {
+            self.best = best_of(self.fitness.as_ref().unwrap());
+        }
+        let i = self.best.unwrap();
+        let improved = match self.best_ever {
+            Some(ref previous) => self.fitness.as_ref().unwrap()[i] > previous.fitness,
+            None => true,
+        };
+        if improved {
+            let iteration = self.iter_limit.get();
+            let fitness = self.fitness.as_ref().unwrap()[i].clone();
+            let best = &self.population[i];
+            self.best_ever = Some(Evaluated {
+                individual: best.clone(),
+                fitness,
+            });
+            if let Some(ref handle) = self.best_handle {
+                handle.set(best.clone(), iteration);
+            }
+            if let Some(ref mut callback) = self.improvement_callback {
+                (callback.0)(best, iteration);
+            }
+            if let Some(ref mut persist) = self.persist_best {
+                (persist.0)(best).map_err(|e| {
+                    Error::Io(format!("failed to persist the best individual: {}", e))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Record the current population in the hall of fame, if one is kept.
+    fn update_hall_of_fame(&mut self) {
+        if self.hall_of_fame.is_some() {
+            self.evaluate();
+            let fitness = self.fitness.as_ref().unwrap();
+            self.hall_of_fame
+                .as_mut()
+                .unwrap()
+                .update(self.population, fitness);
+        }
+    }
+
+    /// Save a checkpoint if automatic checkpointing is enabled and one is due.
+    fn save_checkpoint(&mut self) -> Result<(), Error> {
+        let due = match self.auto_checkpoint {
+            Some(ref a) if a.every == 0 => {
+                return Err(Error::InvalidConfig(String::from(
+                    "Invalid checkpoint interval: 0. Should be larger than zero.",
+                )));
+            }
+            Some(ref a) => self.iter_limit.get() % a.every == 0,
+            None => false,
+        };
+        if due {
+            let checkpoint = self.checkpoint();
+            let save = &mut self.auto_checkpoint.as_mut().unwrap().save;
+            (save.0)(&checkpoint)
+                .map_err(|e| Error::Io(format!("failed to write checkpoint: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    /// Check whether the genotype entropy has dropped below its threshold, if one is set.
+    fn converged(&self) -> bool {
+        match self.entropy_stop {
+            Some(ref e) => (e.entropy)(self.population) < e.threshold,
+            None => false,
+        }
+    }
+
+    /// Calculate the genotype entropy of the current population.
     ///
-    /// Returns a mutable reference to itself for chaining purposes.
-    /// Does not consume the builder.
-    pub fn with_selector(&mut self, sel: Box<dyn Selector<T, F>>) -> &mut Self {
-        self.sim.selector = sel;
-        self
+    /// See `stats::genotype_entropy` for details.
+    pub fn genotype_entropy(&self) -> f64
+    where
+        T: Genome,
+        T::Gene: Hash + Eq,
+    {
+        genotype_entropy(self.population)
     }
 
-    /// Set the maximum number of iterations of the resulting `Simulator`.
+    /// Compute summary statistics of the fitness values of the current population.
     ///
-    /// The `Simulator` will stop running after this number of iterations.
+    /// Returns `None` if the population is empty.
+    pub fn stats(&self) -> Option<PopulationStats<F>>
+    where
+        F: NumericFitness,
+    {
+        match self.fitness {
+            Some(ref fitness) => PopulationStats::from_fitness(fitness.clone()),
+            None => PopulationStats::from_population(self.population),
+        }
+    }
+
+    /// Get the best individual in the population, or `None` if it is empty.
     ///
-    /// Returns itself for chaining purposes.
-    #[deprecated(
-        note = "The consuming builder functions may be removed in a future release.
-                       Use the functions that start with `with_` instead.",
-        since = "1.8.0"
-    )]
-    pub fn set_max_iters(mut self, i: u64) -> Self {
-        self.sim.iter_limit = IterLimit::new(i);
-        self
+    /// Unlike `get`, this does not borrow the simulator for its whole lifetime.
+    pub(crate) fn best(&self) -> Option<&T> {
+        self.best
+            .or_else(|| best_index(self.population))
+            .map(|i| &self.population[i])
     }
 
-    /// Set the maximum number of iterations of the resulting `Simulator`.
+    /// Get the mean fitness of the population, or `None` if it is empty.
+    pub(crate) fn mean_fitness(&self) -> Option<f64>
+    where
+        F: NumericFitness,
+    {
+        if self.population.is_empty() {
+            return None;
+        }
+        let sum: f64 = match self.fitness {
+            Some(ref fitness) => fitness.iter().map(|f| f.to_f64()).sum(),
+            None => self.population.iter().map(|x| x.fitness().to_f64()).sum(),
+        };
+        Some(sum / self.population.len() as f64)
+    }
+
+    /// Get the error of the simulator, if it failed.
+    pub(crate) fn error(&self) -> Option<&Error> {
+        self.error.as_ref()
+    }
+
+    /// Get the number of consecutive iterations in which the highest fitness did not
+    /// change significantly, or `None` if early stopping is not enabled.
     ///
-    /// The `Simulator` will stop running after this number of iterations.
+    /// If several early stopping conditions are set, the largest count is returned.
+    pub fn stagnant_generations(&self) -> Option<u64> {
+        self.earlystoppers.iter().map(|x| x.stagnant()).max()
+    }
+
+    /// Get the number of fitness evaluations made so far.
     ///
-    /// Returns a mutable reference to itself for chaining purposes.
-    /// Does not consume the builder.
-    pub fn with_max_iters(&mut self, i: u64) -> &mut Self {
-        self.sim.iter_limit = IterLimit::new(i);
-        self
+    /// This is the number of times the simulator called `Phenotype::fitness` while
+    /// stepping. The fitness of the population is computed once per iteration, after
+    /// the children have been added, and shared by all settings that need it. Calls
+    /// made by query functions such as `get` and `stats` are not counted.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
     }
 
-    /// Set early stopping. If for `n_iters` iterations, the change in the highest fitness
-    /// is smaller than `delta`, the simulator will stop running.
+    /// Save the population and the counters of this simulator in a `Checkpoint`.
     ///
-    /// Returns itself for chaining purposes.
-    #[deprecated(
-        note = "The consuming builder functions may be removed in a future release.
-                       Use the functions that start with `with_` instead.",
-        since = "1.8.0"
-    )]
-    pub fn set_early_stop(mut self, delta: F, n_iters: u64) -> Self {
-        self.sim.earlystopper = Some(EarlyStopper::new(delta, n_iters));
-        self
+    /// A new simulator can resume from it using `SimulatorBuilder::with_checkpoint`.
+    pub fn checkpoint(&self) -> Checkpoint<T> {
+        Checkpoint::new(
+            self.iter_limit.get(),
+            self.evaluations,
+            self.population.clone(),
+        )
     }
 
-    /// Set early stopping. If for `n_iters` iterations, the change in the highest fitness
-    /// is smaller than `delta`, the simulator will stop running.
+    /// Get the reason the simulator stopped, or `None` if it has not stopped yet.
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        self.stop_reason
+    }
+
+    /// Get the best fitness of every generation, or `None` if the history is not recorded.
     ///
-    /// Returns a mutable reference to itself for chaining purposes.
-    /// Does not consume the builder.
-    pub fn with_early_stop(&mut self, delta: F, n_iters: u64) -> &mut Self {
-        self.sim.earlystopper = Some(EarlyStopper::new(delta, n_iters));
-        self
+    /// Enable it with `SimulatorBuilder::with_fitness_history`.
+    pub fn fitness_history(&self) -> Option<&[F]> {
+        self.history.as_ref().map(|h| &h[..])
     }
-}
 
-impl<'a, T, F> Builder<Simulator<'a, T, F>> for SimulatorBuilder<'a, T, F>
-where
-    T: Phenotype<F>,
-    F: Fitness,
-{
-    fn build(self) -> Simulator<'a, T, F> {
-        self.sim
+    /// Create a `RunReport` of the run so far.
+    ///
+    /// The best individual is the best ever seen if it is tracked, e.g. because a
+    /// hall of fame or an improvement callback is set, and the best individual of
+    /// the current population otherwise.
+    pub fn report(&self) -> RunReport<T, F> {
+        let best = match self.hall_of_fame.as_ref().and_then(|h| h.members().first()) {
+            Some(member) => Some(member.clone()),
+            None => match (self.best_ever.as_ref(), self.best, self.fitness.as_ref()) {
+                (Some(e), _, _) => Some((e.individual.clone(), e.fitness.clone())),
+                (None, Some(i), Some(fitness)) => {
+                    Some((self.population[i].clone(), fitness[i].clone()))
+                }
+                _ => self.best().map(|x| (x.clone(), x.fitness())),
+            },
+        };
+        let operator =
+            |op: Option<String>, default: &str| op.unwrap_or_else(|| default.to_string());
+        RunReport {
+            settings: vec![
+                ("population".to_string(), self.population.len().to_string()),
+                (
+                    "max_iterations".to_string(),
+                    self.iter_limit.max().to_string(),
+                ),
+                ("selector".to_string(), format!("{:?}", self.selector)),
+                ("replacement".to_string(), format!("{:?}", self.replacement)),
+                (
+                    "crossover".to_string(),
+                    operator(
+                        self.crossover.as_ref().map(|op| format!("{:?}", op)),
+                        "Phenotype::crossover",
+                    ),
+                ),
+                (
+                    "crossover_rate".to_string(),
+                    self.crossover_rate.to_string(),
+                ),
+                (
+                    "mutator".to_string(),
+                    operator(
+                        self.mutator.as_ref().map(|op| format!("{:?}", op)),
+                        "Phenotype::mutate",
+                    ),
+                ),
+                ("mutation_rate".to_string(), self.mutation_rate.to_string()),
+                ("elitism".to_string(), self.elitism.to_string()),
+            ],
+            stop_reason: self.stop_reason,
+            error: self.error.as_ref().map(Error::to_string),
+            iterations: self.iter_limit.get(),
+            evaluations: self.evaluations,
+            duration: self.duration,
+            history: self
+                .fitness_history()
+                .map(|h| h.to_vec())
+                .unwrap_or_default(),
+            best_fitness: best.as_ref().map(|b| b.1.clone()),
+            best: best.map(|b| b.0),
+        }
     }
-}
 
-#[cfg(test)]
-#[allow(deprecated)]
-mod tests {
-    use sim::select::*;
-    use sim::*;
-    use test::MyFitness;
-    use test::Test;
+    /// Get the breeding traces of the last generations, in the order the children were bred.
+    ///
+    /// This is empty unless `SimulatorBuilder::with_breeding_trace` was called.
+    pub fn breeding_traces(&self) -> &[BreedingTrace<T, R>] {
+        match self.trace_log {
+            Some(ref log) => &log.traces,
+            None => &[],
+        }
+    }
 
-    #[test]
-    fn test_kill_off_count() {
-        let selector = MaximizeSelector::new(2);
-        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
-        let mut s = seq::Simulator::builder(&mut population)
-            .set_selector(Box::new(selector))
-            .build();
-        s.kill_off(10);
-        assert_eq!(s.population.len(), 90);
+    /// Breed the child identified by `id` again, in isolation.
+    ///
+    /// The child is created from the same parents, with the same operators and rates,
+    /// and the random number generator in the same state, so the operators make the
+    /// same random decisions as they did during the run. This makes it possible to step
+    /// through the creation of a particular child in a debugger. Operators and phenotypes
+    /// that do not draw from the simulator's generator may produce a different child.
+    ///
+    /// Returns an error if no trace of the child is kept.
+    pub fn reproduce_child(&self, id: TraceId) -> Result<T, Error> {
+        let log = match self.trace_log {
+            Some(ref log) => log,
+            None => {
+                return Err(Error::TraceUnavailable(String::from(
+                    "Breeding traces are not recorded. Use `with_breeding_trace`.",
+                )))
+            }
+        };
+        let trace = match log.traces.iter().find(|t| t.id == id) {
+            Some(trace) => trace,
+            None => {
+                return Err(Error::TraceUnavailable(format!(
+                    "No breeding trace of child {} of generation {}.",
+                    id.child, id.generation
+                )))
+            }
+        };
+        let mut rng = (log.snapshot)(&trace.rng);
+        let (child, _, _) = breed(
+            &trace.parents.0,
+            &trace.parents.1,
+            self.crossover.as_deref(),
+            self.mutator.as_deref(),
+            (trace.crossover_rate, trace.mutation_rate),
+            &mut rng,
+        );
+        Ok(child)
     }
 
-    #[test]
-    fn test_max_iters() {
-        let selector = MaximizeSelector::new(2);
-        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
-        let mut s = seq::Simulator::builder(&mut population)
-            .set_selector(Box::new(selector))
-            .set_max_iters(2)
-            .build();
-        s.run();
-        assert!(s.iterations() <= 2);
+    /// Get the hall of fame, or `None` if it is not enabled.
+    ///
+    /// It contains the best distinct individuals of all generations, including the
+    /// initial population, even if they were later replaced.
+    pub fn hall_of_fame(&self) -> Option<&HallOfFame<T, F>> {
+        self.hall_of_fame.as_ref()
     }
 
-    #[test]
-    fn test_early_stopping() {
-        let selector = MaximizeSelector::new(2);
-        let mut population: Vec<Test> = (0..100).map(|_| Test { f: 0 }).collect();
-        let mut s = seq::Simulator::builder(&mut population)
-            .set_selector(Box::new(selector))
-            .set_early_stop(MyFitness { f: 10 }, 5)
-            .set_max_iters(10)
-            .build();
-        s.run();
-        assert!(s.iterations() <= 5);
+    /// Get the number of iterations left before the maximum number of iterations is reached.
+    pub fn remaining_iterations(&self) -> u64 {
+        self.iter_limit.remaining()
     }
 
-    #[test]
+    /// Get the number of nanoseconds the latest step took, or `None` if no step was
+    /// made yet or in case of an overflow.
+    ///
+    /// Use `time` for the total duration of all steps.
+    pub fn last_step_time(&self) -> Option<NanoSecond> {
+        self.last_step
+    }
+
+    /// Get the number of times crossover and mutation were applied in the latest step.
+    ///
+    /// Before the first step, all counts are zero.
+    pub fn operator_counts(&self) -> OperatorCounts {
+        self.operator_counts
+    }
+
+    /// Set aside the individuals that are excluded from the mating pool, so that the
+    /// rest of the population is the mating pool. `restore_mating_pool` puts them back.
+    ///
+    /// If a mating quantile filter is set, the bottom quantile of the population by
+    /// fitness is excluded. Both the mating pool and the excluded individuals keep
+    /// their order.
+    fn exclude_from_mating(&mut self) -> Result<Option<Excluded<T, F>>, Error> {
+        match self.mating_quantile {
+            None => Ok(None),
+            Some(q) if !(0.0..1.0).contains(&q) => Err(Error::InvalidConfig(format!(
+                "Invalid mating quantile: {}. Should be at least zero and less than one.",
+                q
+            ))),
+            Some(q) => {
+                self.evaluate();
+                let fitness = self.fitness.as_mut().unwrap();
+                let mut order: Vec<usize> = (0..fitness.len()).collect();
+                order.sort_by(|&a, &b| fitness[a].cmp(&fitness[b]));
+                let mut marked = vec![false; order.len()];
+                for &i in &order[..(q * order.len() as f64) as usize] {
+                    marked[i] = true;
+                }
+                Ok(Some(Excluded {
+                    individuals: set_aside(self.population, &marked),
+                    fitness: set_aside(fitness, &marked),
+                    marked,
+                }))
+            }
+        }
+    }
+
+    /// Update the termination condition, if any, with the state after an iteration.
+    fn check_termination(&mut self) {
+        if self.termination.is_none() {
+            return;
+        }
+        self.evaluate();
+        if let Some(ref mut termination) = self.termination {
+            let fitness = self.fitness.as_ref().unwrap();
+            let best = match self.best {
+                Some(i) => &fitness[i],
+                None => fitness.iter().max().unwrap(),
+            };
+            let elapsed = match self.duration {
+                Some(d) => Duration::from_nanos(d as u64),
+                None => Duration::MAX,
+            };
+            self.terminated = termination.should_stop(&TerminationState {
+                iterations: self.iter_limit.get(),
+                evaluations: self.evaluations,
+                elapsed,
+                best,
+                fitness,
+            });
+        }
+    }
+
+    /// Compute the fitness of every individual in the population, unless it is known.
+    ///
+    /// The fitness is cached until the population changes, so that every individual
+    /// is evaluated at most once per iteration by the simulator itself.
+    fn evaluate(&mut self) {
+        if self.fitness.is_none() {
+            self.fitness = Some(self.population.iter().map(|x| x.fitness()).collect());
+            self.evaluations += self.population.len() as u64;
+        }
+    }
+
+    /// Replace part of the population by `children` using the replacement policy.
+    ///
+    /// If elitism is enabled, the fittest phenotypes are never replaced, as long as
+    /// enough other phenotypes remain to make room for the children. They keep their
+    /// place in the population, see `ReplacementPolicy::replace_protected`.
+    fn replace(&mut self, children: Vec<T>) {
+        let elites = self
+            .elitism
+            .min(self.population.len().saturating_sub(children.len()));
+        if elites == 0 {
+            self.replacement
+                .replace_with_rng(self.population, children, &mut self.rng);
+            self.fitness = None;
+            return;
+        }
+        self.evaluate();
+        let fitness = self.fitness.take().unwrap();
+        let mut order: Vec<usize> = (0..fitness.len()).collect();
+        order.sort_by(|&a, &b| fitness[a].cmp(&fitness[b]));
+        let mut is_elite = vec![false; order.len()];
+        for &i in &order[order.len() - elites..] {
+            is_elite[i] = true;
+        }
+        self.replacement
+            .replace_protected(self.population, children, &is_elite, &mut self.rng);
+        self.best = None;
+    }
+
+    /// Kill off `count` phenotypes using stochastic universal sampling.
+    #[cfg(test)]
+    fn kill_off(&mut self, count: usize) {
+        replace::kill_off(self.population, count, &mut self.rng);
+        self.fitness = None;
+        self.best = None;
+    }
+}
+
+/// Individuals that are set aside from the mating pool, with their fitness.
+#[derive(Debug)]
+struct Excluded<T, F> {
+    /// The positions in the population that were set aside.
+    marked: Vec<bool>,
+    individuals: Vec<T>,
+    fitness: Vec<F>,
+}
+
+/// Put the individuals set aside by `exclude_from_mating` back in their places.
+fn restore_mating_pool<T, F>(
+    excluded: Option<Excluded<T, F>>,
+    population: &mut Vec<T>,
+    fitness: &mut Option<Vec<F>>,
+) {
+    if let Some(excluded) = excluded {
+        put_back(population, &excluded.marked, excluded.individuals);
+        put_back(
+            fitness.as_mut().unwrap(),
+            &excluded.marked,
+            excluded.fitness,
+        );
+    }
+}
+
+/// Move the items marked in `marked` out of `items`, keeping the order of both parts.
+fn set_aside<X>(items: &mut Vec<X>, marked: &[bool]) -> Vec<X> {
+    let (aside, rest) = items
+        .drain(..)
+        .zip(marked)
+        .partition::<Vec<_>, _>(|&(_, &m)| m);
+    items.extend(rest.into_iter().map(|(x, _)| x));
+    aside.into_iter().map(|(x, _)| x).collect()
+}
+
+/// Put the items moved out by `set_aside` back in their places.
+fn put_back<X>(items: &mut Vec<X>, marked: &[bool], aside: Vec<X>) {
+    let mut rest = ::std::mem::take(items).into_iter();
+    let mut aside = aside.into_iter();
+    for &m in marked {
+        let x = if m { aside.next() } else { rest.next() };
+        items.extend(x);
+    }
+}
+
+/// Check that an operator rate is a valid probability.
+fn check_rate(operator: &str, rate: f64) -> Result<(), Error> {
+    if (0.0..=1.0).contains(&rate) {
+        Ok(())
+    } else {
+        Err(Error::InvalidConfig(format!(
+            "Invalid {} rate: {}. Should be between zero and one.",
+            operator, rate
+        )))
+    }
+}
+
+/// Decide whether to apply an operator with the given rate.
+fn apply<R: Rng>(rate: f64, rng: &mut R) -> bool {
+    rate >= 1.0 || rng.gen::<f64>() < rate
+}
+
+/// Create a child of `a` and `b` with the given crossover and mutation rates.
+///
+/// Returns the child, and whether crossover and mutation were applied.
+fn breed<T, F, R>(
+    a: &T,
+    b: &T,
+    crossover: Option<&dyn CrossoverOperator<T>>,
+    mutator: Option<&dyn MutationOperator<T>>,
+    (crossover_rate, mutation_rate): (f64, f64),
+    rng: &mut R,
+) -> (T, bool, bool)
+where
+    T: Phenotype<F>,
+    F: Fitness,
+    R: Rng,
+{
+    let crossed = apply(crossover_rate, rng);
+    let mut child = if crossed {
+        match crossover {
+            Some(op) => op.crossover_with_rng(a, b, rng),
+            None => a.crossover_with_rng(b, rng),
+        }
+    } else {
+        a.clone()
+    };
+    let mutated = apply(mutation_rate, rng);
+    if mutated {
+        child = match mutator {
+            Some(op) => op.mutate_with_rng(&child, rng),
+            None => child.mutate_with_rng(rng),
+        };
+    }
+    (child, crossed, mutated)
+}
+
+/// A `Builder` for the `Simulator` type.
+#[derive(Debug)]
+pub struct SimulatorBuilder<'a, T, F, R = StdRng>
+where
+    T: 'a + Phenotype<F>,
+    F: Fitness,
+    R: Rng,
+{
+    sim: Simulator<'a, T, F, R>,
+}
+
+impl<'a, T, F> SimulatorBuilder<'a, T, F>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    /// Seed the random number generator of the resulting `Simulator`.
+    ///
+    /// See `with_seed` for details.
+    ///
+    /// Returns itself for chaining purposes.
+    #[deprecated(
+        note = "The consuming builder functions may be removed in a future release.
+                       Use the functions that start with `with_` instead.",
+        since = "1.8.0"
+    )]
+    pub fn set_seed(mut self, seed: u64) -> Self {
+        self.sim.rng = seeded_rng(seed);
+        self
+    }
+
+    /// Seed the random number generator of the resulting `Simulator`.
+    ///
+    /// Selection, replacement, the choice whether to apply crossover and mutation,
+    /// and the built-in operators all draw from this generator, so two runs with the
+    /// same seed and the same settings produce the same result. Phenotypes, custom
+    /// selectors, policies and operators are only covered if they implement the
+    /// `*_with_rng` methods, e.g. `Phenotype::mutate_with_rng`.
+    ///
+    /// This is only available for the default `StdRng`. Other generators are seeded
+    /// before they are passed to `Simulator::builder_with_rng`.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_seed(&mut self, seed: u64) -> &mut Self {
+        self.sim.rng = seeded_rng(seed);
+        self
+    }
+}
+
+impl<'a, T, F, R> SimulatorBuilder<'a, T, F, R>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+    R: Rng,
+{
+    /// Set the selector of the resulting `Simulator`.
+    ///
+    /// Returns itself for chaining purposes.
+    #[deprecated(
+        note = "The consuming builder functions may be removed in a future release.
+                       Use the functions that start with `with_` instead.",
+        since = "1.8.0"
+    )]
+    pub fn set_selector(mut self, sel: Box<dyn Selector<T, F>>) -> Self {
+        self.sim.selector = AnySelector::Stateless(sel);
+        self
+    }
+
+    /// Set the selector of the resulting `Simulator`.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_selector(&mut self, sel: Box<dyn Selector<T, F>>) -> &mut Self {
+        self.sim.selector = AnySelector::Stateless(sel);
+        self
+    }
+
+    /// Set a selector that keeps state between generations, such as a temperature
+    /// schedule or an adaptive selection pressure.
+    ///
+    /// This replaces the selector set with `with_selector`.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_stateful_selector(&mut self, sel: Box<dyn StatefulSelector<T, F>>) -> &mut Self {
+        self.sim.selector = AnySelector::Stateful(sel);
+        self
+    }
+
+    /// Set the maximum number of iterations of the resulting `Simulator`.
+    ///
+    /// The `Simulator` will stop running after this number of iterations.
+    ///
+    /// Returns itself for chaining purposes.
+    #[deprecated(
+        note = "The consuming builder functions may be removed in a future release.
+                       Use the functions that start with `with_` instead.",
+        since = "1.8.0"
+    )]
+    pub fn set_max_iters(mut self, i: u64) -> Self {
+        self.sim.iter_limit.set_max(i);
+        self
+    }
+
+    /// Set the maximum number of iterations of the resulting `Simulator`.
+    ///
+    /// The `Simulator` will stop running after this number of iterations.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_max_iters(&mut self, i: u64) -> &mut Self {
+        self.sim.iter_limit.set_max(i);
+        self
+    }
+
+    /// Set the minimum number of iterations of the resulting `Simulator`.
+    ///
+    /// Early stopping, the termination condition and the maximum number of evaluations
+    /// cannot end the simulation before this number of iterations has been executed.
+    /// This prevents premature exits caused by a lucky initial population. The maximum
+    /// number of iterations still takes precedence.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_min_iters(&mut self, i: u64) -> &mut Self {
+        self.sim.min_iters = i;
+        self
+    }
+
+    /// Set early stopping. If for `n_iters` iterations, the change in the highest fitness
+    /// is smaller than `delta`, the simulator will stop running.
+    ///
+    /// Calling this function multiple times adds multiple early stopping conditions.
+    /// The simulator stops as soon as any of them is met.
+    ///
+    /// Returns itself for chaining purposes.
+    #[deprecated(
+        note = "The consuming builder functions may be removed in a future release.
+                       Use the functions that start with `with_` instead.",
+        since = "1.8.0"
+    )]
+    pub fn set_early_stop(mut self, delta: F, n_iters: u64) -> Self {
+        self.sim
+            .earlystoppers
+            .push(EarlyStopper::new(delta, n_iters));
+        self
+    }
+
+    /// Set early stopping. If for `n_iters` iterations, the change in the highest fitness
+    /// is smaller than `delta`, the simulator will stop running.
+    ///
+    /// Calling this function multiple times adds multiple early stopping conditions.
+    /// The simulator stops as soon as any of them is met.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_early_stop(&mut self, delta: F, n_iters: u64) -> &mut Self {
+        self.sim
+            .earlystoppers
+            .push(EarlyStopper::new(delta, n_iters));
+        self
+    }
+
+    /// Stop the simulation once the genotype entropy of the population drops below `threshold`.
+    ///
+    /// Unlike early stopping, which only watches the best fitness, this detects that the
+    /// population has lost its genetic diversity. See `stats::genotype_entropy` for the
+    /// measure that is used. Like early stopping, it respects the minimum number of iterations.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_entropy_stop(&mut self, threshold: f64) -> &mut Self
+    where
+        T: Genome,
+        T::Gene: Hash + Eq,
+    {
+        self.sim.entropy_stop = Some(EntropyStop {
+            threshold,
+            entropy: genotype_entropy::<T>,
+        });
+        self
+    }
+
+    /// Set a callback that is called whenever the best individual seen so far improves.
+    ///
+    /// The callback receives the new best individual and the number of iterations
+    /// executed when it was found. It is first called for the best individual of
+    /// the initial population, with iteration `0`, and afterwards only when a step
+    /// produces a strictly fitter individual.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_improvement_callback<C>(&mut self, callback: C) -> &mut Self
+    where
+        C: FnMut(&T, u64) + 'a,
+    {
+        self.sim.improvement_callback = Some(Callback(Box::new(callback)));
+        self
+    }
+
+    /// Resume from a `Checkpoint`.
+    ///
+    /// The population of the checkpoint replaces the population of the builder, and
+    /// the number of iterations and evaluations continue from the checkpoint. The
+    /// maximum number of iterations still counts from the start of the original run.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_checkpoint(&mut self, checkpoint: Checkpoint<T>) -> &mut Self {
+        self.sim.iter_limit.set(checkpoint.iterations());
+        self.sim.evaluations = checkpoint.evaluations();
+        *self.sim.population = checkpoint.into_population();
+        self.sim.best = None;
+        self.sim.fitness = None;
+        self
+    }
+
+    /// Add an observer that is called with the population after every step.
+    ///
+    /// The observer receives the number of iterations executed, the population
+    /// and the fitness of every individual. Calling this function multiple times
+    /// adds multiple observers.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_observer(&mut self, observer: Box<dyn PopulationObserver<T, F> + 'a>) -> &mut Self {
+        self.sim.observers.push(observer);
+        self
+    }
+
+    /// Save a checkpoint to `writer` every `n` iterations.
+    ///
+    /// To keep the last few checkpoints rather than only the latest one, use a
+    /// `RotatingFileWriter`. If a checkpoint cannot be written, the simulator fails.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    #[cfg(feature = "serde")]
+    pub fn with_checkpoint_every<W>(&mut self, n: u64, mut writer: W) -> &mut Self
+    where
+        T: Serialize,
+        W: CheckpointWriter + 'a,
+    {
+        self.sim.auto_checkpoint = Some(AutoCheckpoint {
+            every: n,
+            save: Callback(Box::new(move |c: &Checkpoint<T>| c.save(&mut writer))),
+        });
+        self
+    }
+
+    /// Write the best individual seen so far to `writer` whenever it improves.
+    ///
+    /// The individual is written as JSON. Use an `AtomicFileWriter`, so that the
+    /// file always contains a complete individual, even if the process is killed
+    /// while writing. If the individual cannot be written, the simulator fails.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    #[cfg(feature = "serde")]
+    pub fn with_best_persistence<W>(&mut self, mut writer: W) -> &mut Self
+    where
+        T: Serialize,
+        W: CheckpointWriter + 'a,
+    {
+        self.sim.persist_best = Some(Callback(Box::new(move |best: &T| {
+            writer.write(&mut |out| {
+                serde_json::to_writer(out, best)?;
+                Ok(())
+            })
+        })));
+        self
+    }
+
+    /// Record the best fitness of every generation.
+    ///
+    /// The history can be retrieved with `Simulator::fitness_history`, and is part
+    /// of the `RunReport`.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_fitness_history(&mut self) -> &mut Self {
+        self.sim.history = Some(Vec::new());
+        self
+    }
+
+    /// Record how every child of the last `generations` generations is bred.
+    ///
+    /// For every child, its parents and the state of the random number generator are
+    /// kept, so the breeding of a single child can be repeated with
+    /// `Simulator::reproduce_child`, e.g. to debug why a degenerate child was created.
+    /// The traces can be inspected with `Simulator::breeding_traces`.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_breeding_trace(&mut self, generations: u64) -> &mut Self
+    where
+        R: Clone,
+    {
+        self.sim.trace_log = Some(TraceLog {
+            generations,
+            traces: Vec::new(),
+            snapshot: R::clone,
+        });
+        self
+    }
+
+    /// Keep a hall of fame of the `k` best distinct individuals ever seen.
+    ///
+    /// It can be retrieved with `Simulator::hall_of_fame` after the run.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_hall_of_fame(&mut self, k: usize) -> &mut Self
+    where
+        T: PartialEq,
+    {
+        self.sim.hall_of_fame = Some(HallOfFame::new(k));
+        self
+    }
+
+    /// Set a callback that is called after every step with a `GenerationInfo`.
+    ///
+    /// This makes it easy to report progress without stepping the simulator manually:
+    ///
+    /// ```ignore
+    /// builder.with_generation_callback(|g| println!("{}: {:?}", g.iteration, g.best_fitness));
+    /// ```
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_generation_callback<C>(&mut self, callback: C) -> &mut Self
+    where
+        C: FnMut(&GenerationInfo<T, F>) + 'a,
+    {
+        self.sim.generation_callback = Some(Callback(Box::new(callback)));
+        self
+    }
+
+    /// Exclude the worst individuals from the mating pool.
+    ///
+    /// Before selection, the bottom `q`-quantile of the population (ranked by fitness)
+    /// is removed from the pool the selector chooses parents from. These individuals
+    /// are not removed from the population and can still survive to the next iteration.
+    /// This increases selection pressure independently of the selector.
+    ///
+    /// * `q`: must be at least zero and less than one.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_mating_quantile_filter(&mut self, q: f64) -> &mut Self {
+        self.sim.mating_quantile = Some(q);
+        self
+    }
+
+    /// Set the crossover operator of the resulting `Simulator`.
+    ///
+    /// The operator replaces `Phenotype::crossover` when creating children, so that
+    /// recombination strategies can be swapped without changing the phenotype.
+    /// See the `crossover` module for the built-in operators.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_crossover(&mut self, op: Box<dyn CrossoverOperator<T>>) -> &mut Self {
+        self.sim.crossover = Some(op);
+        self
+    }
+
+    /// Set the probability that a child is created by crossover of its parents.
+    ///
+    /// Otherwise, the child is a copy of its first parent. A rate of `0.0` yields
+    /// mutation-only (asexual) reproduction. The default rate is `1.0`, i.e. every
+    /// child is the result of crossover.
+    ///
+    /// * `rate`: must be between zero and one, inclusive.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_crossover_rate(&mut self, rate: f64) -> &mut Self {
+        self.sim.crossover_rate = rate;
+        self
+    }
+
+    /// Set the mutation operator of the resulting `Simulator`.
+    ///
+    /// The operator replaces `Phenotype::mutate` when mutating children, so that
+    /// mutation can be configured without changing the phenotype. Wrap it in a
+    /// `mutation::WithProbability` to mutate only a fraction of the children.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_mutator(&mut self, op: Box<dyn MutationOperator<T>>) -> &mut Self {
+        self.sim.mutator = Some(op);
+        self
+    }
+
+    /// Set the probability that a child is mutated after it has been created.
+    ///
+    /// A rate of `0.0` disables mutation entirely, which together with
+    /// `with_crossover_rate` allows ablation studies without changing the phenotype.
+    /// The default rate is `1.0`, i.e. every child is mutated.
+    ///
+    /// * `rate`: must be between zero and one, inclusive.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_mutation_rate(&mut self, rate: f64) -> &mut Self {
+        self.sim.mutation_rate = rate;
+        self
+    }
+
+    /// Set the number of fittest individuals that survive every iteration unchanged.
+    ///
+    /// Without elitism, the best individual can be killed off at random to make room
+    /// for the children, so the highest fitness in the population may decrease.
+    /// With elitism, the `n` fittest individuals are protected from being killed off.
+    /// The default is `0`.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_elitism(&mut self, n: usize) -> &mut Self {
+        self.sim.elitism = n;
+        self
+    }
+
+    /// Set the maximum number of fitness evaluations of the resulting `Simulator`.
+    ///
+    /// The `Simulator` stops running once this number of evaluations is reached, see
+    /// `Simulator::evaluations`, but not before the minimum number of iterations. Since
+    /// a whole iteration is always completed, the number of evaluations can exceed the
+    /// maximum by less than the size of the population.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_max_evaluations(&mut self, n: u64) -> &mut Self {
+        self.sim.max_evaluations = Some(n);
+        self
+    }
+
+    /// Set the termination condition of the resulting `Simulator`.
+    ///
+    /// The condition is checked after every iteration, in addition to the maximum number
+    /// of iterations and any early stopping conditions. Use `termination::Any` and
+    /// `termination::All` to combine several conditions. Like early stopping, the
+    /// condition cannot end the simulation before the minimum number of iterations set
+    /// by `with_min_iters`. Because the condition sees the
+    /// fitness of every individual, setting one evaluates the whole population after
+    /// every iteration.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_termination(&mut self, termination: Box<dyn Termination<F>>) -> &mut Self {
+        self.sim.termination = Some(termination);
+        self
+    }
+
+    /// Set the replacement policy of the resulting `Simulator`.
+    ///
+    /// The policy decides which individuals are replaced by the children of every
+    /// iteration. The default is `replace::ReplaceRandom`.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_replacement(&mut self, policy: Box<dyn ReplacementPolicy<T, F>>) -> &mut Self {
+        self.sim.replacement = policy;
+        self
+    }
+
+    /// Set a warmup phase for the resulting `Simulator`.
+    ///
+    /// During the first `iterations` iterations, `selector` and `mutation_rate` are used
+    /// instead of the regular selector and mutation rate. Typically, the warmup phase
+    /// uses a higher mutation rate and a selector with lower selection pressure, so that
+    /// a cold-started population explores the search space before it is exploited.
+    ///
+    /// * `mutation_rate`: must be between zero and one, inclusive.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_warmup(
+        &mut self,
+        iterations: u64,
+        selector: Box<dyn Selector<T, F>>,
+        mutation_rate: f64,
+    ) -> &mut Self {
+        self.sim.warmup = Some(Warmup {
+            iterations,
+            selector: AnySelector::Stateless(selector),
+            mutation_rate,
+        });
+        self
+    }
+
+    /// Set a `BestHandle` that is kept up to date with the best individual seen so far.
+    ///
+    /// Clones of the handle can be read from other threads while the resulting
+    /// `Simulator` runs, e.g. to display live results.
+    ///
+    /// Returns a mutable reference to itself for chaining purposes.
+    /// Does not consume the builder.
+    pub fn with_best_handle(&mut self, handle: BestHandle<T>) -> &mut Self {
+        self.sim.best_handle = Some(handle);
+        self
+    }
+}
+
+/// Run a `Simulator` on a background thread.
+///
+/// The simulator takes ownership of `population` and is configured by calling
+/// `configure` with its builder on the worker thread. The returned
+/// `SimulationHandle` can pause, resume and stop the run, read the best individual
+/// found so far, and wait for the final population.
+///
+/// Any `BestHandle` set by `configure` is replaced by the handle's own.
+pub fn spawn<T, F, C>(population: Vec<T>, configure: C) -> SimulationHandle<T>
+where
+    T: Phenotype<F> + Send + Sync + 'static,
+    F: Fitness + 'static,
+    C: for<'b> FnOnce(&mut SimulatorBuilder<'b, T, F>) + Send + 'static,
+{
+    let control = Arc::new(Control::new());
+    let best = BestHandle::new();
+    let thread = {
+        let control = Arc::clone(&control);
+        let best = best.clone();
+        thread::spawn(move || {
+            let mut population = population;
+            {
+                let mut builder = Simulator::builder(&mut population);
+                configure(&mut builder);
+                builder.with_best_handle(best);
+                let mut sim = builder.build();
+                while control.proceed() {
+                    match sim.checked_step() {
+                        StepResult::Success => {}
+                        StepResult::Failure => {
+                            return Err(sim.error.expect("a failed step records its error"));
+                        }
+                        StepResult::Done => break,
+                    }
+                }
+            }
+            Ok(population)
+        })
+    };
+    SimulationHandle::new(control, best, thread)
+}
+
+impl<'a, T, F, R> Builder<Simulator<'a, T, F, R>> for SimulatorBuilder<'a, T, F, R>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+    R: Rng,
+{
+    fn build(self) -> Simulator<'a, T, F, R> {
+        self.sim
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use pheno::Phenotype;
+    use rand::Rng;
+    use sim::crossover::{CrossoverOperator, UniformCrossover};
+    use sim::mutation::{MutationOperator, SwapMutation};
+    use sim::select::*;
+    use sim::*;
+    use stats::{genotype_entropy, PopulationObserver};
+    use std::cell::Cell;
+    use std::time::Duration;
+    use test::Genes;
+    use test::MyFitness;
+    use test::Test;
+
+    #[test]
+    fn test_kill_off_count() {
+        let selector = MaximizeSelector::new(2);
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder(&mut population)
+            .set_selector(Box::new(selector))
+            .build();
+        s.kill_off(10);
+        assert_eq!(s.population.len(), 90);
+    }
+
+    #[test]
+    fn test_max_iters() {
+        let selector = MaximizeSelector::new(2);
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder(&mut population)
+            .set_selector(Box::new(selector))
+            .set_max_iters(2)
+            .build();
+        s.run();
+        assert!(s.iterations() <= 2);
+    }
+
+    #[test]
+    fn test_early_stopping() {
+        let selector = MaximizeSelector::new(2);
+        let mut population: Vec<Test> = (0..100).map(|_| Test { f: 0 }).collect();
+        let mut s = seq::Simulator::builder(&mut population)
+            .set_selector(Box::new(selector))
+            .set_early_stop(MyFitness { f: 10 }, 5)
+            .set_max_iters(10)
+            .build();
+        s.run();
+        assert!(s.iterations() <= 5);
+    }
+
+    #[test]
+    fn test_multiple_early_stoppers() {
+        let mut population: Vec<Test> = (0..100).map(|_| Test { f: 0 }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_early_stop(MyFitness { f: 10 }, 8)
+            .with_early_stop(MyFitness { f: 10 }, 3)
+            .with_max_iters(10);
+        let mut s = builder.build();
+        s.run();
+        assert_eq!(s.earlystoppers.len(), 2);
+        assert_eq!(s.iterations(), 3);
+    }
+
+    #[test]
+    fn test_min_iters() {
+        let mut population: Vec<Test> = (0..100).map(|_| Test { f: 0 }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_early_stop(MyFitness { f: 10 }, 3)
+            .with_min_iters(7)
+            .with_max_iters(10);
+        let mut s = builder.build();
+        s.run();
+        assert_eq!(s.iterations(), 7);
+    }
+
+    #[test]
+    fn test_entropy_stop() {
+        let mut population: Vec<Genes> = (0..100).map(|_| Genes { genes: vec![1, 2] }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_entropy_stop(0.1)
+            .with_max_iters(10);
+        let mut s = builder.build();
+        assert_eq!(s.genotype_entropy(), 0.0);
+        s.run();
+        assert_eq!(s.iterations(), 0);
+    }
+
+    #[test]
+    fn test_entropy_stop_diverse() {
+        let mut population: Vec<Genes> = (0..100).map(|i| Genes { genes: vec![i] }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_entropy_stop(0.1)
+            .with_max_iters(5);
+        let mut s = builder.build();
+        s.run();
+        assert_eq!(s.iterations(), 5);
+    }
+
+    #[test]
+    fn test_termination_state() {
+        let mut population: Vec<Test> = (0..100).map(|_| Test { f: 0 }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_max_iters(10);
+        let mut s = builder.build();
+        assert_eq!(s.stagnant_generations(), None);
+        assert_eq!(s.remaining_iterations(), 10);
+        s.run_for(4);
+        assert_eq!(s.remaining_iterations(), 6);
+    }
+
+    #[test]
+    fn test_stagnant_generations() {
+        let mut population: Vec<Test> = (0..100).map(|_| Test { f: 0 }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_early_stop(MyFitness { f: 10 }, 8)
+            .with_max_iters(10);
+        let mut s = builder.build();
+        assert_eq!(s.stagnant_generations(), Some(0));
+        s.run_for(3);
+        assert_eq!(s.stagnant_generations(), Some(3));
+    }
+
+    #[test]
     fn test_selector_error_propagate() {
         let selector = MaximizeSelector::new(0);
         let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
@@ -348,6 +1782,863 @@ mod tests {
         assert!(s.get().is_err());
     }
 
+    #[test]
+    fn test_improvement_callback() {
+        let mut improvements: Vec<(i64, u64)> = Vec::new();
+        {
+            let mut population: Vec<Test> = (0..100).map(|i| Test { f: -i }).collect();
+            let mut builder = seq::Simulator::builder(&mut population);
+            builder
+                .with_selector(Box::new(UnstableMaximizeSelector::new(10)))
+                .with_max_iters(5)
+                .with_improvement_callback(|best, iteration| {
+                    improvements.push((best.fitness().f, iteration))
+                });
+            let mut s = builder.build();
+            s.run();
+        }
+        assert_eq!(improvements[0], (99, 0));
+        for pair in improvements.windows(2) {
+            assert!(pair[1].0 > pair[0].0);
+            assert!(pair[1].1 > pair[0].1);
+        }
+    }
+
+    #[test]
+    fn test_best_handle() {
+        let handle = handle::BestHandle::new();
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: -i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(UnstableMaximizeSelector::new(10)))
+            .with_max_iters(5)
+            .with_best_handle(handle.clone());
+        let mut s = builder.build();
+        assert!(handle.get().is_none());
+        s.run();
+        let best = handle.get().unwrap();
+        assert!(best.fitness() >= s.get().unwrap().fitness());
+    }
+
+    #[test]
+    fn test_spawn_await() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let handle = seq::spawn(population, |b| {
+            b.with_selector(Box::new(UnstableMaximizeSelector::new(10)))
+                .with_max_iters(10);
+        });
+        let population = handle.await_result().unwrap();
+        assert_eq!(population.len(), 100);
+    }
+
+    #[test]
+    fn test_spawn_stop_while_paused() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let handle = seq::spawn(population, |b| {
+            b.with_selector(Box::new(UnstableMaximizeSelector::new(10)))
+                .with_max_iters(u64::MAX);
+        });
+        handle.pause();
+        handle.resume();
+        handle.pause();
+        handle.stop();
+        assert!(handle.await_result().is_ok());
+    }
+
+    #[test]
+    fn test_spawn_failure() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let handle = seq::spawn(population, |b| {
+            b.with_selector(Box::new(UnstableMaximizeSelector::new(0)));
+        });
+        assert!(handle.await_result().is_err());
+    }
+
+    #[test]
+    fn test_run_for() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(UnstableMaximizeSelector::new(10)))
+            .with_max_iters(5);
+        let mut s = builder.build();
+        let summary = s.run_for(3);
+        assert_eq!(summary.status, StepResult::Success);
+        assert_eq!(summary.steps, 3);
+        assert_eq!(summary.iterations, 3);
+        let summary = s.run_for(3);
+        assert_eq!(summary.status, StepResult::Done);
+        assert_eq!(summary.steps, 2);
+        assert_eq!(summary.iterations, 5);
+    }
+
+    #[test]
+    fn test_run_for_failure() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder.with_selector(Box::new(UnstableMaximizeSelector::new(0)));
+        let mut s = builder.build();
+        let summary = s.run_for(3);
+        assert_eq!(summary.status, StepResult::Failure);
+        assert_eq!(summary.steps, 0);
+    }
+
+    #[test]
+    fn test_timing() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_max_iters(3);
+        let mut s = builder.build();
+        assert_eq!(s.time(), Some(0));
+        assert_eq!(s.last_step_time(), None);
+        s.run();
+        let last = s.last_step_time().unwrap();
+        assert!(s.time().unwrap() >= last);
+    }
+
+    #[test]
+    fn test_run_for_duration() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(UnstableMaximizeSelector::new(10)))
+            .with_max_iters(5);
+        let mut s = builder.build();
+        let summary = s.run_for_duration(Duration::from_secs(60));
+        assert_eq!(summary.status, StepResult::Done);
+        assert_eq!(summary.iterations, 5);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let s = seq::Simulator::builder(&mut population).build();
+        let stats = s.stats().unwrap();
+        assert_eq!(stats.size, 100);
+        assert_eq!(stats.best.f, 99);
+        assert_eq!(stats.worst.f, 0);
+    }
+
+    #[test]
+    fn test_mating_quantile_filter() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder(&mut population)
+            .set_selector(Box::new(StochasticSelector::new(10)))
+            .build();
+        s.mating_quantile = Some(0.5);
+        let excluded = s.exclude_from_mating().unwrap();
+        assert_eq!(s.population.len(), 50);
+        assert!(s.population.iter().all(|x| x.f >= 50));
+        seq::restore_mating_pool(excluded, s.population, &mut s.fitness);
+        assert!(s
+            .population
+            .iter()
+            .enumerate()
+            .all(|(i, x)| x.f == i as i64));
+        s.run();
+        assert_eq!(s.population.len(), 100);
+    }
+
+    #[test]
+    fn test_mating_quantile_filter_invalid() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_mating_quantile_filter(1.0);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Failure);
+        assert!(s.get().is_err());
+    }
+
+    #[test]
+    fn test_best_index_cached() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_max_iters(5);
+        let mut s = builder.build();
+        assert_eq!(s.best, None);
+        assert_eq!(s.get().unwrap().f, 99);
+        s.run();
+        let best = s.best.unwrap();
+        let highest = s.population.iter().map(|x| x.fitness()).max().unwrap();
+        assert_eq!(s.population[best].fitness(), highest);
+        assert!(::std::ptr::eq(s.get().unwrap(), &s.population[best]));
+    }
+
+    #[derive(Clone, Copy)]
+    struct NoCrossover {
+        f: i64,
+    }
+
+    impl Phenotype<MyFitness> for NoCrossover {
+        fn fitness(&self) -> MyFitness {
+            MyFitness { f: self.f }
+        }
+
+        fn crossover(&self, _: &NoCrossover) -> NoCrossover {
+            panic!("crossover should not be called")
+        }
+
+        fn mutate(&self) -> NoCrossover {
+            NoCrossover { f: self.f + 1 }
+        }
+    }
+
+    #[test]
+    fn test_mutation_only() {
+        let mut population: Vec<NoCrossover> = (0..100).map(|i| NoCrossover { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_crossover_rate(0.0)
+            .with_max_iters(3);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+    }
+
+    #[derive(Debug)]
+    struct SumCrossover;
+
+    impl CrossoverOperator<NoCrossover> for SumCrossover {
+        fn crossover(&self, a: &NoCrossover, b: &NoCrossover) -> NoCrossover {
+            NoCrossover { f: a.f + b.f }
+        }
+    }
+
+    #[test]
+    fn test_crossover_operator() {
+        let mut population: Vec<NoCrossover> = (0..100).map(|i| NoCrossover { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_crossover(Box::new(SumCrossover))
+            .with_max_iters(3);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+        let counts = s.operator_counts();
+        assert_eq!(counts.crossovers, counts.children);
+    }
+
+    #[derive(Clone, Copy)]
+    struct NoMutation {
+        f: i64,
+    }
+
+    impl Phenotype<MyFitness> for NoMutation {
+        fn fitness(&self) -> MyFitness {
+            MyFitness { f: self.f }
+        }
+
+        fn crossover(&self, other: &NoMutation) -> NoMutation {
+            NoMutation {
+                f: self.f.max(other.f),
+            }
+        }
+
+        fn mutate(&self) -> NoMutation {
+            panic!("mutate should not be called")
+        }
+    }
+
+    #[derive(Debug)]
+    struct Reset;
+
+    impl MutationOperator<NoMutation> for Reset {
+        fn mutate(&self, _: &NoMutation) -> NoMutation {
+            NoMutation { f: 0 }
+        }
+    }
+
+    #[test]
+    fn test_mutation_operator() {
+        let mut population: Vec<NoMutation> = (0..100).map(|i| NoMutation { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_mutator(Box::new(Reset))
+            .with_max_iters(3);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+        // The children of the last step were all reset and are still in the population.
+        let children = s.operator_counts().children;
+        assert!(s.population().iter().filter(|x| x.f == 0).count() >= children);
+    }
+
+    #[test]
+    fn test_mutation_off() {
+        let mut population: Vec<NoMutation> = (0..100).map(|i| NoMutation { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_mutation_rate(0.0)
+            .with_max_iters(3);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+    }
+
+    #[test]
+    fn test_warmup() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_warmup(1, Box::new(StochasticSelector::new(20)), 0.0)
+            .with_max_iters(5);
+        let mut s = builder.build();
+        assert_eq!(s.checked_step(), StepResult::Success);
+        assert_eq!(s.operator_counts().children, 10);
+        assert_eq!(s.operator_counts().mutations, 0);
+        assert_eq!(s.checked_step(), StepResult::Success);
+        assert_eq!(s.operator_counts().children, 5);
+        assert_eq!(s.operator_counts().mutations, 5);
+    }
+
+    #[test]
+    fn test_warmup_ends() {
+        let mut population: Vec<NoMutation> = (0..100).map(|i| NoMutation { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(0)))
+            .with_mutation_rate(0.0)
+            .with_warmup(2, Box::new(StochasticSelector::new(10)), 0.0)
+            .with_max_iters(5);
+        let mut s = builder.build();
+        assert_eq!(s.checked_step(), StepResult::Success);
+        assert_eq!(s.checked_step(), StepResult::Success);
+        // After the warmup phase, the invalid regular selector is used.
+        assert_eq!(s.checked_step(), StepResult::Failure);
+    }
+
+    #[test]
+    fn test_operator_counts() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_crossover_rate(0.0)
+            .with_max_iters(3);
+        let mut s = builder.build();
+        assert_eq!(s.operator_counts().children, 0);
+        assert_eq!(s.checked_step(), StepResult::Success);
+        let counts = s.operator_counts();
+        assert_eq!(counts.children, 5);
+        assert_eq!(counts.crossover_rate(), Some(0.0));
+        assert_eq!(counts.mutation_rate(), Some(1.0));
+    }
+
+    #[test]
+    fn test_mutation_rate_invalid() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_mutation_rate(-0.5);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Failure);
+    }
+
+    #[test]
+    fn test_crossover_rate_invalid() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_crossover_rate(1.5);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Failure);
+    }
+
+    #[test]
+    fn test_elitism() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(90)))
+            .with_elitism(2);
+        let mut s = builder.build();
+        for _ in 0..20 {
+            s.checked_step();
+            let population = s.population();
+            assert_eq!(population.len(), 100);
+            assert!(population.iter().any(|x| x.f == 99));
+            assert!(population.iter().any(|x| x.f == 98));
+        }
+    }
+
+    #[test]
+    fn test_elitism_larger_than_population() {
+        let mut population: Vec<Test> = (0..10).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(4)))
+            .with_elitism(20)
+            .with_max_iters(5);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+        assert_eq!(s.population().len(), 10);
+    }
+
+    #[test]
+    fn test_replace_oldest_keeps_order() {
+        // The fittest individuals are the oldest ones.
+        let mut population: Vec<Test> = (0..100).rev().map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_replacement(Box::new(replace::ReplaceOldest::new()))
+            .with_mating_quantile_filter(0.5)
+            .with_elitism(2);
+        let mut s = builder.build();
+        s.checked_step();
+        // The elites keep their place, the next oldest are replaced and the other
+        // survivors keep their order, regardless of the mating quantile filter.
+        let survivors: Vec<i64> = s.population()[..95].iter().map(|x| x.f).collect();
+        let expected: Vec<i64> = vec![99, 98].into_iter().chain((0..93).rev()).collect();
+        assert_eq!(survivors, expected);
+    }
+
+    #[test]
+    fn test_replacement_policy() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_replacement(Box::new(replace::ReplaceWorst::new()))
+            .with_max_iters(20);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+        // Replacing the worst never loses the best individual.
+        assert!(s.population().iter().any(|x| x.f == 99));
+        assert_eq!(s.population().len(), 100);
+    }
+
+    #[test]
+    fn test_termination() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_termination(Box::new(termination::Any::new(vec![
+                Box::new(termination::MaxIterations::new(7)),
+                Box::new(termination::MaxDuration::new(Duration::from_secs(60))),
+            ])))
+            .with_max_iters(100);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+        assert_eq!(s.iterations(), 7);
+    }
+
+    #[test]
+    fn test_termination_min_iters() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_termination(Box::new(termination::TargetFitness::new(MyFitness {
+                f: 50,
+            })))
+            .with_min_iters(5)
+            .with_max_iters(100);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+        // The target is reached by the initial population.
+        assert_eq!(s.iterations(), 5);
+        assert_eq!(s.stop_reason(), Some(report::StopReason::Termination));
+    }
+
+    #[test]
+    fn test_max_evaluations() {
+        let mut population: Vec<Counted> = (0..10).map(Counted).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(4)))
+            .with_max_evaluations(35);
+        let mut s = builder.build();
+        assert_eq!(s.evaluations(), 0);
+        assert_eq!(s.run(), RunResult::Done);
+        // The initial population, then the whole population after every iteration.
+        assert_eq!(s.iterations(), 3);
+        assert_eq!(s.evaluations(), 40);
+        assert_eq!(EVALUATIONS.with(Cell::get), 40);
+    }
+
+    #[test]
+    fn test_early_stop_uses_cached_fitness() {
+        let mut population: Vec<Counted> = (0..10).map(Counted).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(4)))
+            .with_early_stop(MyFitness { f: 0 }, 100)
+            .with_improvement_callback(|_, _| {})
+            .with_max_iters(3);
+        let mut s = builder.build();
+        s.run();
+        assert_eq!(s.evaluations(), 40);
+        assert_eq!(EVALUATIONS.with(Cell::get), 40);
+    }
+
+    thread_local! {
+        /// The number of fitness evaluations of `Counted` made by the current test.
+        static EVALUATIONS: Cell<u64> = const { Cell::new(0) };
+    }
+
+    /// Counts the number of times its fitness is computed.
+    #[derive(Clone, Debug)]
+    struct Counted(i64);
+
+    impl Phenotype<MyFitness> for Counted {
+        fn fitness(&self) -> MyFitness {
+            EVALUATIONS.with(|n| n.set(n.get() + 1));
+            MyFitness { f: self.0 }
+        }
+
+        fn crossover(&self, other: &Counted) -> Counted {
+            Counted(self.0.min(other.0))
+        }
+
+        fn mutate(&self) -> Counted {
+            self.clone()
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct Diversity {
+        generations: Vec<u64>,
+        entropy: Vec<f64>,
+        best: Vec<Genes>,
+    }
+
+    impl PopulationObserver<Genes, MyFitness> for Diversity {
+        fn observe(&mut self, generation: u64, population: &[Genes], fitness: &[MyFitness]) {
+            assert_eq!(population.len(), fitness.len());
+            let best = (0..fitness.len()).max_by_key(|&i| fitness[i]).unwrap();
+            self.generations.push(generation);
+            self.entropy.push(genotype_entropy(population));
+            self.best.push(population[best].clone());
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        let mut diversity = Diversity::default();
+        let mut population: Vec<Genes> = (0..20)
+            .map(|i| Genes {
+                genes: vec![i % 2, i % 3],
+            })
+            .collect();
+        {
+            let mut builder = seq::Simulator::builder(&mut population);
+            builder
+                .with_selector(Box::new(StochasticSelector::new(4)))
+                .with_observer(Box::new(&mut diversity))
+                .with_max_iters(3);
+            builder.build().run();
+        }
+        assert_eq!(diversity.generations, vec![1, 2, 3]);
+        assert_eq!(diversity.entropy.len(), 3);
+        assert_eq!(diversity.best.len(), 3);
+    }
+
+    #[test]
+    fn test_generation_callback() {
+        let mut iterations = Vec::new();
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        {
+            let mut builder = seq::Simulator::builder(&mut population);
+            builder
+                .with_selector(Box::new(StochasticSelector::new(10)))
+                .with_generation_callback(|g| {
+                    assert_eq!(g.best.fitness(), *g.best_fitness);
+                    assert_eq!(g.stats().best, *g.best_fitness);
+                    assert_eq!(g.fitness.len(), 100);
+                    iterations.push(g.iteration);
+                })
+                .with_max_iters(4);
+            builder.build().run();
+        }
+        assert_eq!(iterations, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_hall_of_fame() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_hall_of_fame(5)
+            .with_max_iters(20);
+        let mut s = builder.build();
+        s.run();
+        // Every operator of `Test` moves towards zero, so the initial best individuals remain.
+        let hall: Vec<i64> = s
+            .hall_of_fame()
+            .unwrap()
+            .members()
+            .iter()
+            .map(|m| m.0.f)
+            .collect();
+        assert_eq!(hall, vec![99, 98, 97, 96, 95]);
+    }
+
+    #[test]
+    fn test_seed() {
+        let run = |seed: u64| {
+            let mut population: Vec<Genes> = (0..50)
+                .map(|i| Genes {
+                    genes: (0..10).map(|j| (i * j) % 7).collect(),
+                })
+                .collect();
+            {
+                let mut builder = seq::Simulator::builder(&mut population);
+                builder
+                    .with_selector(Box::new(TournamentSelector::new_checked(10, 3).unwrap()))
+                    .with_crossover(Box::new(UniformCrossover::new()))
+                    .with_mutator(Box::new(SwapMutation::new()))
+                    .with_crossover_rate(0.8)
+                    .with_mutation_rate(0.5)
+                    .with_seed(seed)
+                    .with_max_iters(20);
+                builder.build().run();
+            }
+            population
+        };
+        assert_eq!(run(42), run(42));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_set_seed() {
+        let mut first: Vec<Test> = (0..50).map(|i| Test { f: i % 7 }).collect();
+        let mut second = first.clone();
+        seq::Simulator::builder(&mut first)
+            .set_selector(Box::new(StochasticSelector::new(10)))
+            .set_seed(7)
+            .set_max_iters(10)
+            .build()
+            .run();
+        {
+            let mut builder = seq::Simulator::builder(&mut second);
+            builder
+                .with_selector(Box::new(StochasticSelector::new(10)))
+                .with_seed(7)
+                .with_max_iters(10);
+            builder.build().run();
+        }
+        assert_eq!(first, second);
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Noisy {
+        f: i64,
+    }
+
+    impl Phenotype<MyFitness> for Noisy {
+        fn fitness(&self) -> MyFitness {
+            MyFitness { f: self.f }
+        }
+
+        fn crossover(&self, _: &Noisy) -> Noisy {
+            panic!("crossover should not be called")
+        }
+
+        fn mutate(&self) -> Noisy {
+            panic!("mutate should not be called")
+        }
+
+        fn crossover_with_rng(&self, other: &Noisy, mut rng: &mut dyn Rng) -> Noisy {
+            let rng = &mut rng;
+            if rng.gen() {
+                self.clone()
+            } else {
+                other.clone()
+            }
+        }
+
+        fn mutate_with_rng(&self, mut rng: &mut dyn Rng) -> Noisy {
+            let rng = &mut rng;
+            Noisy {
+                f: self.f + rng.gen_range(-5, 6),
+            }
+        }
+    }
+
+    #[test]
+    fn test_seed_phenotype() {
+        let run = |seed: u64| {
+            let mut population: Vec<Noisy> = (0..50).map(|i| Noisy { f: i }).collect();
+            {
+                let mut builder = seq::Simulator::builder(&mut population);
+                builder
+                    .with_selector(Box::new(StochasticSelector::new(10)))
+                    .with_seed(seed)
+                    .with_max_iters(20);
+                assert_eq!(builder.build().run(), RunResult::Done);
+            }
+            population
+        };
+        assert_eq!(run(7), run(7));
+    }
+
+    #[test]
+    fn test_reproduce_child() {
+        let mut population: Vec<Genes> = (0..50)
+            .map(|i| Genes {
+                genes: (0..10).map(|j| (i * j) % 7).collect(),
+            })
+            .collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_crossover(Box::new(UniformCrossover::new()))
+            .with_mutator(Box::new(SwapMutation::new()))
+            .with_crossover_rate(0.8)
+            .with_mutation_rate(0.5)
+            .with_breeding_trace(2)
+            .with_max_iters(5);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+        // Only the children of the last two generations are kept.
+        assert_eq!(s.breeding_traces().len(), 10);
+        for trace in s.breeding_traces() {
+            assert!(trace.id().generation >= 4);
+            assert_eq!(&s.reproduce_child(trace.id()).unwrap(), trace.child());
+        }
+        let id = seq::TraceId {
+            generation: 1,
+            child: 0,
+        };
+        assert!(s.reproduce_child(id).is_err());
+    }
+
+    /// Selects the best individuals a limited number of times.
+    #[derive(Debug)]
+    struct Limited {
+        left: u32,
+    }
+
+    impl StatefulSelector<Test, MyFitness> for Limited {
+        fn select_mut<'a>(
+            &mut self,
+            context: SelectionContext<'a, '_, Test, MyFitness>,
+        ) -> Result<Parents<&'a Test>, Error> {
+            if self.left == 0 {
+                return Err(Error::SelectorFailure(String::from("No selections left.")));
+            }
+            self.left -= 1;
+            UnstableMaximizeSelector::new(10).select_from(context)
+        }
+    }
+
+    #[test]
+    fn test_stateful_selector() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_stateful_selector(Box::new(Limited { left: 3 }))
+            .with_max_iters(10);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Failure);
+        assert_eq!(s.iterations(), 3);
+    }
+
+    #[test]
+    fn test_report() {
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_fitness_history()
+            .with_max_iters(5);
+        let mut s = builder.build();
+        assert_eq!(s.stop_reason(), None);
+        assert_eq!(s.run(), RunResult::Done);
+        assert_eq!(s.stop_reason(), Some(report::StopReason::MaxIterations));
+        let report = s.report();
+        assert_eq!(report.stop_reason, Some(report::StopReason::MaxIterations));
+        assert_eq!(report.iterations, 5);
+        assert_eq!(report.history.len(), 5);
+        assert_eq!(report.best, s.get().ok().cloned());
+        assert_eq!(report.best_fitness, report.best.map(|b| b.fitness()));
+        assert!(report.settings.iter().any(|(k, _)| k == "selector"));
+        assert!(report.to_string().contains("maximum number of iterations"));
+    }
+
+    #[test]
+    fn test_report_early_stop() {
+        let mut population: Vec<Test> = (0..100).map(|_| Test { f: 0 }).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_early_stop(MyFitness { f: 10 }, 3)
+            .with_max_iters(10);
+        let mut s = builder.build();
+        s.run();
+        assert_eq!(s.stop_reason(), Some(report::StopReason::EarlyStop));
+        assert!(s.report().history.is_empty());
+    }
+
+    #[test]
+    fn test_custom_rng() {
+        use rand::{SeedableRng, XorShiftRng};
+        let run = || {
+            let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+            {
+                let rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+                let mut builder = seq::Simulator::builder_with_rng(&mut population, rng);
+                builder
+                    .with_selector(Box::new(StochasticSelector::new(10)))
+                    .with_max_iters(10);
+                assert_eq!(builder.build().run(), RunResult::Done);
+            }
+            population
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn test_random_seeded_rng() {
+        use rand::XorShiftRng;
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut builder =
+            <seq::Simulator<Test, MyFitness, XorShiftRng> as Simulation<_, _>>::builder(
+                &mut population,
+            );
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_max_iters(5);
+        let mut s = builder.build();
+        assert_eq!(s.run(), RunResult::Done);
+        assert_eq!(s.iterations(), 5);
+    }
+
+    #[test]
+    fn test_fitness_cached() {
+        let mut population: Vec<Counted> = (0..100).map(Counted).collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(TournamentSelector::new_checked(10, 3).unwrap()))
+            .with_elitism(5)
+            .with_mating_quantile_filter(0.5)
+            .with_early_stop(MyFitness { f: 1 }, 50)
+            .with_improvement_callback(|_, _| {})
+            .with_termination(Box::new(termination::MaxIterations::new(100)));
+        let mut s = builder.build();
+        s.checked_step();
+        let before = EVALUATIONS.with(Cell::get);
+        s.stats().unwrap();
+        s.checked_step();
+        // The selector, the mating quantile filter, elitism, early stopping, the
+        // improvement callback, the termination condition and the statistics share
+        // the fitness of the population. So every individual is evaluated once per step.
+        assert_eq!(EVALUATIONS.with(Cell::get) - before, 100);
+        assert_eq!(s.evaluations(), EVALUATIONS.with(Cell::get));
+    }
+
     #[test]
     fn test_population_get() {
         let selector = MaximizeSelector::new(0);
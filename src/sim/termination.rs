@@ -0,0 +1,314 @@
+// file: termination.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The termination module provides a trait that can be implemented to decide when a
+//! simulation stops, and the common stopping conditions.
+//!
+//! Conditions can be combined with `Any` and `All`, e.g. to stop after 10000 evaluations,
+//! 30 seconds or 50 iterations without improvement, whichever comes first:
+//!
+//! ```
+//! use rsgenetic::sim::termination::*;
+//! use std::time::Duration;
+//!
+//! let termination: Any<i32> = Any::new(vec![
+//!     Box::new(MaxEvaluations::new(10000)),
+//!     Box::new(MaxDuration::new(Duration::from_secs(30))),
+//!     Box::new(Stagnation::new(1, 50)),
+//! ]);
+//! ```
+//!
+//! A condition is configured on a `seq::SimulatorBuilder` using `with_termination`.
+
+use super::earlystopper::EarlyStopper;
+use pheno::Fitness;
+use std::fmt;
+use std::time::Duration;
+
+/// The state of a simulation after an iteration, as seen by a `Termination`.
+#[derive(Debug)]
+pub struct TerminationState<'s, F: 's> {
+    /// The number of iterations executed so far.
+    pub iterations: u64,
+    /// The number of fitness evaluations made so far.
+    pub evaluations: u64,
+    /// The total time spent in the iterations so far.
+    pub elapsed: Duration,
+    /// The highest fitness in the population.
+    pub best: &'s F,
+    /// The fitness of every individual in the population.
+    pub fitness: &'s [F],
+}
+
+/// A `Termination` decides when a simulation stops.
+///
+/// Conditions must be `Send` and `Sync`, so that boxed conditions can be shared
+/// between threads.
+pub trait Termination<F: Fitness>: fmt::Debug + Send + Sync {
+    /// Return whether the simulation should stop, given its state after an iteration.
+    ///
+    /// This is called exactly once after every iteration, so conditions can keep track
+    /// of the history of the simulation.
+    fn should_stop(&mut self, state: &TerminationState<F>) -> bool;
+}
+
+/// Stops after a number of iterations.
+#[derive(Clone, Copy, Debug)]
+pub struct MaxIterations {
+    iterations: u64,
+}
+
+impl MaxIterations {
+    /// Create a condition that stops after `iterations` iterations.
+    pub fn new(iterations: u64) -> MaxIterations {
+        MaxIterations { iterations }
+    }
+}
+
+impl<F: Fitness> Termination<F> for MaxIterations {
+    fn should_stop(&mut self, state: &TerminationState<F>) -> bool {
+        state.iterations >= self.iterations
+    }
+}
+
+/// Stops after a number of fitness evaluations.
+#[derive(Clone, Copy, Debug)]
+pub struct MaxEvaluations {
+    evaluations: u64,
+}
+
+impl MaxEvaluations {
+    /// Create a condition that stops after `evaluations` fitness evaluations.
+    pub fn new(evaluations: u64) -> MaxEvaluations {
+        MaxEvaluations { evaluations }
+    }
+}
+
+impl<F: Fitness> Termination<F> for MaxEvaluations {
+    fn should_stop(&mut self, state: &TerminationState<F>) -> bool {
+        state.evaluations >= self.evaluations
+    }
+}
+
+/// Stops once the iterations have taken a given amount of time.
+#[derive(Clone, Copy, Debug)]
+pub struct MaxDuration {
+    duration: Duration,
+}
+
+impl MaxDuration {
+    /// Create a condition that stops once the iterations have taken `duration` in total.
+    pub fn new(duration: Duration) -> MaxDuration {
+        MaxDuration { duration }
+    }
+}
+
+impl<F: Fitness> Termination<F> for MaxDuration {
+    fn should_stop(&mut self, state: &TerminationState<F>) -> bool {
+        state.elapsed >= self.duration
+    }
+}
+
+/// Stops if for `n_iters` iterations, the change in the highest fitness is smaller
+/// than `delta`.
+pub struct Stagnation<F: Fitness> {
+    stopper: EarlyStopper<F>,
+}
+
+impl<F: Fitness> Stagnation<F> {
+    /// Create a condition that stops after `n_iters` iterations without a change of
+    /// at least `delta` in the highest fitness.
+    pub fn new(delta: F, n_iters: u64) -> Stagnation<F> {
+        Stagnation {
+            stopper: EarlyStopper::new(delta, n_iters),
+        }
+    }
+}
+
+impl<F> Termination<F> for Stagnation<F>
+where
+    F: Fitness + Send + Sync,
+{
+    fn should_stop(&mut self, state: &TerminationState<F>) -> bool {
+        self.stopper.update(state.best.clone());
+        self.stopper.reached()
+    }
+}
+
+impl<F: Fitness> fmt::Debug for Stagnation<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Stagnation")
+            .field("stagnant", &self.stopper.stagnant())
+            .finish()
+    }
+}
+
+/// Stops once the highest fitness reaches a target.
+pub struct TargetFitness<F> {
+    target: F,
+}
+
+impl<F> TargetFitness<F> {
+    /// Create a condition that stops once the highest fitness is at least `target`.
+    pub fn new(target: F) -> TargetFitness<F> {
+        TargetFitness { target }
+    }
+}
+
+impl<F> Termination<F> for TargetFitness<F>
+where
+    F: Fitness + Send + Sync,
+{
+    fn should_stop(&mut self, state: &TerminationState<F>) -> bool {
+        *state.best >= self.target
+    }
+}
+
+impl<F> fmt::Debug for TargetFitness<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("TargetFitness")
+    }
+}
+
+/// Stops as soon as any of its conditions is met.
+///
+/// Every condition is checked after every iteration, so that all of them can keep
+/// track of the history of the simulation.
+pub struct Any<F> {
+    conditions: Vec<Box<dyn Termination<F>>>,
+}
+
+impl<F: Fitness> Any<F> {
+    /// Create a condition that is met when any of `conditions` is met.
+    pub fn new(conditions: Vec<Box<dyn Termination<F>>>) -> Any<F> {
+        Any { conditions }
+    }
+}
+
+impl<F: Fitness> Termination<F> for Any<F> {
+    fn should_stop(&mut self, state: &TerminationState<F>) -> bool {
+        let mut stop = false;
+        for c in &mut self.conditions {
+            stop |= c.should_stop(state);
+        }
+        stop
+    }
+}
+
+impl<F: Fitness> fmt::Debug for Any<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Any").field(&self.conditions).finish()
+    }
+}
+
+/// Stops as soon as all of its conditions are met at the same time.
+///
+/// Every condition is checked after every iteration, so that all of them can keep
+/// track of the history of the simulation.
+pub struct All<F> {
+    conditions: Vec<Box<dyn Termination<F>>>,
+}
+
+impl<F: Fitness> All<F> {
+    /// Create a condition that is met when all of `conditions` are met.
+    pub fn new(conditions: Vec<Box<dyn Termination<F>>>) -> All<F> {
+        All { conditions }
+    }
+}
+
+impl<F: Fitness> Termination<F> for All<F> {
+    fn should_stop(&mut self, state: &TerminationState<F>) -> bool {
+        let mut stop = true;
+        for c in &mut self.conditions {
+            stop &= c.should_stop(state);
+        }
+        stop
+    }
+}
+
+impl<F: Fitness> fmt::Debug for All<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("All").field(&self.conditions).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test::MyFitness;
+
+    fn check<T: Termination<MyFitness>>(t: &mut T, iterations: u64, best: i64) -> bool {
+        let best = MyFitness { f: best };
+        t.should_stop(&TerminationState {
+            iterations,
+            evaluations: 10 * iterations,
+            elapsed: Duration::from_secs(iterations),
+            best: &best,
+            fitness: &[],
+        })
+    }
+
+    #[test]
+    fn test_max_iterations() {
+        let mut t = MaxIterations::new(3);
+        assert!(!check(&mut t, 2, 0));
+        assert!(check(&mut t, 3, 0));
+    }
+
+    #[test]
+    fn test_max_evaluations() {
+        let mut t = MaxEvaluations::new(30);
+        assert!(!check(&mut t, 2, 0));
+        assert!(check(&mut t, 3, 0));
+    }
+
+    #[test]
+    fn test_max_duration() {
+        let mut t = MaxDuration::new(Duration::from_secs(5));
+        assert!(!check(&mut t, 4, 0));
+        assert!(check(&mut t, 5, 0));
+    }
+
+    #[test]
+    fn test_stagnation() {
+        let mut t = Stagnation::new(MyFitness { f: 1 }, 2);
+        assert!(!check(&mut t, 1, 0));
+        assert!(check(&mut t, 2, 0));
+    }
+
+    #[test]
+    fn test_target_fitness() {
+        let mut t = TargetFitness::new(MyFitness { f: 10 });
+        assert!(!check(&mut t, 1, 9));
+        assert!(check(&mut t, 2, 10));
+    }
+
+    #[test]
+    fn test_any_all() {
+        let conditions = || -> Vec<Box<dyn Termination<MyFitness>>> {
+            vec![
+                Box::new(MaxIterations::new(5)),
+                Box::new(TargetFitness::new(MyFitness { f: 10 })),
+            ]
+        };
+        let mut any = Any::new(conditions());
+        let mut all = All::new(conditions());
+        assert!(!check(&mut any, 1, 0));
+        assert!(check(&mut any, 1, 10));
+        assert!(!check(&mut all, 1, 10));
+        assert!(check(&mut all, 5, 10));
+    }
+}
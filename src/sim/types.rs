@@ -31,8 +31,10 @@
 //! * `u32`
 //! * `u64`
 //! * `usize`
+//!
+//! These types also implement `NumericFitness`.
 
-use pheno::Fitness;
+use pheno::{Fitness, NumericFitness};
 
 macro_rules! implement_fitness_int {
     ( $($t:ty),* ) => {
@@ -50,6 +52,12 @@ macro_rules! implement_fitness_int {
                     }
                 }
             }
+
+            impl NumericFitness for $t {
+                fn to_f64(&self) -> f64 {
+                    *self as f64
+                }
+            }
         )*
     }
 }
@@ -0,0 +1,977 @@
+// file: stats.rs
+//
+// Copyright 2015-2017 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides statistics about populations.
+//!
+//! A `PopulationStats` value summarizes the fitness values of a population:
+//! the best and worst values, the quartiles, the mean and the standard deviation.
+//! It is computed in one pass over the population, so it is the preferred way
+//! to obtain several of these aggregates at once.
+//!
+//! `OperatorCounts` reports how many children actually underwent crossover
+//! and mutation in an iteration, which makes the effective operator rates visible.
+//!
+//! `CacheStats` reports how often a `pheno::FitnessCache` avoided an evaluation.
+//!
+//! `genotype_entropy` measures the genetic diversity of a population of `Genome`s,
+//! and `AlleleFrequencies` records how the allele frequencies at every locus
+//! change over the generations.
+//!
+//! A `PopulationObserver` is called by the sequential simulator after every
+//! generation with the individuals and their fitness values, so it can record
+//! anything that depends on the genomes, such as the diversity of the population.
+//! `SummaryStats`, `BestSoFar` and `HistogramStats` are ready-made observers that
+//! keep a per-generation history of the fitness values. A `HallOfFame` keeps the
+//! best distinct individuals ever observed.
+//!
+//! A `RunDigest` is an observer that records a hash of the best fitness of every
+//! generation, so a seeded run can be checked against a stored digest after changing
+//! operators or upgrading this crate.
+
+use error::Error;
+use pheno::{Fitness, Genome, NumericFitness, Phenotype};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::str::FromStr;
+
+/// Summary statistics of the fitness values of a population.
+///
+/// Quartiles are computed using the nearest-rank method, so they are always
+/// fitness values that occur in the population.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PopulationStats<F> {
+    /// The number of individuals in the population.
+    pub size: usize,
+    /// The highest fitness value.
+    pub best: F,
+    /// The lowest fitness value.
+    pub worst: F,
+    /// The first quartile of the fitness values.
+    pub lower_quartile: F,
+    /// The median of the fitness values.
+    pub median: F,
+    /// The third quartile of the fitness values.
+    pub upper_quartile: F,
+    /// The mean fitness value.
+    pub mean: f64,
+    /// The (population) standard deviation of the fitness values.
+    pub std_dev: f64,
+}
+
+impl<F> PopulationStats<F>
+where
+    F: NumericFitness,
+{
+    /// Compute the statistics of a list of fitness values.
+    ///
+    /// Returns `None` if `values` is empty.
+    pub fn from_fitness(mut values: Vec<F>) -> Option<PopulationStats<F>> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort();
+        let size = values.len();
+        let n = size as f64;
+        let mean = values.iter().map(|x| x.to_f64()).sum::<f64>() / n;
+        let variance = values
+            .iter()
+            .map(|x| {
+                let d = x.to_f64() - mean;
+                d * d
+            })
+            .sum::<f64>()
+            / n;
+        let rank = |q: f64| (q * (size - 1) as f64).round() as usize;
+        Some(PopulationStats {
+            size,
+            best: values[size - 1].clone(),
+            worst: values[0].clone(),
+            lower_quartile: values[rank(0.25)].clone(),
+            median: values[rank(0.5)].clone(),
+            upper_quartile: values[rank(0.75)].clone(),
+            mean,
+            std_dev: variance.sqrt(),
+        })
+    }
+
+    /// Compute the statistics of a population.
+    ///
+    /// The fitness of every individual is evaluated exactly once.
+    /// Returns `None` if `population` is empty.
+    pub fn from_population<T>(population: &[T]) -> Option<PopulationStats<F>>
+    where
+        T: Phenotype<F>,
+    {
+        PopulationStats::from_fitness(population.iter().map(|x| x.fitness()).collect())
+    }
+}
+
+/// The number of times the genetic operators were applied during one iteration.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OperatorCounts {
+    /// The number of children that were created.
+    pub children: usize,
+    /// The number of children that were created by crossover.
+    pub crossovers: usize,
+    /// The number of children that were mutated.
+    pub mutations: usize,
+}
+
+impl OperatorCounts {
+    /// Get the fraction of children that were created by crossover,
+    /// or `None` if no children were created.
+    pub fn crossover_rate(&self) -> Option<f64> {
+        self.rate(self.crossovers)
+    }
+
+    /// Get the fraction of children that were mutated,
+    /// or `None` if no children were created.
+    pub fn mutation_rate(&self) -> Option<f64> {
+        self.rate(self.mutations)
+    }
+
+    fn rate(&self, count: usize) -> Option<f64> {
+        if self.children == 0 {
+            None
+        } else {
+            Some(count as f64 / self.children as f64)
+        }
+    }
+}
+
+/// The number of hits and misses of a `pheno::FitnessCache`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of fitness values that were found in the cache.
+    pub hits: u64,
+    /// The number of fitness values that had to be evaluated.
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Get the fraction of lookups that were hits,
+    /// or `None` if there were no lookups.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            None
+        } else {
+            Some(self.hits as f64 / total as f64)
+        }
+    }
+}
+
+/// Calculate the genotype entropy of a population of `Genome`s.
+///
+/// For every locus, the Shannon entropy (in bits) of the allele frequencies at that
+/// locus is computed. The result is the mean over all loci. It is zero when every
+/// individual has the same genes, and for bit strings it is at most one. A value that
+/// approaches zero indicates that the population has converged.
+///
+/// For genomes of different lengths, each locus only counts the individuals that have it.
+/// Returns `0.0` if the population is empty.
+pub fn genotype_entropy<T>(population: &[T]) -> f64
+where
+    T: Genome,
+    T::Gene: Hash + Eq,
+{
+    let loci = population
+        .iter()
+        .map(|x| x.genes().len())
+        .max()
+        .unwrap_or(0);
+    if loci == 0 {
+        return 0.0;
+    }
+    let mut total = 0.0;
+    for locus in 0..loci {
+        let mut counts: HashMap<&T::Gene, usize> = HashMap::new();
+        let mut n = 0;
+        for gene in population.iter().filter_map(|x| x.genes().get(locus)) {
+            *counts.entry(gene).or_insert(0) += 1;
+            n += 1;
+        }
+        total -= counts
+            .values()
+            .map(|&c| {
+                let p = c as f64 / n as f64;
+                p * p.log2()
+            })
+            .sum::<f64>();
+    }
+    total / loci as f64
+}
+
+/// Collects per-locus allele frequencies of a population of `Genome`s over generations.
+///
+/// Call `record` once per generation, e.g. after every step of a simulator.
+/// The history of any allele can then be exported as a matrix, and loci where
+/// a single allele has taken over can be detected to diagnose premature fixation.
+#[derive(Clone, Debug)]
+pub struct AlleleFrequencies<G>
+where
+    G: Hash + Eq,
+{
+    /// For every generation, the frequency of each allele at each locus.
+    history: Vec<Vec<HashMap<G, f64>>>,
+}
+
+impl<G> AlleleFrequencies<G>
+where
+    G: Hash + Eq + Clone,
+{
+    /// Create a new, empty collector.
+    pub fn new() -> AlleleFrequencies<G> {
+        AlleleFrequencies {
+            history: Vec::new(),
+        }
+    }
+
+    /// Record the allele frequencies of `population` as the next generation.
+    ///
+    /// For genomes of different lengths, each locus only counts the individuals that have it.
+    pub fn record<T>(&mut self, population: &[T])
+    where
+        T: Genome<Gene = G>,
+    {
+        let loci = population
+            .iter()
+            .map(|x| x.genes().len())
+            .max()
+            .unwrap_or(0);
+        let generation = (0..loci)
+            .map(|locus| {
+                let mut counts: HashMap<G, usize> = HashMap::new();
+                let mut n = 0;
+                for gene in population.iter().filter_map(|x| x.genes().get(locus)) {
+                    *counts.entry(gene.clone()).or_insert(0) += 1;
+                    n += 1;
+                }
+                counts
+                    .into_iter()
+                    .map(|(gene, c)| (gene, c as f64 / n as f64))
+                    .collect()
+            })
+            .collect();
+        self.history.push(generation);
+    }
+
+    /// Get the number of recorded generations.
+    pub fn generations(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Get the frequency of `allele` at `locus` in generation `generation`.
+    ///
+    /// Returns `0.0` if the allele did not occur there, or if nothing was recorded for it.
+    pub fn frequency(&self, generation: usize, locus: usize, allele: &G) -> f64 {
+        self.history
+            .get(generation)
+            .and_then(|loci| loci.get(locus))
+            .and_then(|freqs| freqs.get(allele))
+            .cloned()
+            .unwrap_or(0.0)
+    }
+
+    /// Export the history of `allele` as a matrix, with one row per generation
+    /// and one column per locus.
+    ///
+    /// All rows have the same length, which is the largest number of loci
+    /// recorded in any generation.
+    pub fn matrix(&self, allele: &G) -> Vec<Vec<f64>> {
+        let loci = self.history.iter().map(|x| x.len()).max().unwrap_or(0);
+        (0..self.history.len())
+            .map(|g| (0..loci).map(|l| self.frequency(g, l, allele)).collect())
+            .collect()
+    }
+
+    /// Get the loci at which a single allele occurs in the latest generation.
+    pub fn fixed_loci(&self) -> Vec<usize> {
+        match self.history.last() {
+            Some(loci) => loci
+                .iter()
+                .enumerate()
+                .filter(|&(_, freqs)| freqs.len() == 1)
+                .map(|(locus, _)| locus)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl<G> Default for AlleleFrequencies<G>
+where
+    G: Hash + Eq + Clone,
+{
+    fn default() -> Self {
+        AlleleFrequencies::new()
+    }
+}
+
+/// Observes the population of a simulation after every generation.
+///
+/// Observers are added with `sim::seq::SimulatorBuilder::with_observer`. Since the
+/// simulator only borrows an observer for the lifetime of the population, you can
+/// pass `Box::new(&mut observer)` and inspect `observer` after the run.
+pub trait PopulationObserver<T, F>: Debug {
+    /// Called after generation `generation` has been created.
+    ///
+    /// `fitness` contains the fitness of every individual in `population`, in order.
+    fn observe(&mut self, generation: u64, population: &[T], fitness: &[F]);
+}
+
+impl<T, F, O> PopulationObserver<T, F> for &mut O
+where
+    O: PopulationObserver<T, F> + ?Sized,
+{
+    fn observe(&mut self, generation: u64, population: &[T], fitness: &[F]) {
+        (**self).observe(generation, population, fitness)
+    }
+}
+
+/// Records the `PopulationStats` of every generation.
+#[derive(Clone, Debug, Default)]
+pub struct SummaryStats<F> {
+    history: Vec<PopulationStats<F>>,
+}
+
+impl<F> SummaryStats<F> {
+    /// Create an observer with an empty history.
+    pub fn new() -> SummaryStats<F> {
+        SummaryStats {
+            history: Vec::new(),
+        }
+    }
+
+    /// Get the statistics of every observed generation, in order.
+    pub fn history(&self) -> &[PopulationStats<F>] {
+        &self.history
+    }
+}
+
+impl<T, F> PopulationObserver<T, F> for SummaryStats<F>
+where
+    F: NumericFitness + Debug,
+{
+    fn observe(&mut self, _: u64, _: &[T], fitness: &[F]) {
+        if let Some(stats) = PopulationStats::from_fitness(fitness.to_vec()) {
+            self.history.push(stats);
+        }
+    }
+}
+
+/// Keeps the best individual seen so far, and the history of its fitness.
+///
+/// Unlike the best individual of the final population, this individual
+/// cannot be lost to the replacement policy.
+#[derive(Clone, Debug)]
+pub struct BestSoFar<T, F> {
+    best: Option<(T, F)>,
+    history: Vec<F>,
+}
+
+impl<T, F> BestSoFar<T, F> {
+    /// Create an observer that has not seen any individual yet.
+    pub fn new() -> BestSoFar<T, F> {
+        BestSoFar {
+            best: None,
+            history: Vec::new(),
+        }
+    }
+
+    /// Get the best individual seen so far, or `None` if nothing was observed.
+    pub fn best(&self) -> Option<&T> {
+        self.best.as_ref().map(|b| &b.0)
+    }
+
+    /// Get the fitness of the best individual seen so far in every observed generation.
+    ///
+    /// The history never decreases.
+    pub fn history(&self) -> &[F] {
+        &self.history
+    }
+}
+
+impl<T, F> Default for BestSoFar<T, F> {
+    fn default() -> BestSoFar<T, F> {
+        BestSoFar::new()
+    }
+}
+
+impl<T, F> PopulationObserver<T, F> for BestSoFar<T, F>
+where
+    T: Clone + Debug,
+    F: Fitness + Debug,
+{
+    fn observe(&mut self, _: u64, population: &[T], fitness: &[F]) {
+        let generation_best = (0..fitness.len()).rev().max_by_key(|&i| &fitness[i]);
+        if let Some(i) = generation_best {
+            if self.best.as_ref().map_or(true, |b| fitness[i] > b.1) {
+                self.best = Some((population[i].clone(), fitness[i].clone()));
+            }
+        }
+        if let Some(ref b) = self.best {
+            self.history.push(b.1.clone());
+        }
+    }
+}
+
+/// Keeps the `capacity` best distinct individuals ever seen.
+///
+/// Individuals that survive several generations are only recorded once. The
+/// sequential simulator maintains one when it is enabled with
+/// `SimulatorBuilder::with_hall_of_fame`, but it can also be used as an observer.
+#[derive(Clone, Debug)]
+pub struct HallOfFame<T, F> {
+    capacity: usize,
+    members: Vec<(T, F)>,
+    same: fn(&T, &T) -> bool,
+}
+
+impl<T, F> HallOfFame<T, F>
+where
+    T: Phenotype<F>,
+    F: Fitness,
+{
+    /// Create an empty hall of fame that keeps at most `capacity` individuals.
+    pub fn new(capacity: usize) -> HallOfFame<T, F>
+    where
+        T: PartialEq,
+    {
+        HallOfFame {
+            capacity,
+            members: Vec::with_capacity(capacity),
+            same: T::eq,
+        }
+    }
+
+    /// Get the maximum number of individuals that are kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the individuals and their fitness, best first.
+    pub fn members(&self) -> &[(T, F)] {
+        &self.members
+    }
+
+    /// Get the best individual ever seen, or `None` if nothing was recorded.
+    pub fn best(&self) -> Option<&T> {
+        self.members.first().map(|m| &m.0)
+    }
+
+    /// Record every individual of a population, given the fitness of each one.
+    pub fn update(&mut self, population: &[T], fitness: &[F]) {
+        for (individual, f) in population.iter().zip(fitness) {
+            let full = self.members.len() >= self.capacity;
+            if full && self.members.last().map_or(true, |worst| *f <= worst.1) {
+                continue;
+            }
+            if self.members.iter().any(|m| (self.same)(&m.0, individual)) {
+                continue;
+            }
+            // Later individuals with the same fitness rank below earlier ones.
+            let i = self.members.partition_point(|m| m.1 >= *f);
+            self.members.insert(i, (individual.clone(), f.clone()));
+            self.members.truncate(self.capacity);
+        }
+    }
+}
+
+impl<T, F> PopulationObserver<T, F> for HallOfFame<T, F>
+where
+    T: Phenotype<F> + Debug,
+    F: Fitness + Debug,
+{
+    fn observe(&mut self, _: u64, population: &[T], fitness: &[F]) {
+        self.update(population, fitness);
+    }
+}
+
+/// Counts the fitness values of every generation in equal-width bins.
+///
+/// The bins cover a fixed range, so that the histograms of different generations
+/// can be compared. Values below or above the range are counted in the first or
+/// last bin.
+#[derive(Clone, Debug)]
+pub struct HistogramStats {
+    low: f64,
+    high: f64,
+    bins: usize,
+    history: Vec<Vec<usize>>,
+}
+
+impl HistogramStats {
+    /// Create an observer with `bins` bins that cover `[low, high]`.
+    ///
+    /// Returns an error if `bins` is zero or if `low` is not less than `high`.
+    pub fn new(low: f64, high: f64, bins: usize) -> Result<HistogramStats, Error> {
+        if bins == 0 {
+            return Err(Error::InvalidConfig(String::from(
+                "A histogram needs at least one bin.",
+            )));
+        }
+        if low.partial_cmp(&high) != Some(Ordering::Less) {
+            return Err(Error::InvalidConfig(format!(
+                "The range [{}, {}] of a histogram must not be empty.",
+                low, high
+            )));
+        }
+        Ok(HistogramStats {
+            low,
+            high,
+            bins,
+            history: Vec::new(),
+        })
+    }
+
+    /// Get the lower bound of every bin, followed by the upper bound of the last bin.
+    pub fn edges(&self) -> Vec<f64> {
+        let width = (self.high - self.low) / self.bins as f64;
+        (0..=self.bins)
+            .map(|i| self.low + i as f64 * width)
+            .collect()
+    }
+
+    /// Get the number of individuals in every bin, for every observed generation.
+    pub fn history(&self) -> &[Vec<usize>] {
+        &self.history
+    }
+}
+
+impl<T, F> PopulationObserver<T, F> for HistogramStats
+where
+    F: NumericFitness,
+{
+    fn observe(&mut self, _: u64, _: &[T], fitness: &[F]) {
+        let mut counts = vec![0; self.bins];
+        let scale = self.bins as f64 / (self.high - self.low);
+        for f in fitness {
+            let bin = ((f.to_f64() - self.low) * scale).max(0.0) as usize;
+            counts[bin.min(self.bins - 1)] += 1;
+        }
+        self.history.push(counts);
+    }
+}
+
+/// A value with a fixed byte encoding, which `RunDigest` hashes.
+///
+/// Unlike `Hash`, the encoding must not depend on the platform or the Rust version,
+/// so that stored digests remain valid. The implementations for numbers use their
+/// little-endian byte order, with `isize` and `usize` widened to 64 bits.
+pub trait DigestEncode {
+    /// Append the encoding of this value to `bytes`.
+    fn encode(&self, bytes: &mut Vec<u8>);
+}
+
+macro_rules! impl_digest_encode {
+    ($($t:ty),*) => {
+        $(
+            impl DigestEncode for $t {
+                fn encode(&self, bytes: &mut Vec<u8>) {
+                    bytes.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_digest_encode!(i8, i16, i32, i64, u8, u16, u32, u64);
+
+impl DigestEncode for isize {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        (*self as i64).encode(bytes);
+    }
+}
+
+impl DigestEncode for usize {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        (*self as u64).encode(bytes);
+    }
+}
+
+impl DigestEncode for f32 {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.to_bits().encode(bytes);
+    }
+}
+
+impl DigestEncode for f64 {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.to_bits().encode(bytes);
+    }
+}
+
+/// A compact digest of a run: one hash of the best fitness value per generation.
+///
+/// Add it to a sequential simulator with `SimulatorBuilder::with_observer`. Only runs
+/// that are reproducible, e.g. because the simulator was seeded with
+/// `SimulatorBuilder::with_seed`, produce the same digest twice. The digest can be
+/// stored as a string using its `Display` implementation, and read back with
+/// `str::parse`.
+///
+/// ```
+/// use rsgenetic::stats::RunDigest;
+///
+/// let mut golden = RunDigest::new();
+/// for best in &[3i64, 5, 8] {
+///     golden.record(best);
+/// }
+/// let stored = golden.to_string();
+///
+/// let mut digest = RunDigest::new();
+/// for best in &[3i64, 5, 9] {
+///     digest.record(best);
+/// }
+/// let golden: RunDigest = stored.parse().unwrap();
+/// assert_eq!(golden.first_difference(&digest), Some(2));
+/// assert!(golden.verify(&digest).is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunDigest {
+    hashes: Vec<u64>,
+}
+
+impl RunDigest {
+    /// Create an empty digest.
+    pub fn new() -> RunDigest {
+        RunDigest { hashes: Vec::new() }
+    }
+
+    /// Record the best fitness value of the next generation.
+    ///
+    /// The value is hashed with FNV-1a over its `DigestEncode` encoding, so that
+    /// digests remain comparable between platforms and Rust versions.
+    pub fn record<F>(&mut self, best: &F)
+    where
+        F: DigestEncode + ?Sized,
+    {
+        let mut bytes = Vec::new();
+        best.encode(&mut bytes);
+        self.hashes.push(fnv1a(&bytes));
+    }
+
+    /// Get the number of recorded generations.
+    pub fn generations(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Get the first generation in which the two digests differ, or `None` if
+    /// they are equal. A generation missing from one of the digests counts as
+    /// a difference.
+    pub fn first_difference(&self, other: &RunDigest) -> Option<usize> {
+        match self
+            .hashes
+            .iter()
+            .zip(&other.hashes)
+            .position(|(a, b)| a != b)
+        {
+            Some(i) => Some(i),
+            None if self.hashes.len() != other.hashes.len() => {
+                Some(self.hashes.len().min(other.hashes.len()))
+            }
+            None => None,
+        }
+    }
+
+    /// Check that `run` matches this digest.
+    ///
+    /// Returns `Error::DigestMismatch` with the first generation that differs.
+    pub fn verify(&self, run: &RunDigest) -> Result<(), Error> {
+        match self.first_difference(run) {
+            Some(i) => Err(Error::DigestMismatch(i)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T, F> PopulationObserver<T, F> for RunDigest
+where
+    F: Fitness + DigestEncode,
+{
+    fn observe(&mut self, _: u64, _: &[T], fitness: &[F]) {
+        if let Some(best) = fitness.iter().max() {
+            self.record(best);
+        }
+    }
+}
+
+impl fmt::Display for RunDigest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, hash) in self.hashes.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{:016x}", hash)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RunDigest {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<RunDigest, Error> {
+        if s.is_empty() {
+            return Ok(RunDigest::new());
+        }
+        let hashes = s
+            .split(',')
+            .map(|h| {
+                u64::from_str_radix(h, 16)
+                    .map_err(|_| Error::InvalidDigest(format!("Invalid hash: {}", h)))
+            })
+            .collect::<Result<Vec<u64>, Error>>()?;
+        Ok(RunDigest { hashes })
+    }
+}
+
+/// Compute the 64 bit FNV-1a hash of `bytes`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        fnv1a, genotype_entropy, AlleleFrequencies, BestSoFar, CacheStats, HallOfFame,
+        HistogramStats, OperatorCounts, PopulationStats, RunDigest, SummaryStats,
+    };
+    use error::Error;
+    use pheno::{CachedFitness, FitnessCache, Phenotype};
+    use sim::select::*;
+    use sim::*;
+    use std::sync::Arc;
+    use test::{Genes, MyFitness, Test};
+
+    #[test]
+    fn test_empty() {
+        let population: Vec<Test> = Vec::new();
+        assert!(PopulationStats::from_population(&population).is_none());
+    }
+
+    #[test]
+    fn test_stats() {
+        let population: Vec<Test> = (0..101).map(|i| Test { f: i }).collect();
+        let stats = PopulationStats::from_population(&population).unwrap();
+        assert_eq!(stats.size, 101);
+        assert_eq!(stats.best, MyFitness { f: 100 });
+        assert_eq!(stats.worst, MyFitness { f: 0 });
+        assert_eq!(stats.lower_quartile, MyFitness { f: 25 });
+        assert_eq!(stats.median, MyFitness { f: 50 });
+        assert_eq!(stats.upper_quartile, MyFitness { f: 75 });
+        assert!((stats.mean - 50.0).abs() < 1e-9);
+        assert!((stats.std_dev - (850.0f64).sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_single() {
+        let stats = PopulationStats::from_fitness(vec![7i32]).unwrap();
+        assert_eq!(stats.best, 7);
+        assert_eq!(stats.median, 7);
+        assert_eq!(stats.std_dev, 0.0);
+    }
+
+    #[test]
+    fn test_operator_rates() {
+        let counts = OperatorCounts {
+            children: 4,
+            crossovers: 1,
+            mutations: 4,
+        };
+        assert_eq!(counts.crossover_rate(), Some(0.25));
+        assert_eq!(counts.mutation_rate(), Some(1.0));
+        assert_eq!(OperatorCounts::default().crossover_rate(), None);
+    }
+
+    #[test]
+    fn test_genotype_entropy() {
+        let converged: Vec<Genes> = (0..10)
+            .map(|_| Genes {
+                genes: vec![1, 0, 1],
+            })
+            .collect();
+        assert_eq!(genotype_entropy(&converged), 0.0);
+        let population: Vec<Genes> = (0..10)
+            .map(|i| Genes {
+                genes: vec![i % 2, 0],
+            })
+            .collect();
+        // One locus with one bit of entropy, one locus without any.
+        assert!((genotype_entropy(&population) - 0.5).abs() < 1e-9);
+        assert_eq!(genotype_entropy::<Genes>(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_allele_frequencies() {
+        let mut freqs = AlleleFrequencies::new();
+        let population: Vec<Genes> = (0..4)
+            .map(|i| Genes {
+                genes: vec![i % 2, 1],
+            })
+            .collect();
+        freqs.record(&population);
+        freqs.record(&[Genes { genes: vec![1, 1] }]);
+        assert_eq!(freqs.generations(), 2);
+        assert_eq!(freqs.matrix(&1), vec![vec![0.5, 1.0], vec![1.0, 1.0]]);
+        assert_eq!(freqs.matrix(&0), vec![vec![0.5, 0.0], vec![0.0, 0.0]]);
+        assert_eq!(freqs.fixed_loci(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_cache_stats() {
+        let cache = Arc::new(FitnessCache::new());
+        let a = CachedFitness::new(&cache, Test { f: 2 });
+        let b = CachedFitness::new(&cache, Test { f: -2 });
+        assert_eq!(cache.stats().hit_rate(), None);
+        assert_eq!(a.fitness(), MyFitness { f: 2 });
+        assert_eq!(a.fitness(), MyFitness { f: 2 });
+        assert_eq!(b.fitness(), MyFitness { f: 2 });
+        assert_eq!(a.crossover(&b).inner().f, -2);
+        assert_eq!(a.crossover(&b).fitness(), MyFitness { f: 2 });
+        assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 2 });
+        assert_eq!(cache.stats().hit_rate(), Some(0.5));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_cache_in_simulator() {
+        let cache = Arc::new(FitnessCache::new());
+        let mut population: Vec<_> = (0..100)
+            .map(|i| CachedFitness::new(&cache, Test { f: i % 10 }))
+            .collect();
+        let mut builder = seq::Simulator::builder(&mut population);
+        builder
+            .with_selector(Box::new(StochasticSelector::new(10)))
+            .with_max_iters(20);
+        builder.build().run();
+        let stats = cache.stats();
+        // Only values in 0..10 can ever occur.
+        assert!(stats.misses <= 10);
+        assert!(stats.hits > 0);
+    }
+
+    #[test]
+    fn test_run_digest() {
+        let run = |n: i64, seed: u64| {
+            let mut population: Vec<Test> = (0..n).map(|i| Test { f: i }).collect();
+            let mut digest = RunDigest::new();
+            {
+                let mut builder = seq::Simulator::builder(&mut population);
+                builder
+                    .with_selector(Box::new(StochasticSelector::new(10)))
+                    .with_observer(Box::new(&mut digest))
+                    .with_seed(seed)
+                    .with_max_iters(5);
+                builder.build().run();
+            }
+            digest
+        };
+        let golden = run(100, 42);
+        assert_eq!(golden.generations(), 5);
+        assert_eq!(golden.verify(&run(100, 42)), Ok(()));
+        assert_eq!(golden.verify(&run(50, 42)), Err(Error::DigestMismatch(0)));
+        assert_eq!(golden.to_string().parse::<RunDigest>(), Ok(golden.clone()));
+        assert_eq!("".parse::<RunDigest>(), Ok(RunDigest::new()));
+        assert!(matches!(
+            "xyz".parse::<RunDigest>(),
+            Err(Error::InvalidDigest(_))
+        ));
+    }
+
+    #[test]
+    fn test_digest_encode() {
+        // The encoding is fixed, so the hash of a value never changes.
+        let mut digest = RunDigest::new();
+        digest.record(&1i64);
+        digest.record(&1.5f64);
+        assert_eq!(
+            digest.to_string(),
+            format!(
+                "{:016x},{:016x}",
+                fnv1a(&[1, 0, 0, 0, 0, 0, 0, 0]),
+                fnv1a(&[0, 0, 0, 0, 0, 0, 0xf8, 0x3f])
+            )
+        );
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_collectors() {
+        let mut summary = SummaryStats::new();
+        let mut best = BestSoFar::new();
+        let mut histogram = HistogramStats::new(0.0, 100.0, 4).unwrap();
+        let mut population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        {
+            let mut builder = seq::Simulator::builder(&mut population);
+            builder
+                .with_selector(Box::new(StochasticSelector::new(10)))
+                .with_observer(Box::new(&mut summary))
+                .with_observer(Box::new(&mut best))
+                .with_observer(Box::new(&mut histogram))
+                .with_max_iters(5);
+            builder.build().run();
+        }
+        assert_eq!(summary.history().len(), 5);
+        assert!(summary.history().iter().all(|s| s.size == 100));
+        assert_eq!(best.history().len(), 5);
+        assert!(best.history().windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(best.best().unwrap().fitness(), best.history()[4]);
+        assert_eq!(histogram.edges(), vec![0.0, 25.0, 50.0, 75.0, 100.0]);
+        assert_eq!(histogram.history().len(), 5);
+        assert!(histogram
+            .history()
+            .iter()
+            .all(|h| h.iter().sum::<usize>() == 100));
+    }
+
+    #[test]
+    fn test_histogram_invalid() {
+        assert!(HistogramStats::new(0.0, 100.0, 0).is_err());
+        assert!(HistogramStats::new(1.0, 1.0, 4).is_err());
+        assert!(HistogramStats::new(0.0, f64::NAN, 4).is_err());
+    }
+
+    #[test]
+    fn test_hall_of_fame() {
+        let mut hall = HallOfFame::new(3);
+        let first: Vec<Test> = vec![1, -4, 2].into_iter().map(|f| Test { f }).collect();
+        let second: Vec<Test> = vec![-4, 4, 0, 3].into_iter().map(|f| Test { f }).collect();
+        for population in &[first, second] {
+            let fitness: Vec<MyFitness> = population.iter().map(|x| x.fitness()).collect();
+            hall.update(population, &fitness);
+        }
+        let members: Vec<i64> = hall.members().iter().map(|m| m.0.f).collect();
+        assert_eq!(members, vec![-4, 4, 3]);
+        assert_eq!(hall.best(), Some(&Test { f: -4 }));
+    }
+
+    #[test]
+    fn test_hall_of_fame_given_fitness() {
+        let mut hall = HallOfFame::new(2);
+        let population: Vec<Test> = (0..3).map(|f| Test { f }).collect();
+        // The given fitness is recorded, without evaluating the individuals again.
+        let fitness: Vec<MyFitness> = vec![7, 9, 8].into_iter().map(|f| MyFitness { f }).collect();
+        hall.update(&population, &fitness);
+        let members: Vec<(i64, i64)> = hall.members().iter().map(|m| (m.0.f, m.1.f)).collect();
+        assert_eq!(members, vec![(1, 9), (2, 8)]);
+    }
+}
@@ -18,9 +18,13 @@
 // several tests across the library.
 
 use pheno::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use stats::DigestEncode;
 use std::cmp;
 
 #[derive(Clone, Copy, Debug, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MyFitness {
     pub f: i64,
 }
@@ -37,7 +41,20 @@ impl Fitness for MyFitness {
     }
 }
 
-#[derive(Clone, Copy)]
+impl DigestEncode for MyFitness {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.f.encode(bytes);
+    }
+}
+
+impl NumericFitness for MyFitness {
+    fn to_f64(&self) -> f64 {
+        self.f as f64
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Test {
     pub f: i64,
 }
@@ -63,3 +80,42 @@ impl Phenotype<MyFitness> for Test {
         }
     }
 }
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Genes {
+    pub genes: Vec<i64>,
+}
+
+impl Phenotype<MyFitness> for Genes {
+    fn fitness(&self) -> MyFitness {
+        MyFitness {
+            f: self.genes.iter().sum(),
+        }
+    }
+
+    fn crossover(&self, other: &Genes) -> Genes {
+        let mut genes = self.genes.clone();
+        let half = genes.len() / 2;
+        genes.truncate(half);
+        genes.extend_from_slice(&other.genes[half..]);
+        Genes { genes }
+    }
+
+    fn mutate(&self) -> Genes {
+        Genes {
+            genes: self.genes.iter().map(|x| x + 1).collect(),
+        }
+    }
+}
+
+impl Genome for Genes {
+    type Gene = i64;
+
+    fn genes(&self) -> &[i64] {
+        &self.genes
+    }
+
+    fn with_genes(&self, genes: Vec<i64>) -> Genes {
+        Genes { genes }
+    }
+}